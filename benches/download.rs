@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Download throughput for servers streaming chunks of different sizes
+//!
+//! Run with `cargo bench --bench download`. Small chunks should come close
+//! to the throughput of large ones, as they are coalesced before hashing,
+//! writing and progress reporting.
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use podpull::http::ByteStream;
+use podpull::{
+    DownloadContext, Enclosure, Episode, HashAlgorithm, HttpClient, HttpResponse, NoopReporter,
+    download_episode,
+};
+use url::Url;
+
+const TOTAL_BYTES: usize = 64 * 1024 * 1024;
+
+/// Serves `TOTAL_BYTES` in chunks of a fixed size
+struct ChunkedClient {
+    chunk_size: usize,
+}
+
+#[async_trait]
+impl HttpClient for ChunkedClient {
+    async fn get_bytes(&self, _url: &str) -> Result<Bytes, reqwest::Error> {
+        unimplemented!("only streaming is benchmarked")
+    }
+
+    async fn get_stream(&self, _url: &str) -> Result<HttpResponse, reqwest::Error> {
+        let chunk = Bytes::from(vec![0u8; self.chunk_size]);
+        let chunks = (0..TOTAL_BYTES / self.chunk_size).map(move |_| Ok(chunk.clone()));
+        let body: ByteStream = Box::pin(futures::stream::iter(chunks));
+
+        Ok(HttpResponse {
+            status: 200,
+            content_length: Some(TOTAL_BYTES as u64),
+            final_url: None,
+            headers: BTreeMap::new(),
+            peer_certificate: None,
+            retry_after: None,
+            body,
+        })
+    }
+}
+
+fn episode() -> Episode {
+    Episode {
+        title: "Benchmark".to_string(),
+        description: None,
+        pub_date: None,
+        guid: None,
+        enclosure: Enclosure {
+            url: Url::parse("https://example.com/episode.mp3").unwrap(),
+            length: None,
+            mime_type: None,
+        },
+        alternate_enclosures: vec![],
+        transcripts: vec![],
+        duration: None,
+        episode_number: None,
+        season_number: None,
+        language: None,
+        extensions: BTreeMap::new(),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let context = DownloadContext {
+        download_id: 0,
+        episode_index: 0,
+        total_to_download: 1,
+        timeout: None,
+        bandwidth: None,
+        max_bytes: None,
+        fsync: false,
+        stall_timeout: None,
+        hash_algorithm: HashAlgorithm::Sha256,
+    };
+    let reporter = NoopReporter::shared();
+
+    for chunk_size in [1024, 4 * 1024, 64 * 1024, 1024 * 1024] {
+        let client = ChunkedClient { chunk_size };
+        let output_path = dir.path().join(format!("{}.mp3", chunk_size));
+
+        let start = Instant::now();
+        download_episode(&client, &episode(), &output_path, &context, &reporter)
+            .await
+            .unwrap();
+        let elapsed = start.elapsed();
+
+        println!(
+            "{:>8} byte chunks: {:>7.1} MiB/s",
+            chunk_size,
+            TOTAL_BYTES as f64 / 1024.0 / 1024.0 / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,457 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Minimal gettext-style translation layer for CLI output
+//!
+//! Messages are looked up by their English text (the "msgid"). Untranslated
+//! messages fall back to English, so adding a new string never breaks output.
+//! Placeholders use `{name}` syntax and are filled in by [`tr!`].
+
+use std::sync::OnceLock;
+
+/// Languages with a bundled message catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    /// Map a POSIX locale string like `de_DE.UTF-8` to a supported language
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        match code.as_str() {
+            "en" | "c" | "posix" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    /// Detect the language from `LC_ALL`, `LC_MESSAGES` and `LANG` (in that order)
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_locale(&value))
+            .unwrap_or(Language::English)
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::German => GERMAN,
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Select the language used for all subsequent translations
+///
+/// Only the first call has an effect. Without a call the language is
+/// detected from the environment on first use.
+pub fn init(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+fn current() -> Language {
+    *LANGUAGE.get_or_init(Language::detect)
+}
+
+/// Translate a message into the current language
+pub fn translate(msgid: &'static str) -> &'static str {
+    lookup(current(), msgid)
+}
+
+/// Translate a message with singular and plural forms
+pub fn translate_plural(
+    singular: &'static str,
+    plural: &'static str,
+    count: usize,
+) -> &'static str {
+    translate(if count == 1 { singular } else { plural })
+}
+
+fn lookup(language: Language, msgid: &'static str) -> &'static str {
+    language
+        .catalog()
+        .iter()
+        .find(|(id, _)| *id == msgid)
+        .map(|(_, translation)| *translation)
+        .unwrap_or(msgid)
+}
+
+/// Replace `{name}` placeholders in a translated template
+///
+/// Values are inserted verbatim, so placeholders within them stay as they
+/// are; unknown placeholders are kept too.
+pub fn interpolate(template: &str, args: &[(&str, String)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let (_, value) = args.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                result.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Translate a message and fill in its named placeholders
+///
+/// ```ignore
+/// tr!("Output: {path}", path = dir.display())
+/// ```
+macro_rules! tr {
+    ($msgid:literal) => {
+        $crate::cli::i18n::translate($msgid)
+    };
+    ($msgid:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::cli::i18n::interpolate(
+            $crate::cli::i18n::translate($msgid),
+            &[$((stringify!($name), $value.to_string())),+],
+        )
+    };
+}
+
+pub(crate) use tr;
+
+const GERMAN: &[(&str, &str)] = &[
+    ("- Podcast Downloader", "- Podcast-Downloader"),
+    ("Fetching feed: {url}", "Lade Feed: {url}"),
+    ("Parsing feed: {source}", "Verarbeite Feed: {source}"),
+    ("Feed moved to {url}", "Feed umgezogen nach {url}"),
+    (
+        "Failed to read library directory",
+        "Bibliotheksverzeichnis konnte nicht gelesen werden",
+    ),
+    (
+        "All podcast directories match their titles",
+        "Alle Podcast-Verzeichnisse passen zu ihren Titeln",
+    ),
+    (
+        "Would rename {from} to {to}",
+        "Würde {from} in {to} umbenennen",
+    ),
+    ("Renamed {from} to {to}", "{from} in {to} umbenannt"),
+    (
+        "Downloads were refused, fetched the feed again for fresh links",
+        "Downloads wurden abgelehnt, Feed für neue Links erneut abgerufen",
+    ),
+    (
+        "Podcast renamed from {old} to {new}, run `podpull rename-library` to rename its directory",
+        "Podcast umbenannt von {old} in {new}, `podpull rename-library` benennt das Verzeichnis um",
+    ),
+    (
+        "Feed unchanged since the last sync, nothing to download",
+        "Feed seit der letzten Synchronisierung unverändert, nichts herunterzuladen",
+    ),
+    (
+        "Scanning existing episodes...",
+        "Durchsuche vorhandene Episoden...",
+    ),
+    (
+        "{title} • {total} total, {new} new",
+        "{title} • {total} gesamt, {new} neu",
+    ),
+    (
+        "{title} • {total} total, {new} new, downloading {count}",
+        "{title} • {total} gesamt, {new} neu, lade {count} herunter",
+    ),
+    (
+        "Cleaned up {count} interrupted download",
+        "{count} abgebrochenen Download bereinigt",
+    ),
+    (
+        "Cleaned up {count} interrupted downloads",
+        "{count} abgebrochene Downloads bereinigt",
+    ),
+    (
+        "Adopted {count} existing file",
+        "{count} vorhandene Datei übernommen",
+    ),
+    (
+        "Adopted {count} existing files",
+        "{count} vorhandene Dateien übernommen",
+    ),
+    (
+        "Holding back {count} episode that failed recently (--retry-failed tries it now)",
+        "Halte {count} kürzlich fehlgeschlagene Episode zurück (--retry-failed versucht sie jetzt)",
+    ),
+    (
+        "Holding back {count} episodes that failed recently (--retry-failed tries them now)",
+        "Halte {count} kürzlich fehlgeschlagene Episoden zurück (--retry-failed versucht sie jetzt)",
+    ),
+    (
+        "Archived episode no longer in the feed: {title}",
+        "Nicht mehr im Feed enthaltene Episode archiviert: {title}",
+    ),
+    (
+        "Deleted episode no longer in the feed: {title}",
+        "Nicht mehr im Feed enthaltene Episode gelöscht: {title}",
+    ),
+    (
+        "Deleted audio beyond the retention limits: {title}",
+        "Audio jenseits der Aufbewahrungsgrenzen gelöscht: {title}",
+    ),
+    ("size unknown", "Größe unbekannt"),
+    (
+        "Would download {title} ({size})",
+        "Würde {title} herunterladen ({size})",
+    ),
+    (
+        "Would skip over the limit: {title}",
+        "Würde über der Begrenzung überspringen: {title}",
+    ),
+    (
+        "Would wait for its release: {title}",
+        "Würde auf die Veröffentlichung warten: {title}",
+    ),
+    (
+        "Would hold back after recent failures: {title}",
+        "Würde nach kürzlichen Fehlschlägen zurückhalten: {title}",
+    ),
+    (
+        "Would remove episode no longer in the feed: {title}",
+        "Würde nicht mehr im Feed enthaltene Episode entfernen: {title}",
+    ),
+    ("{count} to download", "{count} herunterzuladen"),
+    ("about {size}", "etwa {size}"),
+    ("{count} of unknown size", "{count} mit unbekannter Größe"),
+    ("{count} held back", "{count} zurückgehalten"),
+    ("{count} to prune", "{count} zu entfernen"),
+    ("Dry run:", "Probelauf:"),
+    ("Sync complete:", "Synchronisierung abgeschlossen:"),
+    ("{count} downloaded", "{count} heruntergeladen"),
+    ("{count} existing", "{count} vorhanden"),
+    ("{count} limited", "{count} begrenzt"),
+    ("{count} scheduled", "{count} geplant"),
+    ("{count} failed", "{count} fehlgeschlagen"),
+    ("Failed episodes:", "Fehlgeschlagene Episoden:"),
+    ("Output: {path}", "Ausgabe: {path}"),
+    (
+        "Failed to sync podcast",
+        "Podcast konnte nicht synchronisiert werden",
+    ),
+    (
+        "Healthy, last sync at {time}",
+        "Funktionsfähig, letzte Synchronisierung am {time}",
+    ),
+    ("Unhealthy: {reason}", "Nicht funktionsfähig: {reason}"),
+    (
+        "Failed to read subscriptions",
+        "Abonnements konnten nicht gelesen werden",
+    ),
+    ("Failed podcasts:", "Fehlgeschlagene Podcasts:"),
+    (
+        "Failed to read feed list",
+        "Feed-Liste konnte nicht gelesen werden",
+    ),
+    (
+        "Skipping duplicate feed {url} (same as {kept})",
+        "Überspringe doppelten Feed {url} (wie {kept})",
+    ),
+    (
+        "Retry {attempt}/{max} in {seconds}s: {title}",
+        "Versuch {attempt}/{max} in {seconds} s: {title}",
+    ),
+    ("Skipped video: {title}", "Video übersprungen: {title}"),
+    (
+        "Skipped, larger than {limit}: {title}",
+        "Übersprungen, größer als {limit}: {title}",
+    ),
+    (
+        "{title} is {received}, but the feed says {declared}",
+        "{title} ist {received} groß, laut Feed aber {declared}",
+    ),
+    (
+        "Trying again later: {title} - {error}",
+        "Später erneuter Versuch: {title} - {error}",
+    ),
+    (
+        "Throttled by server, retry {attempt}/{max} in {seconds}s: {title}",
+        "Vom Server gedrosselt, Versuch {attempt}/{max} in {seconds} s: {title}",
+    ),
+    (
+        "Failed to create HTTP client",
+        "HTTP-Client konnte nicht erstellt werden",
+    ),
+    (
+        "Failed to read segment file",
+        "Segmentdatei konnte nicht gelesen werden",
+    ),
+    (
+        "Failed to import segments",
+        "Segmente konnten nicht importiert werden",
+    ),
+    (
+        "Failed to verify episodes",
+        "Episoden konnten nicht geprüft werden",
+    ),
+    ("Damaged: {path}", "Beschädigt: {path}"),
+    ("Missing: {path}", "Fehlt: {path}"),
+    ("Unreadable metadata: {path}", "Unlesbare Metadaten: {path}"),
+    (
+        "{verified} episodes intact, {damaged} damaged, {missing} missing, {unhashed} without hash",
+        "{verified} Episoden intakt, {damaged} beschädigt, {missing} fehlen, {unhashed} ohne Prüfsumme",
+    ),
+    (
+        "Failed to export state",
+        "Zustand konnte nicht exportiert werden",
+    ),
+    (
+        "Failed to import state",
+        "Zustand konnte nicht importiert werden",
+    ),
+    (
+        "Imported {imported} episodes, kept {existing} already present",
+        "{imported} Episoden importiert, {existing} bereits vorhandene beibehalten",
+    ),
+    (
+        "Stored skip segments for {count} of {total} episodes",
+        "Überspringbare Abschnitte für {count} von {total} Episoden gespeichert",
+    ),
+    (
+        "Failed to read podcast metadata",
+        "Podcast-Metadaten konnten nicht gelesen werden",
+    ),
+    (
+        "Failed to read feed {feed}",
+        "Feed {feed} konnte nicht gelesen werden",
+    ),
+    (
+        "Failed to read podcast settings",
+        "Podcast-Einstellungen konnten nicht gelesen werden",
+    ),
+    (
+        "Failed to repair episodes",
+        "Episoden konnten nicht repariert werden",
+    ),
+    (
+        "Set aside unreadable metadata: {path}",
+        "Unlesbare Metadaten beiseitegelegt: {path}",
+    ),
+    (
+        "Removed metadata of missing audio: {path}",
+        "Metadaten fehlender Audiodatei entfernt: {path}",
+    ),
+    (
+        "No episode found for: {path}",
+        "Keine Episode gefunden für: {path}",
+    ),
+    (
+        "{rebuilt} metadata files rebuilt, {rehashed} hashed, {orphaned} removed, {corrupt} set aside",
+        "{rebuilt} Metadatendateien neu erstellt, {rehashed} mit Prüfsumme versehen, {orphaned} entfernt, {corrupt} beiseitegelegt",
+    ),
+    (
+        "Failed to export subscriptions",
+        "Abonnements konnten nicht exportiert werden",
+    ),
+    (
+        "Failed to export episodes",
+        "Episoden konnten nicht exportiert werden",
+    ),
+    (
+        "Failed to read {path}",
+        "{path} konnte nicht gelesen werden",
+    ),
+    (
+        "Failed to write {path}",
+        "{path} konnte nicht geschrieben werden",
+    ),
+    (
+        "Synced {synced} of {total} podcasts into {path}",
+        "{synced} von {total} Podcasts nach {path} synchronisiert",
+    ),
+    (
+        "{downloaded} episodes downloaded, {failed} failed",
+        "{downloaded} Episoden heruntergeladen, {failed} fehlgeschlagen",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_locale_parses_posix_locales() {
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), Some(Language::German));
+        assert_eq!(Language::from_locale("de"), Some(Language::German));
+        assert_eq!(Language::from_locale("en_US"), Some(Language::English));
+        assert_eq!(Language::from_locale("C"), Some(Language::English));
+        assert_eq!(Language::from_locale("fr_FR.UTF-8"), None);
+    }
+
+    #[test]
+    fn lookup_falls_back_to_msgid() {
+        assert_eq!(lookup(Language::German, "Untranslated"), "Untranslated");
+        assert_eq!(
+            lookup(Language::English, "Sync complete:"),
+            "Sync complete:"
+        );
+    }
+
+    #[test]
+    fn lookup_finds_translation() {
+        assert_eq!(
+            lookup(Language::German, "Failed episodes:"),
+            "Fehlgeschlagene Episoden:"
+        );
+    }
+
+    #[test]
+    fn interpolate_replaces_named_placeholders() {
+        let result = interpolate(
+            "{title} • {total} total",
+            &[("title", "Show".to_string()), ("total", "3".to_string())],
+        );
+        assert_eq!(result, "Show • 3 total");
+    }
+
+    #[test]
+    fn interpolate_keeps_placeholders_in_values() {
+        let result = interpolate(
+            "{title} • {total} total {unknown}",
+            &[
+                ("title", "{total} Tips".to_string()),
+                ("total", "3".to_string()),
+            ],
+        );
+        assert_eq!(result, "{total} Tips • 3 total {unknown}");
+    }
+
+    #[test]
+    fn every_german_template_keeps_its_placeholders() {
+        for (msgid, translation) in GERMAN {
+            for placeholder in msgid.split('{').skip(1) {
+                let name = placeholder.split('}').next().unwrap();
+                assert!(
+                    translation.contains(&format!("{{{}}}", name)),
+                    "translation of {:?} lacks {{{}}}",
+                    msgid,
+                    name
+                );
+            }
+        }
+    }
+}
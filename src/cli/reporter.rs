@@ -0,0 +1,686 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use colored::Colorize;
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
+
+use podpull::{ProgressEvent, ProgressReporter};
+
+use super::i18n::{self, tr};
+use super::style::OutputStyle;
+
+/// Progress reporter using indicatif for terminal output
+pub struct IndicatifReporter {
+    style: OutputStyle,
+    multi: MultiProgress,
+    bars: Mutex<HashMap<usize, ProgressBar>>,
+    main_bar: ProgressBar,
+}
+
+impl IndicatifReporter {
+    pub fn new(style: OutputStyle) -> Self {
+        let multi = MultiProgress::new();
+
+        let main_bar = multi.add(ProgressBar::new_spinner());
+        main_bar.set_style(spinner_style(&style));
+        main_bar.enable_steady_tick(std::time::Duration::from_millis(100));
+
+        Self {
+            style,
+            multi,
+            bars: Mutex::new(HashMap::new()),
+            main_bar,
+        }
+    }
+
+    fn get_or_create_bar(&self, download_id: usize) -> ProgressBar {
+        let mut bars = self.bars.lock().unwrap();
+
+        if let Some(bar) = bars.get(&download_id) {
+            return bar.clone();
+        }
+
+        let style = ProgressStyle::default_bar()
+            .template(&format!(
+                "  {}[{{bar:30.cyan/blue}}] {{bytes}}/{{total_bytes}} {{wide_msg}}",
+                self.style.icons.saving
+            ))
+            .unwrap()
+            .progress_chars(self.style.progress_chars);
+
+        let bar = self.multi.add(ProgressBar::new(0));
+        bar.set_style(style);
+        bars.insert(download_id, bar.clone());
+        bar
+    }
+
+    fn finish_bar(&self, download_id: usize) {
+        let mut bars = self.bars.lock().unwrap();
+        if let Some(bar) = bars.remove(&download_id) {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl ProgressReporter for IndicatifReporter {
+    fn report(&self, event: ProgressEvent) {
+        let icons = &self.style.icons;
+
+        match event {
+            ProgressEvent::FetchingFeed { url } => {
+                self.main_bar.set_message(format!(
+                    "{}{}",
+                    icons.globe,
+                    tr!("Fetching feed: {url}", url = url.cyan())
+                ));
+            }
+
+            ProgressEvent::ParsingFeed { source } => {
+                self.main_bar.set_message(format!(
+                    "{}{}",
+                    icons.cog,
+                    tr!("Parsing feed: {source}", source = source.cyan())
+                ));
+            }
+
+            ProgressEvent::FeedMoved { new_url, .. } => {
+                // Printed permanently so the user can update their subscription
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.globe,
+                    tr!("Feed moved to {url}", url = new_url.yellow())
+                ));
+            }
+
+            ProgressEvent::TitleChanged {
+                old_title,
+                new_title,
+            } => {
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.folder,
+                    tr!(
+                        "Podcast renamed from {old} to {new}, run `podpull rename-library` to rename its directory",
+                        old = old_title.dimmed(),
+                        new = new_title.yellow()
+                    )
+                ));
+            }
+
+            ProgressEvent::FeedRefreshed { .. } => {
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.globe,
+                    tr!("Downloads were refused, fetched the feed again for fresh links")
+                ));
+            }
+
+            ProgressEvent::FeedNotModified { .. } => {
+                self.main_bar.finish_and_clear();
+                println!(
+                    "\n{}{}",
+                    icons.party,
+                    tr!("Feed unchanged since the last sync, nothing to download")
+                        .bold()
+                        .green()
+                );
+            }
+
+            ProgressEvent::ScanningDirectory {
+                files_scanned,
+                total_files,
+            } => {
+                if total_files == 0 {
+                    self.main_bar.set_message(format!(
+                        "{}{}",
+                        icons.search,
+                        tr!("Scanning existing episodes...")
+                    ));
+                } else {
+                    // Switch to progress bar style for scanning
+                    if files_scanned == 0 {
+                        let scan_style = ProgressStyle::default_bar()
+                            .template(&format!(
+                                "{{spinner:.green}} {}{} [{{bar:30.cyan/blue}}] {{pos}}/{{len}}",
+                                icons.search,
+                                tr!("Scanning existing episodes...")
+                            ))
+                            .unwrap()
+                            .tick_chars(self.style.spinner_chars)
+                            .progress_chars(self.style.progress_chars);
+                        self.main_bar.set_style(scan_style);
+                        self.main_bar.set_length(total_files as u64);
+                    }
+                    self.main_bar.set_position(files_scanned as u64);
+                }
+            }
+
+            ProgressEvent::SyncPlanReady {
+                podcast_title,
+                total_episodes,
+                new_episodes,
+                to_download,
+            } => {
+                // Reset to spinner style after scanning
+                self.main_bar.set_style(spinner_style(&self.style));
+                if new_episodes == to_download {
+                    // No limit applied or limit >= new
+                    self.main_bar.set_message(format!(
+                        "{}{}",
+                        icons.headphones,
+                        tr!(
+                            "{title} • {total} total, {new} new",
+                            title = podcast_title.bold().green(),
+                            total = total_episodes.to_string().cyan(),
+                            new = new_episodes.to_string().yellow()
+                        )
+                    ));
+                } else {
+                    // Limit applied
+                    self.main_bar.set_message(format!(
+                        "{}{}",
+                        icons.headphones,
+                        tr!(
+                            "{title} • {total} total, {new} new, downloading {count}",
+                            title = podcast_title.bold().green(),
+                            total = total_episodes.to_string().cyan(),
+                            new = new_episodes.to_string().yellow(),
+                            count = to_download.to_string().green()
+                        )
+                    ));
+                }
+            }
+
+            ProgressEvent::DownloadStarting {
+                download_id,
+                episode_title,
+                episode_index,
+                total_to_download,
+                content_length,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                bar.set_length(content_length.unwrap_or(0));
+                bar.set_position(0);
+                // Calculate width needed for "[idx/total]" part
+                let index_width =
+                    (episode_index + 1).to_string().len() + total_to_download.to_string().len();
+                let title_width = available_title_width(index_width);
+                bar.set_message(format!(
+                    "[{}/{}] {}",
+                    (episode_index + 1).to_string().cyan(),
+                    total_to_download.to_string().cyan(),
+                    truncate_title(&episode_title, title_width)
+                ));
+            }
+
+            ProgressEvent::DownloadProgress {
+                download_id,
+                bytes_downloaded,
+                total_bytes,
+                ..
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                if let Some(total) = total_bytes {
+                    bar.set_length(total);
+                }
+                bar.set_position(bytes_downloaded);
+            }
+
+            ProgressEvent::DownloadCompleted {
+                download_id,
+                episode_title,
+                bytes_downloaded,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                bar.set_position(bytes_downloaded);
+                // No index displayed, so use 0 for index_width calculation
+                let title_width = available_title_width(0);
+                bar.set_message(format!(
+                    "{}{}",
+                    icons.success,
+                    truncate_title(&episode_title, title_width).green()
+                ));
+                self.finish_bar(download_id);
+            }
+
+            ProgressEvent::DownloadRetrying {
+                download_id,
+                episode_title,
+                attempt,
+                max_retries,
+                delay,
+                ..
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                bar.set_position(0);
+                let title_width = available_title_width(0).saturating_sub(30);
+                bar.set_message(format!(
+                    "{}{}",
+                    icons.retry,
+                    tr!(
+                        "Retry {attempt}/{max} in {seconds}s: {title}",
+                        attempt = attempt,
+                        max = max_retries,
+                        seconds = delay.as_secs().max(1),
+                        title = truncate_title(&episode_title, title_width.max(20))
+                    )
+                    .yellow()
+                ));
+            }
+
+            ProgressEvent::DownloadThrottled {
+                download_id,
+                episode_title,
+                attempt,
+                max_retries,
+                delay,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                bar.set_position(0);
+                let title_width = available_title_width(0).saturating_sub(40);
+                bar.set_message(format!(
+                    "{}{}",
+                    icons.retry,
+                    tr!(
+                        "Throttled by server, retry {attempt}/{max} in {seconds}s: {title}",
+                        attempt = attempt,
+                        max = max_retries,
+                        seconds = delay.as_secs().max(1),
+                        title = truncate_title(&episode_title, title_width.max(20))
+                    )
+                    .yellow()
+                ));
+            }
+
+            ProgressEvent::DownloadRequeued {
+                download_id,
+                episode_title,
+                error,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                let title_width = available_title_width(0).saturating_sub(3 + 30);
+                bar.abandon_with_message(format!(
+                    "{}{}",
+                    icons.retry,
+                    tr!(
+                        "Trying again later: {title} - {error}",
+                        title = truncate_title(&episode_title, title_width.max(20)),
+                        error = error
+                    )
+                    .yellow()
+                ));
+                self.finish_bar(download_id);
+            }
+
+            ProgressEvent::DownloadFailed {
+                download_id,
+                episode_title,
+                error,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                // Reserve space for " - " and some error text (at least 30 chars)
+                let title_width = available_title_width(0).saturating_sub(3 + 30);
+                bar.abandon_with_message(format!(
+                    "{}{} - {}",
+                    icons.failure,
+                    truncate_title(&episode_title, title_width.max(20)).red(),
+                    error.red()
+                ));
+                self.finish_bar(download_id);
+            }
+
+            ProgressEvent::Finalizing { .. } => {
+                // Silent - the rename is fast
+            }
+
+            ProgressEvent::HashingCompleted { .. } => {
+                // Silent - hashing happens during download
+            }
+
+            ProgressEvent::SizeMismatch {
+                episode_title,
+                declared,
+                received,
+                ..
+            } => {
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.cross,
+                    tr!(
+                        "{title} is {received}, but the feed says {declared}",
+                        title = episode_title,
+                        received = HumanBytes(received),
+                        declared = HumanBytes(declared)
+                    )
+                    .yellow()
+                ));
+            }
+
+            ProgressEvent::EpisodeTooLarge {
+                download_id,
+                episode_title,
+                limit,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                let title_width = available_title_width(0).saturating_sub(40);
+                bar.abandon_with_message(format!(
+                    "{}{}",
+                    icons.cross,
+                    tr!(
+                        "Skipped, larger than {limit}: {title}",
+                        limit = HumanBytes(limit),
+                        title = truncate_title(&episode_title, title_width.max(20))
+                    )
+                    .yellow()
+                ));
+                self.finish_bar(download_id);
+            }
+
+            ProgressEvent::VideoSkipped {
+                download_id,
+                episode_title,
+            } => {
+                let bar = self.get_or_create_bar(download_id);
+                let title_width = available_title_width(0).saturating_sub(30);
+                bar.abandon_with_message(format!(
+                    "{}{}",
+                    icons.cross,
+                    tr!(
+                        "Skipped video: {title}",
+                        title = truncate_title(&episode_title, title_width.max(20))
+                    )
+                    .yellow()
+                ));
+                self.finish_bar(download_id);
+            }
+
+            ProgressEvent::PartialFilesCleanedUp { count } => {
+                if count > 0 {
+                    let template = i18n::translate_plural(
+                        "Cleaned up {count} interrupted download",
+                        "Cleaned up {count} interrupted downloads",
+                        count,
+                    );
+                    self.main_bar.set_message(format!(
+                        "{}{}",
+                        icons.broom,
+                        i18n::interpolate(
+                            template,
+                            &[("count", count.to_string().yellow().to_string())]
+                        )
+                    ));
+                }
+            }
+
+            ProgressEvent::DownloadsAdopted { count } => {
+                let template = i18n::translate_plural(
+                    "Adopted {count} existing file",
+                    "Adopted {count} existing files",
+                    count,
+                );
+                self.main_bar.set_message(format!(
+                    "{}{}",
+                    icons.folder,
+                    i18n::interpolate(
+                        template,
+                        &[("count", count.to_string().green().to_string())]
+                    )
+                ));
+            }
+
+            ProgressEvent::EpisodesBackedOff { count } => {
+                let template = i18n::translate_plural(
+                    "Holding back {count} episode that failed recently (--retry-failed tries it now)",
+                    "Holding back {count} episodes that failed recently (--retry-failed tries them now)",
+                    count,
+                );
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.retry,
+                    i18n::interpolate(
+                        template,
+                        &[("count", count.to_string().yellow().to_string())]
+                    )
+                ));
+            }
+
+            ProgressEvent::EpisodePruned {
+                episode_title,
+                archived,
+            } => {
+                let title = truncate_title(&episode_title, available_title_width(0).max(20));
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.broom,
+                    if archived {
+                        tr!(
+                            "Archived episode no longer in the feed: {title}",
+                            title = title
+                        )
+                    } else {
+                        tr!(
+                            "Deleted episode no longer in the feed: {title}",
+                            title = title
+                        )
+                    }
+                    .dimmed()
+                ));
+            }
+
+            ProgressEvent::EpisodeRetired { episode_title } => {
+                let title = truncate_title(&episode_title, available_title_width(0).max(20));
+                self.main_bar.println(format!(
+                    "{}{}",
+                    icons.broom,
+                    tr!(
+                        "Deleted audio beyond the retention limits: {title}",
+                        title = title
+                    )
+                    .dimmed()
+                ));
+            }
+
+            ProgressEvent::DryRunPlanned { report } => {
+                self.main_bar.finish_and_clear();
+
+                let title_width = available_title_width(0).max(20);
+                for episode in &report.to_download {
+                    let size = episode.bytes.map_or_else(
+                        || tr!("size unknown").to_string(),
+                        |bytes| HumanBytes(bytes).to_string(),
+                    );
+                    println!(
+                        "  {}",
+                        tr!(
+                            "Would download {title} ({size})",
+                            title = truncate_title(&episode.title, title_width),
+                            size = size
+                        )
+                    );
+                }
+                for title in &report.limited {
+                    println!(
+                        "  {}",
+                        tr!(
+                            "Would skip over the limit: {title}",
+                            title = truncate_title(title, title_width)
+                        )
+                        .dimmed()
+                    );
+                }
+                for title in &report.deferred {
+                    println!(
+                        "  {}",
+                        tr!(
+                            "Would wait for its release: {title}",
+                            title = truncate_title(title, title_width)
+                        )
+                        .dimmed()
+                    );
+                }
+                for title in &report.backed_off {
+                    println!(
+                        "  {}",
+                        tr!(
+                            "Would hold back after recent failures: {title}",
+                            title = truncate_title(title, title_width)
+                        )
+                        .dimmed()
+                    );
+                }
+                for episode in &report.pruned {
+                    println!(
+                        "  {}",
+                        tr!(
+                            "Would remove episode no longer in the feed: {title}",
+                            title = truncate_title(&episode.title, title_width)
+                        )
+                        .yellow()
+                    );
+                }
+
+                let mut parts = vec![
+                    tr!(
+                        "{count} to download",
+                        count = report.to_download.len().to_string().green().bold()
+                    ),
+                    tr!(
+                        "about {size}",
+                        size = HumanBytes(report.estimated_bytes()).to_string().bold()
+                    ),
+                ];
+                if report.unknown_sizes() > 0 {
+                    parts.push(tr!(
+                        "{count} of unknown size",
+                        count = report.unknown_sizes()
+                    ));
+                }
+                parts.push(tr!(
+                    "{count} existing",
+                    count = report.existing.to_string().yellow()
+                ));
+                if !report.limited.is_empty() {
+                    parts.push(tr!(
+                        "{count} limited",
+                        count = report.limited.len().to_string().cyan()
+                    ));
+                }
+                if !report.deferred.is_empty() {
+                    parts.push(tr!(
+                        "{count} scheduled",
+                        count = report.deferred.len().to_string().cyan()
+                    ));
+                }
+                if !report.backed_off.is_empty() {
+                    parts.push(tr!(
+                        "{count} held back",
+                        count = report.backed_off.len().to_string().yellow()
+                    ));
+                }
+                if !report.pruned.is_empty() {
+                    parts.push(tr!(
+                        "{count} to prune",
+                        count = report.pruned.len().to_string().yellow()
+                    ));
+                }
+
+                println!(
+                    "\n{}{} {}",
+                    icons.search,
+                    tr!("Dry run:").bold().cyan(),
+                    parts.join(", ")
+                );
+            }
+
+            ProgressEvent::SyncCompleted {
+                downloaded_count,
+                existing_count,
+                limited_count,
+                deferred_count,
+                failed_count,
+            } => {
+                self.main_bar.finish_and_clear();
+
+                let mut parts = vec![
+                    tr!(
+                        "{count} downloaded",
+                        count = downloaded_count.to_string().green().bold()
+                    ),
+                    tr!(
+                        "{count} existing",
+                        count = existing_count.to_string().yellow()
+                    ),
+                ];
+
+                if limited_count > 0 {
+                    parts.push(tr!(
+                        "{count} limited",
+                        count = limited_count.to_string().cyan()
+                    ));
+                }
+
+                if deferred_count > 0 {
+                    parts.push(tr!(
+                        "{count} scheduled",
+                        count = deferred_count.to_string().cyan()
+                    ));
+                }
+
+                parts.push(if failed_count > 0 {
+                    tr!(
+                        "{count} failed",
+                        count = failed_count.to_string().red().bold()
+                    )
+                } else {
+                    tr!("{count} failed", count = failed_count.to_string().green())
+                });
+
+                println!(
+                    "\n{}{} {}",
+                    icons.party,
+                    tr!("Sync complete:").bold().green(),
+                    parts.join(", ")
+                );
+            }
+        }
+    }
+}
+
+/// Spinner style for the main status line
+fn spinner_style(style: &OutputStyle) -> ProgressStyle {
+    ProgressStyle::default_bar()
+        .template("{spinner:.green} {wide_msg}")
+        .unwrap()
+        .tick_chars(style.spinner_chars)
+}
+
+fn truncate_title(title: &str, max_len: usize) -> String {
+    if title.len() <= max_len {
+        title.to_string()
+    } else {
+        format!("{}...", &title[..max_len.saturating_sub(3)])
+    }
+}
+
+/// Calculate available width for episode title in progress bar
+/// Layout: "  📥 [{bar:30}] XX.XX MiB/XX.XX MiB [idx/total] title"
+fn available_title_width(index_width: usize) -> usize {
+    let term_width = console::Term::stdout().size().1 as usize;
+
+    // Fixed parts:
+    // - "  " prefix: 2
+    // - emoji + space: 4 (📥 + space, accounting for unicode width)
+    // - "[" + "]": 2
+    // - bar: 30
+    // - " ": 1
+    // - bytes display "XX.XX MiB/XX.XX MiB": ~21 (max reasonable)
+    // - " ": 1
+    // - index "[idx/total] ": index_width + 4 brackets/slash + 1 space
+    let fixed_width = 2 + 4 + 2 + 30 + 1 + 21 + 1 + index_width + 4 + 1;
+
+    term_width.saturating_sub(fixed_width).max(20) // minimum 20 chars for title
+}
@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Output style (icons, colors, progress characters) for terminal output
+
+/// Status icons shown in front of progress and summary lines
+///
+/// Every icon includes its trailing padding so it can be prepended directly.
+#[derive(Debug, Clone, Copy)]
+pub struct Icons {
+    pub microphone: &'static str,
+    pub globe: &'static str,
+    pub cog: &'static str,
+    pub search: &'static str,
+    pub headphones: &'static str,
+    pub saving: &'static str,
+    pub success: &'static str,
+    pub failure: &'static str,
+    pub party: &'static str,
+    pub folder: &'static str,
+    pub cross: &'static str,
+    pub broom: &'static str,
+    pub retry: &'static str,
+}
+
+impl Icons {
+    /// Emoji icons for terminals with Unicode support
+    pub const EMOJI: Icons = Icons {
+        microphone: "🎙️  ",
+        globe: "🌐 ",
+        cog: "⚙️  ",
+        search: "🔍 ",
+        headphones: "🎧 ",
+        saving: "💾 ",
+        success: "✅ ",
+        failure: "❌ ",
+        party: "🎉 ",
+        folder: "📁 ",
+        cross: "✗ ",
+        broom: "🧹 ",
+        retry: "🔁 ",
+    };
+
+    /// Plain ASCII markers for terminals without emoji support
+    pub const ASCII: Icons = Icons {
+        microphone: "",
+        globe: "[w] ",
+        cog: "[*] ",
+        search: "[~] ",
+        headphones: "[i] ",
+        saving: "[v] ",
+        success: "[+] ",
+        failure: "[!] ",
+        party: "[*] ",
+        folder: "",
+        cross: "x ",
+        broom: "[c] ",
+        retry: "[r] ",
+    };
+}
+
+/// Complete description of how terminal output looks
+#[derive(Debug, Clone, Copy)]
+pub struct OutputStyle {
+    pub icons: Icons,
+    /// Whether ANSI colors are emitted
+    pub color: bool,
+    /// Characters for filled, current and empty progress bar cells
+    pub progress_chars: &'static str,
+    /// Spinner animation frames, the last one is shown when finished
+    pub spinner_chars: &'static str,
+}
+
+impl OutputStyle {
+    const UNICODE_PROGRESS: &'static str = "█▓░";
+    const ASCII_PROGRESS: &'static str = "#>-";
+    const UNICODE_SPINNER: &'static str = "⠁⠂⠄⡀⢀⠠⠐⠈ ";
+    const ASCII_SPINNER: &'static str = "|/-\\ ";
+
+    /// Resolve the style from command line flags and the environment
+    ///
+    /// Emoji are disabled by `--no-emoji` or when the terminal does not
+    /// support them. Colors are disabled by `--no-color` or a non-empty
+    /// `NO_COLOR` environment variable (see <https://no-color.org>).
+    pub fn resolve(no_emoji: bool, no_color: bool, ascii_progress: bool) -> Self {
+        let emoji = !no_emoji && console::Term::stdout().features().wants_emoji();
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+
+        Self {
+            icons: if emoji { Icons::EMOJI } else { Icons::ASCII },
+            color: !(no_color || no_color_env),
+            progress_chars: if ascii_progress {
+                Self::ASCII_PROGRESS
+            } else {
+                Self::UNICODE_PROGRESS
+            },
+            spinner_chars: if ascii_progress {
+                Self::ASCII_SPINNER
+            } else {
+                Self::UNICODE_SPINNER
+            },
+        }
+    }
+
+    /// Apply global settings (color output) for the `colored` and `console` crates
+    pub fn apply(&self) {
+        if !self.color {
+            colored::control::set_override(false);
+            console::set_colors_enabled(false);
+            console::set_colors_enabled_stderr(false);
+        }
+    }
+}
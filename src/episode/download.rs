@@ -3,17 +3,29 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use bytes::BytesMut;
+use chrono::Utc;
 use futures::StreamExt;
-use sha2::{Digest, Sha256};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
 use crate::error::DownloadError;
 use crate::feed::Episode;
-use crate::http::HttpClient;
+use crate::hash::HashAlgorithm;
+use crate::http::{BandwidthLimiter, HttpClient, within};
+use crate::metadata::DownloadReceipt;
 use crate::progress::{ProgressEvent, SharedProgressReporter};
 
+/// Small chunks are collected up to this size before being hashed, written
+/// and reported
+const FLUSH_SIZE: usize = 64 * 1024;
+
+/// Longest time small chunks are held back, so progress keeps moving on
+/// slow connections
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Context for tracking a download in concurrent scenarios
 #[derive(Debug, Clone)]
 pub struct DownloadContext {
@@ -23,6 +35,22 @@ pub struct DownloadContext {
     pub episode_index: usize,
     /// Total number of episodes to download
     pub total_to_download: usize,
+    /// Give up when the server takes longer than this to respond, or to
+    /// send the next chunk of the file (None = wait indefinitely)
+    pub timeout: Option<Duration>,
+    /// Limiter shared by all concurrent downloads (None = full speed)
+    pub bandwidth: Option<BandwidthLimiter>,
+    /// Abort with [`DownloadError::TooLarge`] once the file exceeds this
+    /// many bytes (None = no limit)
+    pub max_bytes: Option<u64>,
+    /// Force the file, and on Unix its directory entry, to disk before the
+    /// download counts as complete
+    pub fsync: bool,
+    /// Abort with [`DownloadError::Stalled`] when no data arrives for this
+    /// long once the transfer has started (None = use `timeout`)
+    pub stall_timeout: Option<Duration>,
+    /// Algorithm of the content hash
+    pub hash_algorithm: HashAlgorithm,
 }
 
 /// Result of a successful download
@@ -30,14 +58,22 @@ pub struct DownloadContext {
 pub struct DownloadResult {
     /// Number of bytes downloaded
     pub bytes_downloaded: u64,
-    /// SHA-256 hash of the downloaded content (format: "sha256:...")
+    /// Hash of the downloaded content, prefixed with its algorithm
+    /// (format: "sha256:...")
     pub content_hash: String,
+    /// URL the file was served from after redirects, if it differs from
+    /// the enclosure URL
+    pub final_url: Option<String>,
+    /// Provenance record of the transfer
+    pub receipt: DownloadReceipt,
 }
 
 /// Download an episode to the specified output path
 ///
-/// Streams the response body to disk while computing a SHA-256 hash.
-/// Downloads to a `.partial` file first, then atomically renames on completion.
+/// Streams the response body to disk while computing its content hash.
+/// Servers sending tiny chunks are coalesced into larger writes (see
+/// [`FLUSH_SIZE`]) to keep per-chunk overhead down. Downloads to a
+/// `.partial` file first, then atomically renames on completion.
 /// Returns a `DownloadResult` containing bytes downloaded and content hash.
 pub async fn download_episode<C: HttpClient>(
     client: &C,
@@ -47,11 +83,23 @@ pub async fn download_episode<C: HttpClient>(
     reporter: &SharedProgressReporter,
 ) -> Result<DownloadResult, DownloadError> {
     let url = episode.enclosure.url.as_str();
+    let timed_out = || DownloadError::TimedOut {
+        url: url.to_string(),
+        timeout: context.timeout.unwrap_or_default(),
+    };
+
+    let stall_timeout = context.stall_timeout.or(context.timeout);
+    let stalled = || DownloadError::Stalled {
+        url: url.to_string(),
+        idle: stall_timeout.unwrap_or_default(),
+    };
+
+    let started_at = Utc::now().to_rfc3339();
 
     // Get streaming response
-    let response = client
-        .get_stream(url)
+    let response = within(context.timeout, client.get_stream(url))
         .await
+        .ok_or_else(timed_out)?
         .map_err(|e| DownloadError::HttpFailed {
             url: url.to_string(),
             source: e,
@@ -62,9 +110,24 @@ pub async fn download_episode<C: HttpClient>(
         return Err(DownloadError::HttpStatus {
             url: url.to_string(),
             status: response.status,
+            retry_after: response.retry_after,
         });
     }
 
+    let too_large = |limit| DownloadError::TooLarge {
+        url: url.to_string(),
+        limit,
+    };
+    if let Some(limit) = context.max_bytes
+        && response.content_length.is_some_and(|length| length > limit)
+    {
+        return Err(too_large(limit));
+    }
+
+    let final_url = response.final_url.filter(|final_url| final_url != url);
+    let receipt_headers = response.headers;
+    let peer_certificate = response.peer_certificate;
+
     // Report download starting
     reporter.report(ProgressEvent::DownloadStarting {
         download_id: context.download_id,
@@ -87,17 +150,51 @@ pub async fn download_episode<C: HttpClient>(
             })?;
 
     // Initialize hasher for streaming hash computation
-    let mut hasher = Sha256::new();
+    let mut hasher = context.hash_algorithm.hasher();
 
     // Stream body to file while computing hash
     let mut bytes_downloaded: u64 = 0;
     let mut stream = response.body;
+    let mut buffer = BytesMut::new();
+    let mut last_flush = Instant::now();
 
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result.map_err(|e| DownloadError::StreamFailed {
-            url: url.to_string(),
-            source: e,
-        })?;
+    loop {
+        let next = within(stall_timeout, stream.next())
+            .await
+            .ok_or_else(stalled)?;
+        let finished = next.is_none();
+
+        let chunk = match next {
+            Some(chunk_result) => {
+                let chunk = chunk_result.map_err(|e| DownloadError::StreamFailed {
+                    url: url.to_string(),
+                    source: e,
+                })?;
+
+                if buffer.is_empty() && chunk.len() >= FLUSH_SIZE {
+                    // Large chunks pass through without copying
+                    chunk
+                } else {
+                    buffer.extend_from_slice(&chunk);
+                    if buffer.len() < FLUSH_SIZE && last_flush.elapsed() < FLUSH_INTERVAL {
+                        continue;
+                    }
+                    buffer.split().freeze()
+                }
+            }
+            None if buffer.is_empty() => break,
+            None => buffer.split().freeze(),
+        };
+        last_flush = Instant::now();
+
+        // Servers may send more than announced, or announce nothing
+        if let Some(limit) = context.max_bytes
+            && bytes_downloaded + chunk.len() as u64 > limit
+        {
+            drop(file);
+            let _ = tokio::fs::remove_file(&partial_path).await;
+            return Err(too_large(limit));
+        }
 
         // Update hash with chunk data
         hasher.update(&chunk);
@@ -111,6 +208,10 @@ pub async fn download_episode<C: HttpClient>(
 
         bytes_downloaded += chunk.len() as u64;
 
+        if let Some(bandwidth) = &context.bandwidth {
+            bandwidth.consume(chunk.len()).await;
+        }
+
         // Report progress
         reporter.report(ProgressEvent::DownloadProgress {
             download_id: context.download_id,
@@ -118,9 +219,14 @@ pub async fn download_episode<C: HttpClient>(
             bytes_downloaded,
             total_bytes: response.content_length,
         });
+
+        if finished {
+            break;
+        }
     }
 
-    // Ensure all data is flushed to disk
+    // Ensure all data is flushed to disk, also for truncated files kept
+    // in quarantine
     file.flush()
         .await
         .map_err(|e| DownloadError::FileWriteFailed {
@@ -128,8 +234,42 @@ pub async fn download_episode<C: HttpClient>(
             source: e,
         })?;
 
+    // A connection closed early looks just like the end of the body
+    if let Some(expected) = response.content_length
+        && bytes_downloaded < expected
+    {
+        return Err(DownloadError::Truncated {
+            url: url.to_string(),
+            expected,
+            received: bytes_downloaded,
+        });
+    }
+
+    if let Some(declared) = episode.enclosure.length
+        && size_differs_wildly(declared, bytes_downloaded)
+    {
+        reporter.report(ProgressEvent::SizeMismatch {
+            download_id: context.download_id,
+            episode_title: episode.title.clone(),
+            declared,
+            received: bytes_downloaded,
+        });
+    }
+
+    // Without this, a crash shortly after the rename can leave an empty
+    // file under the final name on some filesystems
+    if context.fsync {
+        file.sync_all()
+            .await
+            .map_err(|e| DownloadError::FileWriteFailed {
+                path: partial_path.clone(),
+                source: e,
+            })?;
+    }
+    drop(file);
+
     // Finalize hash
-    let content_hash = format!("sha256:{:x}", hasher.finalize());
+    let content_hash = hasher.finalize();
 
     // Report hashing completed
     reporter.report(ProgressEvent::HashingCompleted {
@@ -145,7 +285,7 @@ pub async fn download_episode<C: HttpClient>(
     });
 
     // Atomically rename partial file to final path
-    tokio::fs::rename(&partial_path, output_path)
+    finalize(&partial_path, output_path)
         .await
         .map_err(|e| DownloadError::RenameFailed {
             partial_path: partial_path.clone(),
@@ -153,6 +293,10 @@ pub async fn download_episode<C: HttpClient>(
             source: e,
         })?;
 
+    if context.fsync {
+        sync_directory(output_path).await;
+    }
+
     // Report completion
     reporter.report(ProgressEvent::DownloadCompleted {
         download_id: context.download_id,
@@ -160,12 +304,95 @@ pub async fn download_episode<C: HttpClient>(
         bytes_downloaded,
     });
 
+    let receipt = DownloadReceipt {
+        url: url.to_string(),
+        final_url: final_url.clone(),
+        status: response.status,
+        started_at,
+        completed_at: Utc::now().to_rfc3339(),
+        bytes: bytes_downloaded,
+        content_hash: content_hash.clone(),
+        headers: receipt_headers,
+        peer_certificate,
+    };
+
     Ok(DownloadResult {
         bytes_downloaded,
         content_hash,
+        final_url,
+        receipt,
     })
 }
 
+/// Move a finished download to its final name
+///
+/// Renames atomically, see [`copy_across_devices`] for partial files on
+/// another filesystem.
+async fn finalize(partial_path: &Path, output_path: &Path) -> std::io::Result<()> {
+    let renamed = tokio::fs::rename(partial_path, output_path).await;
+    copy_across_devices(renamed, partial_path, output_path).await
+}
+
+/// Complete a rename that failed with `EXDEV`, as the partial file lives on
+/// another filesystem
+///
+/// The file is copied next to the destination as `.finalizing` first,
+/// forced to disk and renamed from there, so the final name never shows a
+/// half-copied file; a leftover one is removed by the next scan. Other
+/// rename results are passed through.
+async fn copy_across_devices(
+    renamed: std::io::Result<()>,
+    partial_path: &Path,
+    output_path: &Path,
+) -> std::io::Result<()> {
+    match renamed {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            let staging = PathBuf::from(format!("{}.finalizing", output_path.display()));
+            let copied = async {
+                tokio::fs::copy(partial_path, &staging).await?;
+                File::open(&staging).await?.sync_all().await?;
+                tokio::fs::rename(&staging, output_path).await
+            }
+            .await;
+            if copied.is_err() {
+                let _ = tokio::fs::remove_file(&staging).await;
+            }
+            copied?;
+            tokio::fs::remove_file(partial_path).await
+        }
+        result => result,
+    }
+}
+
+/// Persist the rename of a finished download
+///
+/// Best effort: only Unix can open directories for syncing, and the file
+/// itself is already on disk.
+async fn sync_directory(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        })
+        .await
+    {
+        let _ = dir.sync_all().await;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Whether a file is less than half or more than double the size the feed
+/// declared
+///
+/// Declared sizes below 1 KiB are ignored; feeds use `0` or `1` as
+/// placeholders.
+fn size_differs_wildly(declared: u64, received: u64) -> bool {
+    declared >= 1024 && (received < declared / 2 || received > declared.saturating_mul(2))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,31 +401,53 @@ mod tests {
     use crate::progress::NoopReporter;
     use async_trait::async_trait;
     use bytes::Bytes;
+    use sha2::{Digest, Sha256};
+    use std::collections::BTreeMap;
 
     use tempfile::tempdir;
     use url::Url;
 
     struct MockHttpClient {
-        response_data: Vec<u8>,
+        /// Response body, streamed one chunk after another
+        chunks: Vec<Vec<u8>>,
         status: u16,
+        content_length: Option<u64>,
+        final_url: Option<String>,
+    }
+
+    impl MockHttpClient {
+        /// Answer with `data` as a single chunk of announced length
+        fn new(data: &[u8], status: u16) -> Self {
+            Self {
+                chunks: vec![data.to_vec()],
+                status,
+                content_length: Some(data.len() as u64),
+                final_url: None,
+            }
+        }
     }
 
     #[async_trait]
     impl HttpClient for MockHttpClient {
         async fn get_bytes(&self, _url: &str) -> Result<Bytes, reqwest::Error> {
-            Ok(Bytes::from(self.response_data.clone()))
+            Ok(Bytes::from(self.chunks.concat()))
         }
 
         async fn get_stream(&self, _url: &str) -> Result<HttpResponse, reqwest::Error> {
-            let data = self.response_data.clone();
-            let len = data.len() as u64;
-
-            let stream: ByteStream =
-                Box::pin(futures::stream::once(async move { Ok(Bytes::from(data)) }));
+            let chunks = self
+                .chunks
+                .clone()
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from(chunk)));
+            let stream: ByteStream = Box::pin(futures::stream::iter(chunks));
 
             Ok(HttpResponse {
                 status: self.status,
-                content_length: Some(len),
+                content_length: self.content_length,
+                final_url: self.final_url.clone(),
+                headers: BTreeMap::new(),
+                peer_certificate: None,
+                retry_after: None,
                 body: stream,
             })
         }
@@ -215,9 +464,13 @@ mod tests {
                 length: Some(1000),
                 mime_type: Some("audio/mpeg".to_string()),
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: None,
             episode_number: None,
             season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -226,16 +479,19 @@ mod tests {
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("episode.mp3");
 
-        let client = MockHttpClient {
-            response_data: b"test audio content".to_vec(),
-            status: 200,
-        };
+        let client = MockHttpClient::new(b"test audio content", 200);
 
         let episode = make_episode();
         let context = DownloadContext {
             download_id: 0,
             episode_index: 0,
             total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: None,
+            fsync: true,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
         };
         let reporter = NoopReporter::shared();
 
@@ -254,20 +510,268 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn download_fails_on_http_error() {
+    async fn download_reports_redirect_target() {
+        let client = MockHttpClient {
+            final_url: Some("https://cdn.example.com/episode.mp3".to_string()),
+            ..MockHttpClient::new(b"audio", 200)
+        };
+
+        let dir = tempdir().unwrap();
+        let context = DownloadContext {
+            download_id: 0,
+            episode_index: 0,
+            total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: None,
+            fsync: false,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
+        };
+
+        let result = download_episode(
+            &client,
+            &make_episode(),
+            &dir.path().join("episode.mp3"),
+            &context,
+            &NoopReporter::shared(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result.final_url.as_deref(),
+            Some("https://cdn.example.com/episode.mp3")
+        );
+        assert_eq!(result.receipt.final_url, result.final_url);
+        assert_eq!(result.receipt.bytes, 5);
+    }
+
+    #[tokio::test]
+    async fn download_rejects_truncated_body() {
+        let client = MockHttpClient {
+            content_length: Some(1000),
+            ..MockHttpClient::new(b"only the beginning", 200)
+        };
+
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("episode.mp3");
+        let context = DownloadContext {
+            download_id: 0,
+            episode_index: 0,
+            total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: None,
+            fsync: false,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
+        };
+
+        let result = download_episode(
+            &client,
+            &make_episode(),
+            &output_path,
+            &context,
+            &NoopReporter::shared(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DownloadError::Truncated {
+                expected: 1000,
+                received: 18,
+                ..
+            })
+        ));
+        assert!(!output_path.exists());
+    }
 
+    #[tokio::test]
+    async fn download_aborts_files_over_the_size_limit() {
         let client = MockHttpClient {
-            response_data: b"Not Found".to_vec(),
-            status: 404,
+            content_length: None,
+            ..MockHttpClient::new(&[b'x'; 4096], 200)
+        };
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("episode.mp3");
+        let context = DownloadContext {
+            download_id: 0,
+            episode_index: 0,
+            total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: Some(1024),
+            fsync: false,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
         };
 
+        let result = download_episode(
+            &client,
+            &make_episode(),
+            &output_path,
+            &context,
+            &NoopReporter::shared(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DownloadError::TooLarge { limit: 1024, .. })
+        ));
+        assert!(!output_path.exists());
+        assert!(!dir.path().join("episode.mp3.partial").exists());
+    }
+
+    #[tokio::test]
+    async fn finalize_copies_across_filesystems() {
+        let dir = tempdir().unwrap();
+        let partial_path = dir.path().join("episode.mp3.partial");
+        let output_path = dir.path().join("episode.mp3");
+        std::fs::write(&partial_path, b"audio").unwrap();
+
+        let exdev = std::io::Error::from(std::io::ErrorKind::CrossesDevices);
+        copy_across_devices(Err(exdev), &partial_path, &output_path)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&output_path).unwrap(), b"audio");
+        assert!(!partial_path.exists());
+        assert!(!dir.path().join("episode.mp3.finalizing").exists());
+    }
+
+    #[test]
+    fn flags_sizes_far_from_the_declared_length() {
+        assert!(!size_differs_wildly(50_000_000, 48_000_000));
+        assert!(size_differs_wildly(50_000_000, 1_000_000));
+        assert!(size_differs_wildly(50_000_000, 120_000_000));
+        // Placeholder lengths
+        assert!(!size_differs_wildly(1, 50_000_000));
+    }
+
+    #[tokio::test]
+    async fn download_coalesces_small_chunks() {
+        use std::sync::Mutex;
+
+        use crate::progress::ProgressReporter;
+
+        let client = MockHttpClient {
+            chunks: vec![vec![b'x'; 1024]; 100],
+            status: 200,
+            content_length: Some(100 * 1024),
+            final_url: None,
+        };
+
+        #[derive(Default)]
+        struct ProgressCounter(Mutex<usize>);
+
+        impl ProgressReporter for ProgressCounter {
+            fn report(&self, event: ProgressEvent) {
+                if let ProgressEvent::DownloadProgress { .. } = event {
+                    *self.0.lock().unwrap() += 1;
+                }
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("episode.mp3");
+        let context = DownloadContext {
+            download_id: 0,
+            episode_index: 0,
+            total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: None,
+            fsync: false,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
+        };
+        let counter = std::sync::Arc::new(ProgressCounter::default());
+        let reporter: SharedProgressReporter = counter.clone();
+
+        let result = download_episode(&client, &make_episode(), &output_path, &context, &reporter)
+            .await
+            .unwrap();
+
+        assert_eq!(result.bytes_downloaded, 100 * 1024);
+        assert_eq!(std::fs::read(&output_path).unwrap(), vec![b'x'; 100 * 1024]);
+        assert_eq!(
+            result.content_hash,
+            format!("sha256:{:x}", Sha256::digest(vec![b'x'; 100 * 1024]))
+        );
+        // 64 KiB, then the remaining 36 KiB at the end of the stream
+        assert_eq!(*counter.0.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn download_aborts_stalled_transfer() {
+        struct StalledClient;
+
+        #[async_trait]
+        impl HttpClient for StalledClient {
+            async fn get_bytes(&self, _url: &str) -> Result<Bytes, reqwest::Error> {
+                std::future::pending().await
+            }
+
+            async fn get_stream(&self, _url: &str) -> Result<HttpResponse, reqwest::Error> {
+                Ok(HttpResponse {
+                    status: 200,
+                    content_length: None,
+                    final_url: None,
+                    headers: BTreeMap::new(),
+                    peer_certificate: None,
+                    retry_after: None,
+                    body: Box::pin(futures::stream::pending()),
+                })
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        let context = DownloadContext {
+            download_id: 0,
+            episode_index: 0,
+            total_to_download: 1,
+            timeout: Some(Duration::from_secs(3600)),
+            bandwidth: None,
+            max_bytes: None,
+            fsync: false,
+            stall_timeout: Some(Duration::from_millis(10)),
+            hash_algorithm: HashAlgorithm::Sha256,
+        };
+
+        let result = download_episode(
+            &StalledClient,
+            &make_episode(),
+            &dir.path().join("episode.mp3"),
+            &context,
+            &NoopReporter::shared(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(DownloadError::Stalled { .. })));
+    }
+
+    #[tokio::test]
+    async fn download_fails_on_http_error() {
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("episode.mp3");
+
+        let client = MockHttpClient::new(b"Not Found", 404);
+
         let episode = make_episode();
         let context = DownloadContext {
             download_id: 0,
             episode_index: 0,
             total_to_download: 1,
+            timeout: None,
+            bandwidth: None,
+            max_bytes: None,
+            fsync: false,
+            stall_timeout: None,
+            hash_algorithm: HashAlgorithm::Sha256,
         };
         let reporter = NoopReporter::shared();
 
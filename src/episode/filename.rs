@@ -2,28 +2,253 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use percent_encoding::percent_decode_str;
+
+use crate::error::UnitError;
 use crate::feed::Episode;
 
-/// Maximum length for the title portion of a filename
-const MAX_TITLE_LENGTH: usize = 100;
+/// Default maximum length, in characters, for the title portion of a filename
+pub const MAX_TITLE_LENGTH: usize = 100;
 
-/// Generate a filename stem (without extension) for an episode
+const PLACEHOLDERS: &[&str] = &["date", "year", "month", "day", "title", "episode", "season"];
+
+const DEFAULT_TEMPLATE: &str = "{date}-{title}";
+
+/// Name of the built-in template numbering episodes, see
+/// [`FilenameTemplate::numbered`]
+pub const NUMBERED_TEMPLATE: &str = "numbered";
+
+/// Name of the built-in template keeping the publisher's filenames, see
+/// [`FilenameTemplate::original`]
+pub const ORIGINAL_TEMPLATE: &str = "original";
+
+/// Template for naming episode files, such as `S{season:02}E{episode:02} - {title}`
 ///
-/// Format: "YYYY-MM-DD-sanitized-title" or "undated-sanitized-title"
-pub fn generate_filename_stem(episode: &Episode) -> String {
-    let date_prefix = episode
-        .pub_date
-        .map(|dt| dt.format("%Y-%m-%d").to_string())
-        .unwrap_or_else(|| "undated".to_string());
+/// Placeholders are `{date}` (`YYYY-MM-DD`, or `undated`), `{year}`,
+/// `{month}`, `{day}`, `{title}`, and the feed's `{episode}` and `{season}`
+/// numbers. The numbers take a zero-padded width, as in `{episode:03}`.
+/// Missing values render empty, and separators left dangling at either end
+/// are trimmed. The default is `{date}-{title}`; [`NUMBERED_TEMPLATE`] and
+/// [`ORIGINAL_TEMPLATE`] select [`FilenameTemplate::numbered`] and
+/// [`FilenameTemplate::original`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    template: String,
+    max_title_length: usize,
+    max_stem_bytes: Option<usize>,
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEMPLATE)
+    }
+}
+
+impl FilenameTemplate {
+    /// Parse a template, rejecting unknown, malformed or unclosed placeholders
+    ///
+    /// Templates without any placeholder are rejected as well, as they would
+    /// give every episode the same name.
+    pub fn parse(template: &str) -> Result<Self, UnitError> {
+        if template == NUMBERED_TEMPLATE {
+            return Ok(Self::numbered());
+        }
+        if template == ORIGINAL_TEMPLATE {
+            return Ok(Self::original());
+        }
+        let invalid = || UnitError::InvalidFilenameTemplate(template.to_string());
+
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}').ok_or_else(invalid)?;
+            let (name, width) = split_placeholder(&rest[start + 1..end]);
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(invalid());
+            }
+            if let Some(width) = width
+                && (!matches!(name, "episode" | "season") || width.parse::<usize>().is_err())
+            {
+                return Err(invalid());
+            }
+            rest = &rest[end + 1..];
+        }
+        if rest.contains('}') || !template.contains('{') {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(template))
+    }
 
-    let sanitized_title = sanitize_title(&episode.title);
+    fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            max_title_length: MAX_TITLE_LENGTH,
+            max_stem_bytes: None,
+        }
+    }
 
-    format!("{}-{}", date_prefix, sanitized_title)
+    /// Shorten titles to this many characters (default [`MAX_TITLE_LENGTH`])
+    pub fn max_title_length(mut self, max: usize) -> Self {
+        self.max_title_length = max;
+        self
+    }
+
+    /// Shorten whole filename stems to this many bytes, for filesystems
+    /// with short name limits such as eCryptfs (about 143 bytes)
+    ///
+    /// Multi-byte characters are never split.
+    pub fn max_stem_bytes(mut self, max: usize) -> Self {
+        self.max_stem_bytes = Some(max);
+        self
+    }
+
+    /// Built-in template ordering episodes by their number in the feed
+    ///
+    /// Names are `<season>-<episode>-<title>` with two- and three-digit
+    /// numbers (`02-007-Title`), or `<episode>-<title>` for episodes without
+    /// a season. Episodes without a number fall back to the default,
+    /// date-based name. Suits audio dramas and other serialized feeds.
+    pub fn numbered() -> Self {
+        Self::new(NUMBERED_TEMPLATE)
+    }
+
+    /// Built-in template keeping the filename of the enclosure URL, only
+    /// sanitized (`https://cdn.example.com/show/ep42_final.mp3` stays
+    /// `ep42_final.mp3`)
+    ///
+    /// Episodes whose URL ends without a filename fall back to the default
+    /// name.
+    pub fn original() -> Self {
+        Self::new(ORIGINAL_TEMPLATE)
+    }
+
+    /// Filename stem (without extension) for an episode
+    ///
+    /// Falls back to the default template if nothing is left after
+    /// rendering, so episodes never end up with an empty name.
+    pub fn render(&self, episode: &Episode) -> String {
+        let date = |format: &str| episode.pub_date.map(|dt| dt.format(format).to_string());
+        let number = |number: Option<u32>, width: Option<&str>| {
+            let width = width.and_then(|width| width.parse().ok()).unwrap_or(0);
+            number.map(|number| format!("{:0width$}", number, width = width))
+        };
+
+        let mut rendered = match self.template.as_str() {
+            ORIGINAL_TEMPLATE => original_stem(episode),
+            _ => String::new(),
+        };
+        let mut rest = match (
+            self.template.as_str(),
+            episode.season_number,
+            episode.episode_number,
+        ) {
+            (NUMBERED_TEMPLATE, Some(_), Some(_)) => "{season:02}-{episode:03}-{title}",
+            (NUMBERED_TEMPLATE, None, Some(_)) => "{episode:03}-{title}",
+            (NUMBERED_TEMPLATE, _, None) => DEFAULT_TEMPLATE,
+            (ORIGINAL_TEMPLATE, _, _) => "",
+            (template, _, _) => template,
+        };
+        while let Some(start) = rest.find('{') {
+            // Validated by parse
+            let end = start + rest[start..].find('}').unwrap_or(0);
+            rendered.push_str(&rest[..start]);
+
+            let (name, width) = split_placeholder(&rest[start + 1..end]);
+            let value = match name {
+                "date" => date("%Y-%m-%d").or_else(|| Some("undated".to_string())),
+                "year" => date("%Y"),
+                "month" => date("%m"),
+                "day" => date("%d"),
+                "title" => Some(sanitize_title_to(&episode.title, self.max_title_length)),
+                "episode" => number(episode.episode_number, width),
+                "season" => number(episode.season_number, width),
+                _ => None,
+            };
+            rendered.push_str(&value.unwrap_or_default());
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+
+        let is_edge = |c: char| matches!(c, '-' | '_' | '.') || c.is_whitespace();
+        let sanitized = sanitize_filename::sanitize(&rendered);
+        let mut stem = sanitized.trim_matches(is_edge);
+        if let Some(max) = self.max_stem_bytes {
+            stem = truncate_to_bytes(stem, max).trim_end_matches(is_edge);
+        }
+        let stem = avoid_reserved_name(stem);
+
+        if stem.is_empty() && self.template != DEFAULT_TEMPLATE {
+            let fallback = Self {
+                template: DEFAULT_TEMPLATE.to_string(),
+                ..self.clone()
+            };
+            return fallback.render(episode);
+        }
+        stem
+    }
+
+    /// Complete filename for an episode (with extension)
+    pub fn filename(&self, episode: &Episode) -> String {
+        format!("{}.{}", self.render(episode), get_audio_extension(episode))
+    }
+
+    /// Like [`filename`](Self::filename), but lowercase ASCII words joined
+    /// by dashes, for devices that cannot handle spaces or Unicode
+    ///
+    /// Unicode is transliterated where possible (`Über Café` becomes
+    /// `uber-cafe`).
+    pub fn ascii_filename(&self, episode: &Episode) -> String {
+        let stem = slug(&self.render(episode));
+        format!("{}.{}", stem, get_audio_extension(episode))
+    }
+}
+
+/// Lowercase ASCII words joined by dashes, transliterating Unicode
+pub(crate) fn slug(value: &str) -> String {
+    deunicode::deunicode(value)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Last path segment of the enclosure URL, percent-decoded and without its
+/// extension
+pub(crate) fn original_stem(episode: &Episode) -> String {
+    let segment = episode
+        .enclosure
+        .url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default();
+    let segment = percent_decode_str(segment).decode_utf8_lossy();
+    match segment.rsplit_once('.') {
+        Some((stem, _)) => stem.to_string(),
+        None => segment.into_owned(),
+    }
+}
+
+/// Split `name:width` into its parts
+fn split_placeholder(placeholder: &str) -> (&str, Option<&str>) {
+    match placeholder.split_once(':') {
+        Some((name, width)) => (name, Some(width)),
+        None => (placeholder, None),
+    }
+}
+
+/// Generate a filename stem (without extension) for an episode
+///
+/// Format: "YYYY-MM-DD-sanitized-title" or "undated-sanitized-title", see
+/// [`FilenameTemplate`] for other schemes.
+pub fn generate_filename_stem(episode: &Episode) -> String {
+    FilenameTemplate::default().render(episode)
 }
 
 /// Get the audio file extension from an episode's enclosure
 ///
-/// Attempts to extract from URL path or MIME type, defaults to "mp3"
+/// Attempts to extract from URL path or MIME type, defaults to "mp3".
+/// Video enclosures get their video extension (`mp4`, `m4v`, ...).
 pub fn get_audio_extension(episode: &Episode) -> String {
     // Try to get extension from URL path
     if let Some(ext) = episode
@@ -50,16 +275,21 @@ pub fn get_audio_extension(episode: &Episode) -> String {
 
 /// Generate a complete filename for an episode (with extension)
 pub fn generate_filename(episode: &Episode) -> String {
-    let stem = generate_filename_stem(episode);
-    let ext = get_audio_extension(episode);
-    format!("{}.{}", stem, ext)
+    FilenameTemplate::default().filename(episode)
 }
 
 /// Sanitize a title for use in a filename
 ///
 /// Uses sanitize_filename to remove/replace filesystem-invalid characters
 /// while preserving Unicode. Then normalizes whitespace and limits length.
-fn sanitize_title(title: &str) -> String {
+/// The result is also safe on Windows: no leading or trailing dots and
+/// spaces, and no reserved device names.
+pub(crate) fn sanitize_title(title: &str) -> String {
+    sanitize_title_to(title, MAX_TITLE_LENGTH)
+}
+
+/// [`sanitize_title`] with a custom maximum length, in characters
+fn sanitize_title_to(title: &str, max_length: usize) -> String {
     // Remove filesystem-invalid characters (preserves Unicode)
     let sanitized = sanitize_filename::sanitize(title);
 
@@ -67,13 +297,68 @@ fn sanitize_title(title: &str) -> String {
     let collapsed = collapse_separators(&sanitized);
 
     // Trim and limit length
-    let trimmed = collapsed.trim_matches(|c: char| c == '-' || c.is_whitespace());
+    let is_edge = |c: char| c == '-' || c == '.' || c.is_whitespace();
+    let trimmed = collapsed.trim_matches(is_edge);
 
-    if trimmed.len() > MAX_TITLE_LENGTH {
+    let limited = if trimmed.chars().count() > max_length {
         // Truncate at word boundary if possible
-        truncate_at_boundary(trimmed, MAX_TITLE_LENGTH)
+        truncate_at_boundary(trimmed, max_length)
     } else {
         trimmed.to_string()
+    };
+
+    avoid_reserved_name(limited.trim_end_matches(is_edge))
+}
+
+/// Device names Windows reserves, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Append `_` to names Windows would take for a device (`CON`, `Con. Air`)
+fn avoid_reserved_name(name: &str) -> String {
+    let base = name.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base))
+    {
+        format!("{}_{}", base, &name[base.len()..])
+    } else {
+        name.to_string()
+    }
+}
+
+/// Longest prefix of `s` with at most `max` bytes, ending on a character
+/// boundary
+fn truncate_to_bytes(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let end = (0..=max)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0);
+    &s[..end]
+}
+
+/// Shorten the stem of a filename so the whole name has at most
+/// `max_chars` characters, keeping its extension
+///
+/// At least one character of the stem is kept.
+pub(crate) fn shorten_filename(filename: &str, max_chars: usize) -> String {
+    if filename.chars().count() <= max_chars {
+        return filename.to_string();
+    }
+
+    let (stem, ext) = filename.rsplit_once('.').unwrap_or((filename, ""));
+    let keep = max_chars.saturating_sub(ext.chars().count() + 1).max(1);
+    let stem: String = stem.chars().take(keep).collect();
+    let stem = stem.trim_end_matches(|c: char| c == '-' || c == '.' || c.is_whitespace());
+    if ext.is_empty() {
+        stem.to_string()
+    } else {
+        format!("{}.{}", stem, ext)
     }
 }
 
@@ -154,15 +439,20 @@ fn truncate_at_boundary(s: &str, max_len: usize) -> String {
 }
 
 /// Check if a string is a valid audio file extension
-fn is_valid_audio_extension(ext: &str) -> bool {
+pub(crate) fn is_valid_audio_extension(ext: &str) -> bool {
     matches!(
         ext.to_lowercase().as_str(),
         "mp3" | "m4a" | "mp4" | "aac" | "ogg" | "opus" | "wav" | "flac"
-    )
+    ) || is_video_extension(ext)
+}
+
+/// Extensions that only occur for video files
+pub(crate) fn is_video_extension(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "m4v" | "mov" | "webm" | "mkv")
 }
 
 /// Map MIME types to file extensions
-fn mime_to_extension(mime: &str) -> Option<&'static str> {
+pub(crate) fn mime_to_extension(mime: &str) -> Option<&'static str> {
     match mime.to_lowercase().as_str() {
         "audio/mpeg" | "audio/mp3" => Some("mp3"),
         "audio/mp4" | "audio/m4a" | "audio/x-m4a" => Some("m4a"),
@@ -171,6 +461,11 @@ fn mime_to_extension(mime: &str) -> Option<&'static str> {
         "audio/opus" => Some("opus"),
         "audio/wav" | "audio/x-wav" => Some("wav"),
         "audio/flac" | "audio/x-flac" => Some("flac"),
+        "video/mp4" => Some("mp4"),
+        "video/x-m4v" => Some("m4v"),
+        "video/quicktime" => Some("mov"),
+        "video/webm" => Some("webm"),
+        "video/x-matroska" => Some("mkv"),
         _ => None,
     }
 }
@@ -180,6 +475,7 @@ mod tests {
     use super::*;
     use crate::feed::Enclosure;
     use chrono::DateTime;
+    use std::collections::BTreeMap;
     use url::Url;
 
     fn make_episode(title: &str, date: Option<&str>, url: &str) -> Episode {
@@ -202,9 +498,13 @@ mod tests {
                 length: None,
                 mime_type: mime.map(String::from),
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: None,
             episode_number: None,
             season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -285,6 +585,30 @@ mod tests {
         assert!(!result.contains('\\'));
     }
 
+    #[test]
+    fn sanitize_avoids_windows_pitfalls() {
+        assert_eq!(sanitize_title("Wait for it..."), "Wait for it");
+        assert_eq!(sanitize_title(".hack"), "hack");
+        assert_eq!(sanitize_title("con"), "con_");
+        assert_eq!(sanitize_title("Con. Air"), "Con_. Air");
+        assert_eq!(sanitize_title("LPT1 "), "LPT1_");
+        assert_eq!(sanitize_title("Console"), "Console");
+    }
+
+    #[test]
+    fn shortens_filenames_keeping_the_extension() {
+        assert_eq!(shorten_filename("short.mp3", 20), "short.mp3");
+        assert_eq!(
+            shorten_filename("2024-01-15-A long title.mp3", 20),
+            "2024-01-15-A lon.mp3"
+        );
+        assert_eq!(
+            shorten_filename("2024-01-15-A long title.mp3", 15),
+            "2024-01-15.mp3"
+        );
+        assert_eq!(shorten_filename("title.mp3", 2), "t.mp3");
+    }
+
     #[test]
     fn sanitize_handles_newlines_and_tabs() {
         // sanitize_filename removes control characters entirely
@@ -352,6 +676,132 @@ mod tests {
         assert!(stem.starts_with("2024-01-15") || stem.starts_with("2024-01-16"));
     }
 
+    // === Filename template tests ===
+
+    #[test]
+    fn template_renders_every_placeholder() {
+        let mut episode = make_episode(
+            "Test: Episode",
+            Some("Mon, 15 Jan 2024 12:00:00 +0000"),
+            "https://example.com/ep.ogg",
+        );
+        episode.episode_number = Some(7);
+        episode.season_number = Some(2);
+
+        let render = |template: &str| FilenameTemplate::parse(template).unwrap().render(&episode);
+        assert_eq!(render("{date}-{title}"), generate_filename_stem(&episode));
+        assert_eq!(
+            render("{year}.{month}.{day} {title}"),
+            "2024.01.15 Test Episode"
+        );
+        assert_eq!(
+            render("{date}-{episode:03}-{title}"),
+            "2024-01-15-007-Test Episode"
+        );
+        assert_eq!(
+            render("S{season:02}E{episode:02} - {title}"),
+            "S02E07 - Test Episode"
+        );
+        assert_eq!(render("{season}x{episode}"), "2x7");
+        assert_eq!(
+            FilenameTemplate::parse("{episode:03} {title}")
+                .unwrap()
+                .filename(&episode),
+            "007 Test Episode.ogg"
+        );
+    }
+
+    #[test]
+    fn template_trims_missing_values() {
+        let episode = make_episode("Test", None, "https://example.com/ep.mp3");
+
+        let render = |template: &str| FilenameTemplate::parse(template).unwrap().render(&episode);
+        assert_eq!(render("{date}-{title}"), "undated-Test");
+        assert_eq!(render("{episode:03} - {title}"), "Test");
+        assert_eq!(render("{year}-{month}-{day}"), "undated-Test");
+    }
+
+    #[test]
+    fn template_applies_length_limits() {
+        let episode = make_episode(
+            "Größenwahn und Übermut",
+            Some("Mon, 15 Jan 2024 12:00:00 +0000"),
+            "https://example.com/ep.mp3",
+        );
+
+        let template = FilenameTemplate::default().max_title_length(9);
+        assert_eq!(template.render(&episode), "2024-01-15-Größenwah");
+
+        // "ö" takes two bytes and is not split
+        let template = FilenameTemplate::default().max_stem_bytes(14);
+        assert_eq!(template.render(&episode), "2024-01-15-Gr");
+        let template = FilenameTemplate::default().max_stem_bytes(16);
+        assert_eq!(template.render(&episode), "2024-01-15-Grö");
+    }
+
+    #[test]
+    fn original_template_keeps_remote_filenames() {
+        let original = FilenameTemplate::parse(ORIGINAL_TEMPLATE).unwrap();
+
+        let episode = make_episode(
+            "Test",
+            None,
+            "https://cdn.example.com/show/ep42_final%20mix.MP3?token=abc",
+        );
+        assert_eq!(original.filename(&episode), "ep42_final mix.mp3");
+
+        let episode = make_episode("Test", None, "https://cdn.example.com/media/12345");
+        assert_eq!(original.filename(&episode), "12345.mp3");
+
+        let episode = make_episode("Test", None, "https://cdn.example.com/");
+        assert_eq!(original.filename(&episode), "undated-Test.mp3");
+    }
+
+    #[test]
+    fn numbered_template_falls_back_to_date() {
+        let mut episode = make_episode(
+            "Test",
+            Some("Mon, 15 Jan 2024 12:00:00 +0000"),
+            "https://example.com/ep.mp3",
+        );
+        let numbered = FilenameTemplate::parse(NUMBERED_TEMPLATE).unwrap();
+        assert_eq!(numbered, FilenameTemplate::numbered());
+        assert_eq!(numbered.render(&episode), "2024-01-15-Test");
+
+        episode.episode_number = Some(7);
+        assert_eq!(numbered.render(&episode), "007-Test");
+
+        episode.season_number = Some(2);
+        assert_eq!(numbered.render(&episode), "02-007-Test");
+    }
+
+    #[test]
+    fn ascii_filenames_transliterate_unicode() {
+        let episode = make_episode(
+            "Über Café: Ça va?",
+            Some("Mon, 15 Jan 2024 12:00:00 +0000"),
+            "https://example.com/ep.MP3",
+        );
+        assert_eq!(
+            FilenameTemplate::default().ascii_filename(&episode),
+            "2024-01-15-uber-cafe-ca-va.mp3"
+        );
+
+        let episode = make_episode("???", None, "https://example.com/ep.mp3");
+        let template = FilenameTemplate::parse("{title}").unwrap();
+        assert_eq!(template.ascii_filename(&episode), "undated.mp3");
+    }
+
+    #[test]
+    fn template_rejects_unknown_placeholders() {
+        assert!(FilenameTemplate::parse("{date}-{name}").is_err());
+        assert!(FilenameTemplate::parse("{title:03}").is_err());
+        assert!(FilenameTemplate::parse("{episode:wide}").is_err());
+        assert!(FilenameTemplate::parse("{title").is_err());
+        assert!(FilenameTemplate::parse("title}").is_err());
+        assert!(FilenameTemplate::parse("episode").is_err());
+    }
+
     #[test]
     fn sanitizes_invalid_characters() {
         let episode = make_episode(
@@ -460,6 +910,20 @@ mod tests {
         assert_eq!(get_audio_extension(&episode), "ogg");
     }
 
+    #[test]
+    fn video_mimes_map_to_video_extensions() {
+        let episode = make_episode_with_mime(
+            "Test",
+            None,
+            "https://example.com/episode",
+            Some("video/mp4"),
+        );
+        assert_eq!(get_audio_extension(&episode), "mp4");
+
+        let episode = make_episode("Test", None, "https://example.com/episode.m4v");
+        assert_eq!(get_audio_extension(&episode), "m4v");
+    }
+
     #[test]
     fn defaults_to_mp3_for_unknown_extension() {
         let episode = make_episode("Test", None, "https://example.com/episode");
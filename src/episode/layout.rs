@@ -0,0 +1,113 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::feed::Episode;
+
+/// How episodes are arranged inside the output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// All episodes directly in the output directory
+    #[default]
+    Flat,
+    /// Episodes with a season number in `Season 01/`, `Season 02/`, ...;
+    /// episodes without one stay directly in the output directory
+    Seasons,
+    /// Episodes in `2023/`, `2024/`, ... by publication year; undated
+    /// episodes stay directly in the output directory
+    Years,
+    /// Like `Years`, with a subdirectory per month (`2024/01/`)
+    Months,
+}
+
+impl OutputLayout {
+    /// Subdirectory of the output directory an episode belongs in
+    ///
+    /// Returns `None` for episodes stored directly in the output directory.
+    pub fn episode_directory(self, episode: &Episode) -> Option<String> {
+        match self {
+            OutputLayout::Flat => None,
+            OutputLayout::Seasons => episode
+                .season_number
+                .map(|season| format!("Season {:02}", season)),
+            OutputLayout::Years => episode.pub_date.map(|date| date.format("%Y").to_string()),
+            OutputLayout::Months => episode
+                .pub_date
+                .map(|date| date.format("%Y/%m").to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use std::collections::BTreeMap;
+    use url::Url;
+
+    fn make_episode(season_number: Option<u32>) -> Episode {
+        Episode {
+            title: "Test".to_string(),
+            description: None,
+            pub_date: None,
+            guid: None,
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn date_layouts_nest_by_publication() {
+        let mut episode = make_episode(None);
+        assert_eq!(OutputLayout::Years.episode_directory(&episode), None);
+
+        episode.pub_date =
+            chrono::DateTime::parse_from_rfc2822("Mon, 15 Jan 2024 12:00:00 +0000").ok();
+        assert_eq!(
+            OutputLayout::Years.episode_directory(&episode),
+            Some("2024".to_string())
+        );
+        assert_eq!(
+            OutputLayout::Months.episode_directory(&episode),
+            Some("2024/01".to_string())
+        );
+    }
+
+    #[test]
+    fn flat_layout_uses_no_subdirectories() {
+        assert_eq!(
+            OutputLayout::Flat.episode_directory(&make_episode(Some(3))),
+            None
+        );
+    }
+
+    #[test]
+    fn season_layout_pads_season_number() {
+        assert_eq!(
+            OutputLayout::Seasons.episode_directory(&make_episode(Some(3))),
+            Some("Season 03".to_string())
+        );
+        assert_eq!(
+            OutputLayout::Seasons.episode_directory(&make_episode(Some(112))),
+            Some("Season 112".to_string())
+        );
+    }
+
+    #[test]
+    fn season_layout_keeps_unseasoned_episodes_at_top_level() {
+        assert_eq!(
+            OutputLayout::Seasons.episode_directory(&make_episode(None)),
+            None
+        );
+    }
+}
@@ -4,6 +4,24 @@
 
 mod download;
 mod filename;
+mod layout;
+mod select;
+mod transcript;
+
+pub(crate) use filename::{
+    is_valid_audio_extension, original_stem, sanitize_title, shorten_filename, slug,
+};
 
 pub use download::{DownloadContext, DownloadResult, download_episode};
-pub use filename::{generate_filename, generate_filename_stem, get_audio_extension};
+pub use filename::{
+    FilenameTemplate, MAX_TITLE_LENGTH, NUMBERED_TEMPLATE, ORIGINAL_TEMPLATE, generate_filename,
+    generate_filename_stem, get_audio_extension,
+};
+pub use layout::OutputLayout;
+pub use select::{
+    BitratePreference, EnclosurePreference, VideoPolicy, is_video, matches_language,
+    select_enclosure,
+};
+pub use transcript::{
+    TRANSCRIPT_INFIX, download_transcript, transcript_extension, transcript_filenames,
+};
@@ -0,0 +1,281 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::feed::{Enclosure, Episode};
+
+use super::filename::{is_video_extension, mime_to_extension};
+
+/// Which bitrate to pick when several encodings match the preference
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitratePreference {
+    /// Pick the highest bitrate (best quality)
+    #[default]
+    Highest,
+    /// Pick the lowest bitrate (smallest file)
+    Lowest,
+}
+
+/// Preference for choosing between an episode's enclosure and its alternates
+#[derive(Debug, Clone, Default)]
+pub struct EnclosurePreference {
+    /// Formats in order of preference, as MIME type (`audio/opus`) or
+    /// extension (`opus`). Unlisted formats rank after all listed ones.
+    pub formats: Vec<String>,
+    /// Minimum bitrate in bits per second
+    pub min_bitrate: Option<u64>,
+    /// Maximum bitrate in bits per second
+    pub max_bitrate: Option<u64>,
+    /// Tie-breaker between encodings of the same format
+    pub bitrate: BitratePreference,
+}
+
+/// Choose the enclosure to download according to a preference
+///
+/// Considers the regular enclosure together with all alternate enclosures.
+/// Encodings with a declared bitrate outside the configured bounds are
+/// excluded; encodings without a declared bitrate are kept but rank after
+/// those with one. If nothing satisfies the bounds, the regular enclosure is
+/// returned unchanged.
+pub fn select_enclosure(episode: &Episode, preference: &EnclosurePreference) -> Enclosure {
+    let candidates = std::iter::once((&episode.enclosure, None)).chain(
+        episode
+            .alternate_enclosures
+            .iter()
+            .map(|alternate| (&alternate.enclosure, alternate.bitrate)),
+    );
+
+    let best = candidates
+        .filter(|(_, bitrate)| {
+            bitrate.is_none_or(|b| {
+                preference.min_bitrate.is_none_or(|min| b >= min)
+                    && preference.max_bitrate.is_none_or(|max| b <= max)
+            })
+        })
+        .enumerate()
+        .min_by_key(|(index, (enclosure, bitrate))| {
+            let format_rank = format_rank(enclosure, &preference.formats);
+            let bitrate_rank = match (bitrate, preference.bitrate) {
+                (Some(b), BitratePreference::Lowest) => (0, *b),
+                (Some(b), BitratePreference::Highest) => (0, u64::MAX - b),
+                (None, _) => (1, 0),
+            };
+            (format_rank, bitrate_rank, *index)
+        });
+
+    best.map(|(_, (enclosure, _))| enclosure.clone())
+        .unwrap_or_else(|| episode.enclosure.clone())
+}
+
+/// What to do with episodes whose enclosure is a video
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VideoPolicy {
+    /// Download videos like audio files, with their video extension
+    #[default]
+    Download,
+    /// Leave video episodes out of the sync
+    Skip,
+    /// Count video episodes as failed
+    Fail,
+}
+
+/// Whether an enclosure is a video, judged by its MIME type (`video/...`)
+/// or, without one, by a video-only file extension such as `.m4v`
+pub fn is_video(enclosure: &Enclosure) -> bool {
+    match &enclosure.mime_type {
+        Some(mime) => mime.to_lowercase().starts_with("video/"),
+        None => enclosure
+            .url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|filename| filename.rsplit_once('.'))
+            .is_some_and(|(_, ext)| is_video_extension(ext)),
+    }
+}
+
+/// Position of the enclosure's format in the preference list
+fn format_rank(enclosure: &Enclosure, formats: &[String]) -> usize {
+    let Some(mime) = enclosure.mime_type.as_deref().map(str::to_lowercase) else {
+        return formats.len();
+    };
+
+    formats
+        .iter()
+        .position(|format| {
+            let format = format.to_lowercase();
+            mime == format
+                || mime.split('/').nth(1) == Some(format.as_str())
+                || mime_to_extension(&mime) == Some(format.as_str())
+        })
+        .unwrap_or(formats.len())
+}
+
+/// Whether an episode's language is among `languages`
+///
+/// A language matches its own subtags, so `en` matches `en-US`; comparison
+/// ignores case and `_` vs. `-`. Episodes of unknown language always match,
+/// as does any episode when `languages` is empty.
+pub fn matches_language(episode: &Episode, languages: &[String]) -> bool {
+    let Some(language) = &episode.language else {
+        return true;
+    };
+    if languages.is_empty() {
+        return true;
+    }
+
+    let normalize = |tag: &str| tag.trim().to_ascii_lowercase().replace('_', "-");
+    let language = normalize(language);
+    languages
+        .iter()
+        .map(|wanted| normalize(wanted))
+        .any(|wanted| {
+            language == wanted
+                || language
+                    .strip_prefix(&wanted)
+                    .is_some_and(|rest| rest.starts_with('-'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::AlternateEnclosure;
+    use std::collections::BTreeMap;
+    use url::Url;
+
+    fn make_enclosure(url: &str, mime: &str) -> Enclosure {
+        Enclosure {
+            url: Url::parse(url).unwrap(),
+            length: None,
+            mime_type: Some(mime.to_string()),
+        }
+    }
+
+    fn make_alternate(url: &str, mime: &str, bitrate: u64) -> AlternateEnclosure {
+        AlternateEnclosure {
+            enclosure: make_enclosure(url, mime),
+            bitrate: Some(bitrate),
+            title: None,
+            default: false,
+        }
+    }
+
+    fn make_episode() -> Episode {
+        Episode {
+            title: "Test".to_string(),
+            description: None,
+            pub_date: None,
+            guid: None,
+            enclosure: make_enclosure("https://example.com/ep.mp3", "audio/mpeg"),
+            alternate_enclosures: vec![
+                make_alternate("https://example.com/ep-32.opus", "audio/opus", 32_000),
+                make_alternate("https://example.com/ep-64.opus", "audio/opus", 64_000),
+                make_alternate("https://example.com/ep-96.opus", "audio/opus", 96_000),
+                make_alternate("https://example.com/ep-256.mp3", "audio/mpeg", 256_000),
+            ],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn prefers_listed_format_with_lowest_bitrate_above_minimum() {
+        let preference = EnclosurePreference {
+            formats: vec!["opus".to_string()],
+            min_bitrate: Some(64_000),
+            max_bitrate: None,
+            bitrate: BitratePreference::Lowest,
+        };
+
+        let selected = select_enclosure(&make_episode(), &preference);
+        assert_eq!(selected.url.as_str(), "https://example.com/ep-64.opus");
+    }
+
+    #[test]
+    fn prefers_highest_bitrate_by_default() {
+        let preference = EnclosurePreference {
+            formats: vec!["audio/opus".to_string()],
+            ..Default::default()
+        };
+
+        let selected = select_enclosure(&make_episode(), &preference);
+        assert_eq!(selected.url.as_str(), "https://example.com/ep-96.opus");
+    }
+
+    #[test]
+    fn matches_formats_by_extension() {
+        let preference = EnclosurePreference {
+            formats: vec!["mp3".to_string()],
+            ..Default::default()
+        };
+
+        let selected = select_enclosure(&make_episode(), &preference);
+        assert_eq!(selected.url.as_str(), "https://example.com/ep-256.mp3");
+    }
+
+    #[test]
+    fn respects_maximum_bitrate() {
+        let preference = EnclosurePreference {
+            formats: vec!["opus".to_string()],
+            max_bitrate: Some(50_000),
+            ..Default::default()
+        };
+
+        let selected = select_enclosure(&make_episode(), &preference);
+        assert_eq!(selected.url.as_str(), "https://example.com/ep-32.opus");
+    }
+
+    #[test]
+    fn falls_back_to_regular_enclosure_without_alternates() {
+        let mut episode = make_episode();
+        episode.alternate_enclosures.clear();
+
+        let preference = EnclosurePreference {
+            formats: vec!["opus".to_string()],
+            ..Default::default()
+        };
+
+        let selected = select_enclosure(&episode, &preference);
+        assert_eq!(selected.url.as_str(), "https://example.com/ep.mp3");
+    }
+
+    #[test]
+    fn recognizes_video_enclosures() {
+        assert!(is_video(&make_enclosure(
+            "https://example.com/ep.mp4",
+            "video/mp4"
+        )));
+        assert!(!is_video(&make_enclosure(
+            "https://example.com/ep.mp4",
+            "audio/mp4"
+        )));
+
+        let untyped = |url: &str| Enclosure {
+            url: Url::parse(url).unwrap(),
+            length: None,
+            mime_type: None,
+        };
+        assert!(is_video(&untyped("https://example.com/ep.m4v")));
+        assert!(!is_video(&untyped("https://example.com/ep.mp4")));
+    }
+
+    #[test]
+    fn matches_languages_by_prefix() {
+        let mut episode = make_episode();
+        let languages = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        episode.language = Some("en-US".to_string());
+        assert!(matches_language(&episode, &languages(&["en"])));
+        assert!(matches_language(&episode, &languages(&["de", "en_us"])));
+        assert!(!matches_language(&episode, &languages(&["de"])));
+        assert!(!matches_language(&episode, &languages(&["e"])));
+        assert!(matches_language(&episode, &[]));
+
+        episode.language = None;
+        assert!(matches_language(&episode, &languages(&["de"])));
+    }
+}
@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use futures::StreamExt;
+
+use crate::error::DownloadError;
+use crate::feed::Transcript;
+use crate::http::HttpClient;
+
+/// Marks transcript files, between the episode's filename stem and the
+/// format extension (`2024-01-15-title.transcript.vtt`)
+pub const TRANSCRIPT_INFIX: &str = ".transcript";
+
+/// File extension for a transcript, from its MIME type or URL
+///
+/// Defaults to "txt" if neither identifies the format.
+pub fn transcript_extension(transcript: &Transcript) -> String {
+    if let Some(ext) = transcript.mime_type.as_deref().and_then(mime_to_extension) {
+        return ext.to_string();
+    }
+
+    transcript
+        .url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|filename| filename.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| {
+            !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        .unwrap_or_else(|| "txt".to_string())
+}
+
+/// Filenames for an episode's transcripts, in the same order
+///
+/// Each is named `{stem}.transcript.{ext}`. When several transcripts share a
+/// format, the later ones get their language (or position) inserted, as in
+/// `{stem}.transcript.de.vtt`.
+pub fn transcript_filenames(stem: &str, transcripts: &[Transcript]) -> Vec<String> {
+    let mut filenames: Vec<String> = Vec::with_capacity(transcripts.len());
+
+    for (index, transcript) in transcripts.iter().enumerate() {
+        let ext = transcript_extension(transcript);
+        let mut filename = format!("{}{}.{}", stem, TRANSCRIPT_INFIX, ext);
+
+        if filenames.contains(&filename) {
+            let qualifier = transcript
+                .language
+                .as_deref()
+                .map(sanitize_filename::sanitize)
+                .filter(|language| !language.is_empty())
+                .unwrap_or_else(|| (index + 1).to_string());
+            filename = format!("{}{}.{}.{}", stem, TRANSCRIPT_INFIX, qualifier, ext);
+        }
+
+        if filenames.contains(&filename) {
+            filename = format!("{}{}.{}.{}", stem, TRANSCRIPT_INFIX, index + 1, ext);
+        }
+
+        filenames.push(filename);
+    }
+
+    filenames
+}
+
+/// Download a transcript file
+///
+/// Transcripts are small, so the body is collected in memory and written in
+/// one go.
+pub async fn download_transcript<C: HttpClient>(
+    client: &C,
+    transcript: &Transcript,
+    output_path: &Path,
+) -> Result<(), DownloadError> {
+    let url = transcript.url.as_str();
+
+    let response = client
+        .get_stream(url)
+        .await
+        .map_err(|e| DownloadError::HttpFailed {
+            url: url.to_string(),
+            source: e,
+        })?;
+
+    if !(200..300).contains(&response.status) {
+        return Err(DownloadError::HttpStatus {
+            url: url.to_string(),
+            status: response.status,
+            retry_after: response.retry_after,
+        });
+    }
+
+    let mut body = Vec::new();
+    let mut stream = response.body;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| DownloadError::StreamFailed {
+            url: url.to_string(),
+            source: e,
+        })?;
+        body.extend_from_slice(&chunk);
+    }
+
+    tokio::fs::write(output_path, body)
+        .await
+        .map_err(|e| DownloadError::FileWriteFailed {
+            path: output_path.to_path_buf(),
+            source: e,
+        })
+}
+
+/// Map a transcript MIME type to a file extension
+fn mime_to_extension(mime: &str) -> Option<&'static str> {
+    let mime = mime.split(';').next().unwrap_or(mime).trim();
+
+    match mime.to_lowercase().as_str() {
+        "text/vtt" => Some("vtt"),
+        "application/x-subrip" | "application/srt" | "text/srt" => Some("srt"),
+        "application/json" => Some("json"),
+        "text/html" => Some("html"),
+        "text/plain" => Some("txt"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    fn transcript(url: &str, mime_type: Option<&str>, language: Option<&str>) -> Transcript {
+        Transcript {
+            url: Url::parse(url).unwrap(),
+            mime_type: mime_type.map(String::from),
+            language: language.map(String::from),
+        }
+    }
+
+    #[test]
+    fn extension_from_mime_type_or_url() {
+        let srt = transcript("https://example.com/t", Some("application/x-subrip"), None);
+        assert_eq!(transcript_extension(&srt), "srt");
+
+        let json = transcript("https://example.com/t.JSON", Some("unknown/type"), None);
+        assert_eq!(transcript_extension(&json), "json");
+
+        let unknown = transcript("https://example.com/transcript", None, None);
+        assert_eq!(transcript_extension(&unknown), "txt");
+    }
+
+    #[test]
+    fn filenames_are_unique_per_format() {
+        let transcripts = [
+            transcript("https://example.com/en.vtt", Some("text/vtt"), Some("en")),
+            transcript("https://example.com/de.vtt", Some("text/vtt"), Some("de")),
+            transcript("https://example.com/x.vtt", Some("text/vtt"), None),
+            transcript("https://example.com/t.json", Some("application/json"), None),
+        ];
+
+        assert_eq!(
+            transcript_filenames("2024-01-15-ep", &transcripts),
+            vec![
+                "2024-01-15-ep.transcript.vtt",
+                "2024-01-15-ep.transcript.de.vtt",
+                "2024-01-15-ep.transcript.3.vtt",
+                "2024-01-15-ep.transcript.json",
+            ]
+        );
+    }
+}
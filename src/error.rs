@@ -15,6 +15,12 @@ pub enum FeedError {
         source: reqwest::Error,
     },
 
+    #[error("Fetching feed from {url} timed out after {timeout:?}")]
+    TimedOut {
+        url: String,
+        timeout: std::time::Duration,
+    },
+
     #[error("Failed to read feed file {path}: {source}")]
     FileReadFailed {
         path: PathBuf,
@@ -35,6 +41,17 @@ pub enum FeedError {
     InvalidDate { date_str: String, reason: String },
 }
 
+impl FeedError {
+    /// Whether the failure may go away when the feed is fetched again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FeedError::FetchFailed { source, .. } => crate::retry::is_transient(source),
+            FeedError::TimedOut { .. } => true,
+            _ => false,
+        }
+    }
+}
+
 /// Errors that can occur during episode downloads
 #[derive(Error, Debug)]
 pub enum DownloadError {
@@ -46,7 +63,24 @@ pub enum DownloadError {
     },
 
     #[error("HTTP error {status} for {url}")]
-    HttpStatus { url: String, status: u16 },
+    HttpStatus {
+        url: String,
+        status: u16,
+        /// Wait requested by the server's `Retry-After` header
+        retry_after: Option<std::time::Duration>,
+    },
+
+    #[error("No response from {url} for {timeout:?}")]
+    TimedOut {
+        url: String,
+        timeout: std::time::Duration,
+    },
+
+    #[error("Download of {url} stalled, no data for {idle:?}")]
+    Stalled {
+        url: String,
+        idle: std::time::Duration,
+    },
 
     #[error("Failed to create file {path}: {source}")]
     FileCreateFailed {
@@ -69,6 +103,19 @@ pub enum DownloadError {
         source: reqwest::Error,
     },
 
+    #[error("Download of {url} ended after {received} of {expected} bytes")]
+    Truncated {
+        url: String,
+        expected: u64,
+        received: u64,
+    },
+
+    #[error("{url} is a video")]
+    Video { url: String },
+
+    #[error("{url} is larger than the limit of {limit} bytes")]
+    TooLarge { url: String, limit: u64 },
+
     #[error("Failed to rename {partial_path} to {final_path}: {source}")]
     RenameFailed {
         partial_path: PathBuf,
@@ -78,6 +125,39 @@ pub enum DownloadError {
     },
 }
 
+impl DownloadError {
+    /// Whether the failure may go away when the download is retried
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DownloadError::HttpFailed { source, .. } => crate::retry::is_transient(source),
+            DownloadError::HttpStatus { status, .. } => crate::retry::is_transient_status(*status),
+            // The connection broke off or stalled mid-download
+            DownloadError::StreamFailed { .. }
+            | DownloadError::TimedOut { .. }
+            | DownloadError::Stalled { .. }
+            | DownloadError::Truncated { .. } => true,
+            DownloadError::FileCreateFailed { .. }
+            | DownloadError::FileWriteFailed { .. }
+            | DownloadError::TooLarge { .. }
+            | DownloadError::Video { .. }
+            | DownloadError::RenameFailed { .. } => false,
+        }
+    }
+
+    /// How long a throttling server (`429`, `503`) asked to wait before
+    /// retrying
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            DownloadError::HttpStatus {
+                status: 429 | 503,
+                retry_after,
+                ..
+            } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
 /// Errors that can occur during metadata operations
 #[derive(Error, Debug)]
 pub enum MetadataError {
@@ -104,6 +184,13 @@ pub enum MetadataError {
 
     #[error("Failed to serialize metadata: {0}")]
     JsonSerializeFailed(#[from] serde_json::Error),
+
+    #[error("Failed to write tags to {path}: {source}")]
+    TagWriteFailed {
+        path: PathBuf,
+        #[source]
+        source: id3::Error,
+    },
 }
 
 /// Errors that can occur when scanning the output directory
@@ -128,6 +215,106 @@ pub enum StateError {
 
     #[error("Metadata error: {0}")]
     Metadata(#[from] MetadataError),
+
+    #[error("Unsupported state export version {0}")]
+    UnsupportedExportVersion(u32),
+
+    #[error("State export lists a file outside the output directory: {0}")]
+    UnsafeExportPath(String),
+
+    #[cfg(feature = "sqlite")]
+    #[error("Library database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+/// Errors that can occur when reading subscription lists
+#[derive(Error, Debug)]
+pub enum SubscriptionError {
+    #[error("Failed to read subscription file {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse OPML: {0}")]
+    ParseFailed(#[from] quick_xml::Error),
+
+    #[error("Failed to read library directory {path}: {source}")]
+    LibraryReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(
+        "Invalid directory template '{0}' (placeholders: {{title}}, {{author}}, {{year}}, {{host}}, optionally with :slug)"
+    )]
+    InvalidTemplate(String),
+
+    #[error("Failed to rename {from} to {to}: {source}")]
+    RenameFailed {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors parsing human-readable option values
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UnitError {
+    #[error("Invalid size '{0}' (expected e.g. \"500K\", \"1.5G\" or a number of bytes)")]
+    InvalidSize(String),
+
+    #[error("Invalid duration '{0}' (expected e.g. \"45m\", \"1h30m\" or a number of seconds)")]
+    InvalidDuration(String),
+
+    #[error(
+        "Invalid filename template '{0}' (placeholders: {{date}}, {{year}}, {{month}}, {{day}}, {{title}}, {{episode}}, {{season}}; numbers optionally padded, e.g. {{episode:03}})"
+    )]
+    InvalidFilenameTemplate(String),
+}
+
+/// Errors that can occur when reading a per-podcast settings file
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Failed to read settings file {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid settings file {path}, line {line}: {message}")]
+    Invalid {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+/// Reasons an output directory is considered unhealthy
+#[derive(Error, Debug)]
+pub enum HealthError {
+    #[error("Output directory does not exist: {0}")]
+    DirectoryNotFound(PathBuf),
+
+    #[error("Output directory {path} is not writable: {source}")]
+    NotWritable {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("No sync found: {0}")]
+    NeverSynced(#[source] MetadataError),
+
+    #[error("Invalid sync timestamp '{0}' in podcast.json")]
+    InvalidTimestamp(String),
+
+    #[error("Last sync was {age_hours} hours ago (limit: {max_age_hours} hours)")]
+    SyncTooOld { age_hours: i64, max_age_hours: i64 },
 }
 
 /// Top-level errors for sync operations
@@ -142,6 +329,21 @@ pub enum SyncError {
     #[error("Metadata error: {0}")]
     Metadata(#[from] MetadataError),
 
+    #[error("Settings error: {0}")]
+    Settings(#[from] SettingsError),
+
     #[error("All downloads failed")]
     AllDownloadsFailed,
+
+    #[error("Read-only mode: {pending} new episode(s) would be written to {path}")]
+    ReadOnly { path: PathBuf, pending: usize },
+
+    #[error(
+        "Stopped downloading: only {available} bytes free at {path}, at least {required} required"
+    )]
+    InsufficientSpace {
+        path: PathBuf,
+        available: u64,
+        required: u64,
+    },
 }
@@ -8,7 +8,7 @@ use bytes::Bytes;
 use url::Url;
 
 use crate::error::FeedError;
-use crate::http::HttpClient;
+use crate::http::{CacheValidators, ConditionalResponse, HttpClient};
 
 use super::parse::{Podcast, parse_feed};
 
@@ -24,6 +24,24 @@ pub async fn fetch_feed_bytes<C: HttpClient>(client: &C, url: &str) -> Result<By
     Ok(bytes)
 }
 
+/// Fetch raw feed bytes with a conditional request (without parsing)
+///
+/// Sends `If-None-Match`/`If-Modified-Since` from `validators`, so an
+/// unchanged feed costs a `304 Not Modified` instead of the full document.
+pub async fn fetch_feed_bytes_conditional<C: HttpClient>(
+    client: &C,
+    url: &str,
+    validators: &CacheValidators,
+) -> Result<ConditionalResponse, FeedError> {
+    client
+        .get_bytes_conditional(url, validators)
+        .await
+        .map_err(|e| FeedError::FetchFailed {
+            url: url.to_string(),
+            source: e,
+        })
+}
+
 /// Read raw feed bytes from a local file (without parsing)
 pub fn read_feed_file(path: &Path) -> Result<Vec<u8>, FeedError> {
     std::fs::read(path).map_err(|e| FeedError::FileReadFailed {
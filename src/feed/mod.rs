@@ -6,6 +6,10 @@ mod fetch;
 mod parse;
 
 pub use fetch::{
-    fetch_feed, fetch_feed_bytes, file_path_to_url, is_url, parse_feed_file, read_feed_file,
+    fetch_feed, fetch_feed_bytes, fetch_feed_bytes_conditional, file_path_to_url, is_url,
+    parse_feed_file, read_feed_file,
+};
+pub use parse::{
+    AlternateEnclosure, Enclosure, Episode, FundingLink, Podcast, Transcript, parse_feed,
+    parse_feed_lenient, repair_xml,
 };
-pub use parse::{Enclosure, Episode, Podcast, parse_feed};
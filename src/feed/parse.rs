@@ -2,6 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, FixedOffset};
 use html_escape::decode_html_entities;
 use url::Url;
@@ -17,9 +19,21 @@ pub struct Podcast {
     pub author: Option<String>,
     pub image_url: Option<Url>,
     pub feed_url: Url,
+    /// New location announced via `itunes:new-feed-url`, if any
+    pub new_feed_url: Option<Url>,
+    /// Donation and support links (`podcast:funding`, `atom:link rel="payment"`)
+    pub funding: Vec<FundingLink>,
     pub episodes: Vec<Episode>,
 }
 
+/// A link where listeners can support the show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FundingLink {
+    pub url: Url,
+    /// Call to action shown for the link (e.g. "Support the show")
+    pub title: Option<String>,
+}
+
 /// Represents a single podcast episode
 #[derive(Debug, Clone)]
 pub struct Episode {
@@ -28,9 +42,23 @@ pub struct Episode {
     pub pub_date: Option<DateTime<FixedOffset>>,
     pub guid: Option<String>,
     pub enclosure: Enclosure,
+    /// Additional encodings offered via `podcast:alternateEnclosure`
+    pub alternate_enclosures: Vec<AlternateEnclosure>,
+    /// Transcripts linked via `podcast:transcript`
+    pub transcripts: Vec<Transcript>,
     pub duration: Option<String>,
     pub episode_number: Option<u32>,
     pub season_number: Option<u32>,
+    /// Language tag such as `en` or `de-AT`, from the item's `dc:language`
+    /// or, failing that, the channel's `<language>`
+    pub language: Option<String>,
+    /// Raw values of namespaced elements not covered by the typed fields
+    ///
+    /// Keys are qualified element names (`podcast:locked`); attributes are
+    /// stored under `element@attribute` (`podcast:transcript@url`). When an
+    /// element occurs several times, the first occurrence is kept. iTunes and
+    /// Dublin Core elements are parsed by the rss crate and not included.
+    pub extensions: BTreeMap<String, String>,
 }
 
 /// Represents the audio file attached to an episode
@@ -41,6 +69,26 @@ pub struct Enclosure {
     pub mime_type: Option<String>,
 }
 
+/// An alternative encoding of an episode's media (`podcast:alternateEnclosure`)
+#[derive(Debug, Clone)]
+pub struct AlternateEnclosure {
+    pub enclosure: Enclosure,
+    /// Encoding bitrate in bits per second, if declared
+    pub bitrate: Option<u64>,
+    pub title: Option<String>,
+    /// Whether the feed marks this as the default encoding
+    pub default: bool,
+}
+
+/// A transcript of an episode (`podcast:transcript`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    pub url: Url,
+    /// Format of the transcript, e.g. "text/vtt" or "application/x-subrip"
+    pub mime_type: Option<String>,
+    pub language: Option<String>,
+}
+
 /// Parse RSS feed XML bytes into a Podcast struct
 pub fn parse_feed(xml_bytes: &[u8], feed_url: Url) -> Result<Podcast, FeedError> {
     let channel = rss::Channel::read_from(xml_bytes)?;
@@ -48,7 +96,7 @@ pub fn parse_feed(xml_bytes: &[u8], feed_url: Url) -> Result<Podcast, FeedError>
     let episodes = channel
         .items()
         .iter()
-        .filter_map(|item| parse_episode(item).ok())
+        .filter_map(|item| parse_episode(item, channel.language()).ok())
         .collect();
 
     let image_url = channel
@@ -66,19 +114,183 @@ pub fn parse_feed(xml_bytes: &[u8], feed_url: Url) -> Result<Podcast, FeedError>
         .and_then(|ext| ext.author().map(String::from))
         .or_else(|| channel.managing_editor().map(String::from));
 
+    let new_feed_url = channel
+        .itunes_ext()
+        .and_then(|ext| ext.new_feed_url())
+        .and_then(|url| Url::parse(url.trim()).ok());
+
     Ok(Podcast {
         title: decode_html_entities(channel.title()).into_owned(),
         description: Some(decode_html_entities(channel.description()).into_owned())
             .filter(|s| !s.is_empty()),
-        link: Url::parse(channel.link()).ok(),
+        link: Url::parse(channel.link())
+            .ok()
+            .or_else(|| parse_alternate_link(&channel)),
         author: author.map(|a| decode_html_entities(&a).into_owned()),
         image_url,
         feed_url,
+        new_feed_url,
+        funding: parse_funding(&channel),
         episodes,
     })
 }
 
-fn parse_episode(item: &rss::Item) -> Result<Episode, FeedError> {
+/// Channel `atom:link` elements with the given `rel` (missing `rel` counts as "alternate")
+fn atom_links<'a>(channel: &'a rss::Channel, rel: &'a str) -> impl Iterator<Item = Url> + 'a {
+    channel
+        .extensions()
+        .get("atom")
+        .and_then(|ext| ext.get("link"))
+        .into_iter()
+        .flatten()
+        .filter(move |link| link.attrs.get("rel").map_or("alternate", String::as_str) == rel)
+        .filter_map(|link| link.attrs.get("href"))
+        .filter_map(|href| Url::parse(href.trim()).ok())
+}
+
+/// Website link from `atom:link rel="alternate"`, for feeds without a usable `<link>`
+fn parse_alternate_link(channel: &rss::Channel) -> Option<Url> {
+    atom_links(channel, "alternate").next()
+}
+
+fn parse_funding(channel: &rss::Channel) -> Vec<FundingLink> {
+    let mut funding: Vec<FundingLink> = channel
+        .extensions()
+        .get("podcast")
+        .and_then(|ext| ext.get("funding"))
+        .into_iter()
+        .flatten()
+        .filter_map(|element| {
+            let url = Url::parse(element.attrs.get("url")?.trim()).ok()?;
+            let title = element
+                .value()
+                .map(|v| decode_html_entities(v.trim()).into_owned())
+                .filter(|v| !v.is_empty());
+            Some(FundingLink { url, title })
+        })
+        .collect();
+
+    for url in atom_links(channel, "payment") {
+        if !funding.iter().any(|link| link.url == url) {
+            funding.push(FundingLink { url, title: None });
+        }
+    }
+
+    funding
+}
+
+/// Parse feed XML, repairing common XML errors if the feed is malformed
+///
+/// The feed is parsed as-is first. Only if that fails is it cleaned up with
+/// [`repair_xml`] and parsed again; if the repaired feed still fails, the
+/// original error is returned.
+pub fn parse_feed_lenient(xml_bytes: &[u8], feed_url: Url) -> Result<Podcast, FeedError> {
+    match parse_feed(xml_bytes, feed_url.clone()) {
+        Err(FeedError::ParseFailed(original)) => {
+            parse_feed(&repair_xml(xml_bytes), feed_url).map_err(|_| original.into())
+        }
+        result => result,
+    }
+}
+
+/// XML entities every parser knows without a DTD
+const PREDEFINED_ENTITIES: [&[u8]; 5] = [b"amp", b"lt", b"gt", b"quot", b"apos"];
+
+/// Clean up XML errors commonly found in hand-made feeds
+///
+/// - Bare `&` (as in `AT&T`) is escaped as `&amp;`
+/// - HTML entities such as `&nbsp;` are replaced by numeric character references
+/// - Control characters that XML forbids are removed
+///
+/// CDATA sections and comments keep their content apart from control characters.
+pub fn repair_xml(xml: &[u8]) -> Vec<u8> {
+    let mut repaired = Vec::with_capacity(xml.len());
+    let mut pos = 0;
+
+    while pos < xml.len() {
+        let rest = &xml[pos..];
+
+        let verbatim_end = [(&b"<![CDATA["[..], &b"]]>"[..]), (b"<!--", b"-->")]
+            .iter()
+            .find(|(start, _)| rest.starts_with(start))
+            .map(|(start, end)| {
+                find_bytes(&rest[start.len()..], end)
+                    .map_or(rest.len(), |index| start.len() + index + end.len())
+            });
+
+        if let Some(end) = verbatim_end {
+            repaired.extend(rest[..end].iter().filter(|&&b| !is_forbidden_control(b)));
+            pos += end;
+            continue;
+        }
+
+        match rest[0] {
+            b'&' => pos += repair_entity(rest, &mut repaired),
+            b if is_forbidden_control(b) => pos += 1,
+            b => {
+                repaired.push(b);
+                pos += 1;
+            }
+        }
+    }
+
+    repaired
+}
+
+/// Copy or fix the entity reference at the start of `rest`, returning the
+/// number of bytes consumed
+fn repair_entity(rest: &[u8], repaired: &mut Vec<u8>) -> usize {
+    // Longest HTML entity names are around 30 characters
+    let name_end = rest
+        .iter()
+        .take(40)
+        .position(|&b| b == b';')
+        .filter(|&end| end > 1);
+
+    if let Some(end) = name_end {
+        let name = &rest[1..end];
+
+        let valid_numeric = match name {
+            [b'#', b'x' | b'X', hex @ ..] => {
+                !hex.is_empty() && hex.iter().all(u8::is_ascii_hexdigit)
+            }
+            [b'#', digits @ ..] => !digits.is_empty() && digits.iter().all(u8::is_ascii_digit),
+            _ => false,
+        };
+
+        if valid_numeric || PREDEFINED_ENTITIES.contains(&name) {
+            repaired.extend_from_slice(&rest[..=end]);
+            return end + 1;
+        }
+
+        if name.iter().all(u8::is_ascii_alphanumeric) {
+            let reference = std::str::from_utf8(&rest[..=end]).unwrap_or_default();
+            let decoded = decode_html_entities(reference);
+            if decoded != reference {
+                for c in decoded.chars() {
+                    repaired.extend_from_slice(format!("&#{};", c as u32).as_bytes());
+                }
+                return end + 1;
+            }
+        }
+    }
+
+    repaired.extend_from_slice(b"&amp;");
+    1
+}
+
+/// Control characters not allowed anywhere in an XML 1.0 document
+fn is_forbidden_control(b: u8) -> bool {
+    b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r')
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_episode(item: &rss::Item, channel_language: Option<&str>) -> Result<Episode, FeedError> {
     let title = item
         .title()
         .map(|t| decode_html_entities(t).into_owned())
@@ -105,6 +317,16 @@ fn parse_episode(item: &rss::Item) -> Result<Episode, FeedError> {
 
     let itunes = item.itunes_ext();
 
+    // Multilingual feeds tag items individually
+    let language = item
+        .dublin_core_ext()
+        .and_then(|dc| dc.languages().first())
+        .map(String::as_str)
+        .or(channel_language)
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+        .map(String::from);
+
     Ok(Episode {
         title,
         description: item
@@ -117,12 +339,100 @@ fn parse_episode(item: &rss::Item) -> Result<Episode, FeedError> {
             length: enclosure.length().parse().ok(),
             mime_type: Some(enclosure.mime_type().to_string()).filter(|s| !s.is_empty()),
         },
+        alternate_enclosures: parse_alternate_enclosures(item),
+        transcripts: parse_transcripts(item),
         duration: itunes.and_then(|ext| ext.duration().map(String::from)),
         episode_number: itunes.and_then(|ext| ext.episode().and_then(|e| e.parse().ok())),
         season_number: itunes.and_then(|ext| ext.season().and_then(|s| s.parse().ok())),
+        language,
+        extensions: flatten_extensions(item.extensions()),
     })
 }
 
+/// Flatten the item's extension map into `prefix:name` (and `@attribute`) keys
+fn flatten_extensions(extensions: &rss::extension::ExtensionMap) -> BTreeMap<String, String> {
+    let mut flattened = BTreeMap::new();
+
+    for (prefix, elements) in extensions {
+        for (name, occurrences) in elements {
+            let Some(element) = occurrences.first() else {
+                continue;
+            };
+            let key = format!("{}:{}", prefix, name);
+
+            for (attribute, value) in &element.attrs {
+                flattened.insert(format!("{}@{}", key, attribute), value.clone());
+            }
+            if let Some(value) = element.value() {
+                flattened.insert(key, decode_html_entities(value).into_owned());
+            }
+        }
+    }
+
+    flattened
+}
+
+/// Parse `podcast:alternateEnclosure` elements of an item
+///
+/// Each alternate enclosure may list several `podcast:source` URIs for the
+/// same file; the first one with a valid URL is used.
+fn parse_alternate_enclosures(item: &rss::Item) -> Vec<AlternateEnclosure> {
+    let Some(elements) = item
+        .extensions()
+        .get("podcast")
+        .and_then(|ext| ext.get("alternateEnclosure"))
+    else {
+        return Vec::new();
+    };
+
+    elements
+        .iter()
+        .filter_map(|element| {
+            let url = element
+                .children
+                .get("source")?
+                .iter()
+                .filter_map(|source| source.attrs.get("uri"))
+                .find_map(|uri| Url::parse(uri).ok())?;
+
+            Some(AlternateEnclosure {
+                enclosure: Enclosure {
+                    url,
+                    length: element.attrs.get("length").and_then(|l| l.parse().ok()),
+                    mime_type: element.attrs.get("type").cloned().filter(|s| !s.is_empty()),
+                },
+                bitrate: element
+                    .attrs
+                    .get("bitrate")
+                    .and_then(|b| b.parse::<f64>().ok())
+                    .map(|b| b as u64),
+                title: element.attrs.get("title").cloned(),
+                default: element.attrs.get("default").is_some_and(|d| d == "true"),
+            })
+        })
+        .collect()
+}
+
+fn parse_transcripts(item: &rss::Item) -> Vec<Transcript> {
+    item.extensions()
+        .get("podcast")
+        .and_then(|ext| ext.get("transcript"))
+        .into_iter()
+        .flatten()
+        .filter_map(|element| {
+            Some(Transcript {
+                url: Url::parse(element.attrs.get("url")?.trim()).ok()?,
+                mime_type: element.attrs.get("type").cloned().filter(|s| !s.is_empty()),
+                language: element
+                    .attrs
+                    .get("language")
+                    .cloned()
+                    .filter(|s| !s.is_empty()),
+            })
+        })
+        .collect()
+}
+
 /// Try to parse dates that don't strictly conform to RFC 2822
 fn parse_relaxed_date(date_str: &str) -> Result<DateTime<FixedOffset>, chrono::ParseError> {
     // Try common alternative formats
@@ -213,6 +523,131 @@ mod tests {
         assert!(ep2.episode_number.is_none());
     }
 
+    #[test]
+    fn parse_feed_reads_item_languages() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <language>en-us</language>
+    <item>
+      <title>Folge</title>
+      <dc:language>de</dc:language>
+      <enclosure url="https://example.com/de.mp3" type="audio/mpeg"/>
+    </item>
+    <item>
+      <title>Episode</title>
+      <enclosure url="https://example.com/en.mp3" type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+
+        assert_eq!(podcast.episodes[0].language.as_deref(), Some("de"));
+        assert_eq!(podcast.episodes[1].language.as_deref(), Some("en-us"));
+    }
+
+    #[test]
+    fn parse_feed_exposes_raw_extensions() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:podcast="https://podcastindex.org/namespace/1.0" xmlns:media="http://search.yahoo.com/mrss/">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <item>
+      <title>Episode</title>
+      <enclosure url="https://example.com/ep.mp3" type="audio/mpeg"/>
+      <podcast:transcript url="https://example.com/ep.vtt" type="text/vtt"/>
+      <podcast:person role="host">Jane Doe</podcast:person>
+      <podcast:person role="guest">John Doe</podcast:person>
+      <media:rating>nonadult</media:rating>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+        let extensions = &podcast.episodes[0].extensions;
+
+        assert_eq!(
+            extensions.get("podcast:transcript@url").map(String::as_str),
+            Some("https://example.com/ep.vtt")
+        );
+        assert!(!extensions.contains_key("podcast:transcript"));
+        assert_eq!(
+            extensions.get("podcast:person").map(String::as_str),
+            Some("Jane Doe")
+        );
+        assert_eq!(
+            extensions.get("podcast:person@role").map(String::as_str),
+            Some("host")
+        );
+        assert_eq!(
+            extensions.get("media:rating").map(String::as_str),
+            Some("nonadult")
+        );
+    }
+
+    #[test]
+    fn parse_feed_extracts_new_feed_url() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <itunes:new-feed-url>https://new.example.com/feed.xml</itunes:new-feed-url>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+        assert_eq!(
+            podcast.new_feed_url,
+            Some(Url::parse("https://new.example.com/feed.xml").unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_feed_extracts_funding_and_alternate_link() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:podcast="https://podcastindex.org/namespace/1.0">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <atom:link rel="self" href="https://example.com/feed.xml"/>
+    <atom:link rel="alternate" href="https://example.com/show"/>
+    <atom:link rel="payment" href="https://example.com/donate"/>
+    <atom:link rel="payment" href="https://patreon.com/show"/>
+    <podcast:funding url="https://patreon.com/show">Support us &amp; get perks</podcast:funding>
+    <podcast:funding url="not a url">Broken</podcast:funding>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+
+        assert_eq!(
+            podcast.link,
+            Some(Url::parse("https://example.com/show").unwrap())
+        );
+        assert_eq!(
+            podcast.funding,
+            vec![
+                FundingLink {
+                    url: Url::parse("https://patreon.com/show").unwrap(),
+                    title: Some("Support us & get perks".to_string()),
+                },
+                FundingLink {
+                    url: Url::parse("https://example.com/donate").unwrap(),
+                    title: None,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn parse_feed_skips_items_without_enclosure() {
         let feed_no_enclosure = r#"<?xml version="1.0"?>
@@ -231,6 +666,129 @@ mod tests {
         assert!(podcast.episodes.is_empty());
     }
 
+    #[test]
+    fn repair_xml_fixes_entities_and_control_characters() {
+        let broken = b"<t>AT&T &nbsp;&amp; &#38; &#x26; &bogus; x\x0by</t><![CDATA[R&D\x01]]>";
+
+        assert_eq!(
+            String::from_utf8(repair_xml(broken)).unwrap(),
+            "<t>AT&amp;T &#160;&amp; &#38; &#x26; &amp;bogus; xy</t><![CDATA[R&D]]>"
+        );
+    }
+
+    #[test]
+    fn parse_feed_lenient_recovers_broken_feed() {
+        let feed = "<?xml version=\"1.0\"?>
+<rss version=\"2.0\">
+  <channel>
+    <title>Tom & Jerry&nbsp;Talk</title>
+    <description>Cartoons\u{1}</description>
+    <item>
+      <title>Episode</title>
+      <enclosure url=\"https://example.com/ep.mp3?a=1&b=2\" type=\"audio/mpeg\"/>
+    </item>
+  </channel>
+</rss>";
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        assert!(parse_feed(feed.as_bytes(), feed_url.clone()).is_err());
+
+        let podcast = parse_feed_lenient(feed.as_bytes(), feed_url).unwrap();
+        assert_eq!(podcast.title, "Tom & Jerry\u{a0}Talk");
+        assert_eq!(
+            podcast.episodes[0].enclosure.url.as_str(),
+            "https://example.com/ep.mp3?a=1&b=2"
+        );
+    }
+
+    #[test]
+    fn parse_feed_extracts_transcripts() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:podcast="https://podcastindex.org/namespace/1.0">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <item>
+      <title>Episode</title>
+      <enclosure url="https://example.com/ep.mp3" length="1000" type="audio/mpeg"/>
+      <podcast:transcript url="https://example.com/ep.vtt" type="text/vtt" language="en"/>
+      <podcast:transcript url="https://example.com/ep.srt" type="application/x-subrip"/>
+      <podcast:transcript type="text/html"/>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+
+        assert_eq!(
+            podcast.episodes[0].transcripts,
+            vec![
+                Transcript {
+                    url: Url::parse("https://example.com/ep.vtt").unwrap(),
+                    mime_type: Some("text/vtt".to_string()),
+                    language: Some("en".to_string()),
+                },
+                Transcript {
+                    url: Url::parse("https://example.com/ep.srt").unwrap(),
+                    mime_type: Some("application/x-subrip".to_string()),
+                    language: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_feed_extracts_alternate_enclosures() {
+        let feed = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:podcast="https://podcastindex.org/namespace/1.0">
+  <channel>
+    <title>Test</title>
+    <description>Test</description>
+    <item>
+      <title>Episode</title>
+      <enclosure url="https://example.com/ep.mp3" length="1000" type="audio/mpeg"/>
+      <podcast:alternateEnclosure type="audio/opus" length="500" bitrate="64000" title="Low">
+        <podcast:source uri="https://example.com/ep-64.opus"/>
+      </podcast:alternateEnclosure>
+      <podcast:alternateEnclosure type="audio/mpeg" bitrate="128000.5" default="true">
+        <podcast:source uri="not a url"/>
+        <podcast:source uri="https://example.com/ep-128.mp3"/>
+      </podcast:alternateEnclosure>
+      <podcast:alternateEnclosure type="audio/aac">
+      </podcast:alternateEnclosure>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed_url = Url::parse("https://example.com/feed.xml").unwrap();
+        let podcast = parse_feed(feed.as_bytes(), feed_url).unwrap();
+        let alternates = &podcast.episodes[0].alternate_enclosures;
+
+        // The enclosure without a source is dropped
+        assert_eq!(alternates.len(), 2);
+
+        assert_eq!(
+            alternates[0].enclosure.url.as_str(),
+            "https://example.com/ep-64.opus"
+        );
+        assert_eq!(
+            alternates[0].enclosure.mime_type,
+            Some("audio/opus".to_string())
+        );
+        assert_eq!(alternates[0].enclosure.length, Some(500));
+        assert_eq!(alternates[0].bitrate, Some(64000));
+        assert_eq!(alternates[0].title, Some("Low".to_string()));
+        assert!(!alternates[0].default);
+
+        assert_eq!(
+            alternates[1].enclosure.url.as_str(),
+            "https://example.com/ep-128.mp3"
+        );
+        assert_eq!(alternates[1].bitrate, Some(128000));
+        assert!(alternates[1].default);
+    }
+
     #[test]
     fn parse_feed_decodes_html_entities() {
         // Uses numeric character references (&#8212; for em dash, &#8230; for ellipsis)
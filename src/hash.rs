@@ -0,0 +1,120 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Content hashes of downloaded files, written as `<algorithm>:<hex>`
+
+use sha2::{Digest, Sha256, Sha512};
+
+/// Algorithm for the content hashes recorded in episode metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    /// Several times faster than SHA-256 on large files
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub(crate) const ALL: [HashAlgorithm; 3] = [Self::Sha256, Self::Sha512, Self::Blake3];
+
+    /// Prefix identifying the algorithm in a content hash, e.g. `sha256`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Blake3 => "blake3",
+        }
+    }
+
+    /// Split a content hash into its algorithm and hex digest
+    ///
+    /// Returns None for unknown algorithms and hashes without a prefix.
+    pub fn parse(hash: &str) -> Option<(Self, &str)> {
+        let (prefix, digest) = hash.split_once(':')?;
+        let algorithm = Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.prefix() == prefix)?;
+        Some((algorithm, digest))
+    }
+
+    /// Start hashing with this algorithm
+    pub fn hasher(self) -> ContentHasher {
+        ContentHasher(match self {
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+            Self::Sha512 => Hasher::Sha512(Sha512::new()),
+            Self::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        })
+    }
+}
+
+/// Incremental content hash, see [`HashAlgorithm::hasher`]
+#[derive(Clone)]
+pub struct ContentHasher(Hasher);
+
+#[derive(Clone)]
+enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl ContentHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.0 {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// The content hash, including the algorithm prefix
+    pub fn finalize(self) -> String {
+        match self.0 {
+            Hasher::Sha256(hasher) => format!("sha256:{:x}", hasher.finalize()),
+            Hasher::Sha512(hasher) => format!("sha512:{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => format!("blake3:{}", hasher.finalize().to_hex()),
+        }
+    }
+}
+
+impl std::io::Write for ContentHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_round_trip_through_parse() {
+        for algorithm in HashAlgorithm::ALL {
+            let mut hasher = algorithm.hasher();
+            hasher.update(b"foo");
+            let hash = hasher.finalize();
+
+            let (parsed, digest) = HashAlgorithm::parse(&hash).unwrap();
+            assert_eq!(parsed, algorithm);
+            assert!(digest.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+
+        let mut hasher = HashAlgorithm::Sha256.hasher();
+        hasher.update(b"foo");
+        assert_eq!(
+            hasher.finalize(),
+            "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert_eq!(HashAlgorithm::parse("md5:acbd18db"), None);
+        assert_eq!(HashAlgorithm::parse("abc123"), None);
+    }
+}
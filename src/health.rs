@@ -0,0 +1,168 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Health checks for an output directory, e.g. for container orchestration
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+
+use crate::error::{HealthError, MetadataError};
+use crate::metadata::{PODCAST_METADATA_FILENAME, read_podcast_metadata};
+
+const PROBE_FILENAME: &str = ".podpull-healthcheck";
+
+/// State of a healthy output directory
+#[derive(Debug, Clone)]
+pub struct HealthStatus {
+    /// When `podcast.json` was last written by a sync
+    pub last_sync: DateTime<FixedOffset>,
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+///
+/// Returns `None` where this cannot be determined (e.g. on non-Unix
+/// platforms).
+pub fn available_space(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        let stats = rustix::fs::statvfs(path).ok()?;
+        Some(stats.f_bavail.saturating_mul(stats.f_frsize))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Check that an output directory is writable and has been synced recently
+///
+/// The last sync time is taken from `podcast.json`, or from its modification
+/// time if it was written without timestamps. With `max_age` set, a
+/// sync older than that is reported as [`HealthError::SyncTooOld`].
+pub fn check_health(
+    output_dir: &Path,
+    max_age: Option<Duration>,
+) -> Result<HealthStatus, HealthError> {
+    if !output_dir.is_dir() {
+        return Err(HealthError::DirectoryNotFound(output_dir.to_path_buf()));
+    }
+
+    let probe = output_dir.join(PROBE_FILENAME);
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| HealthError::NotWritable {
+            path: output_dir.to_path_buf(),
+            source: e,
+        })?;
+
+    let metadata = read_podcast_metadata(output_dir).map_err(HealthError::NeverSynced)?;
+    let last_sync = match &metadata.updated_at {
+        Some(updated_at) => DateTime::parse_from_rfc3339(updated_at)
+            .map_err(|_| HealthError::InvalidTimestamp(updated_at.clone()))?,
+        None => metadata_modified(output_dir)?,
+    };
+
+    if let Some(max_age) = max_age {
+        let age = Utc::now().signed_duration_since(last_sync);
+        if age > max_age {
+            return Err(HealthError::SyncTooOld {
+                age_hours: age.num_hours(),
+                max_age_hours: max_age.num_hours(),
+            });
+        }
+    }
+
+    Ok(HealthStatus { last_sync })
+}
+
+/// Modification time of `podcast.json`, for reproducible metadata without `updated_at`
+fn metadata_modified(output_dir: &Path) -> Result<DateTime<FixedOffset>, HealthError> {
+    let path = output_dir.join(PODCAST_METADATA_FILENAME);
+    let modified = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| HealthError::NeverSynced(MetadataError::ReadFailed { path, source: e }))?;
+
+    Ok(DateTime::<Utc>::from(modified).fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{PodcastMetadata, save_podcast_metadata};
+    use tempfile::tempdir;
+
+    fn write_metadata(dir: &Path, updated_at: Option<DateTime<Utc>>) {
+        let metadata = PodcastMetadata {
+            title: "Test".to_string(),
+            description: None,
+            link: None,
+            author: None,
+            image_url: None,
+            feed_url: "https://example.com/feed.xml".to_string(),
+            updated_at: updated_at.map(|time| time.to_rfc3339()),
+            etag: None,
+            last_modified: None,
+            funding: vec![],
+        };
+        save_podcast_metadata(&metadata, dir).unwrap();
+    }
+
+    #[test]
+    fn healthy_after_recent_sync() {
+        let dir = tempdir().unwrap();
+        write_metadata(dir.path(), Some(Utc::now()));
+
+        let status = check_health(dir.path(), Some(Duration::hours(1))).unwrap();
+        assert!(status.last_sync <= Utc::now());
+        assert!(!dir.path().join(PROBE_FILENAME).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_available_space() {
+        let dir = tempdir().unwrap();
+        assert!(available_space(dir.path()).is_some());
+        assert_eq!(available_space(&dir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn falls_back_to_modification_time_without_timestamp() {
+        let dir = tempdir().unwrap();
+        write_metadata(dir.path(), None);
+
+        let status = check_health(dir.path(), Some(Duration::hours(1))).unwrap();
+        assert!(Utc::now().signed_duration_since(status.last_sync) < Duration::hours(1));
+    }
+
+    #[test]
+    fn unhealthy_without_sync() {
+        let dir = tempdir().unwrap();
+        let result = check_health(dir.path(), None);
+        assert!(matches!(result, Err(HealthError::NeverSynced(_))));
+    }
+
+    #[test]
+    fn unhealthy_when_sync_too_old() {
+        let dir = tempdir().unwrap();
+        write_metadata(dir.path(), Some(Utc::now() - Duration::hours(30)));
+
+        let result = check_health(dir.path(), Some(Duration::hours(24)));
+        assert!(matches!(
+            result,
+            Err(HealthError::SyncTooOld {
+                age_hours: 30,
+                max_age_hours: 24
+            })
+        ));
+    }
+
+    #[test]
+    fn unhealthy_when_directory_missing() {
+        let dir = tempdir().unwrap();
+        let result = check_health(&dir.path().join("missing"), None);
+        assert!(matches!(result, Err(HealthError::DirectoryNotFound(_))));
+    }
+}
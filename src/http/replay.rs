@@ -0,0 +1,284 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Record real HTTP responses to fixture files and replay them offline
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use super::{Credentials, HttpClient, HttpResponse, ReqwestClient};
+
+/// Response metadata stored next to the recorded body
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    final_url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    headers: BTreeMap<String, String>,
+}
+
+/// An [`HttpClient`] that records responses to fixture files, or replays
+/// them without network access
+///
+/// Each URL is stored as `<hash>.json` (status, final URL, headers) and
+/// `<hash>.body` in the fixture directory. Recording a URL again replaces
+/// its fixture. When replaying, URLs without a fixture are answered with
+/// `404 Not Found`. Bodies are held in memory, so this is meant for test
+/// fixtures rather than full-size episodes.
+///
+/// Requires the `replay` feature.
+#[derive(Debug, Clone)]
+pub struct ReplayClient<C = ReqwestClient> {
+    /// Client fetching the responses to record (None = replaying)
+    inner: Option<C>,
+    fixtures: PathBuf,
+}
+
+impl ReplayClient {
+    /// Answer requests from the fixtures recorded in `fixtures`
+    pub fn replay(fixtures: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: None,
+            fixtures: fixtures.into(),
+        }
+    }
+}
+
+impl<C: HttpClient> ReplayClient<C> {
+    /// Pass requests on to `inner` and record its responses in `fixtures`
+    ///
+    /// Panics on requests whose fixture cannot be written.
+    pub fn record(inner: C, fixtures: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: Some(inner),
+            fixtures: fixtures.into(),
+        }
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+        let stem = &hash[..16];
+        (
+            self.fixtures.join(format!("{}.json", stem)),
+            self.fixtures.join(format!("{}.body", stem)),
+        )
+    }
+
+    fn save(&self, fixture: &Fixture, body: &[u8]) {
+        let (metadata_path, body_path) = self.paths(&fixture.url);
+        let json = serde_json::to_string_pretty(fixture).expect("fixture is serializable");
+        std::fs::create_dir_all(&self.fixtures)
+            .and_then(|()| std::fs::write(&body_path, body))
+            .and_then(|()| std::fs::write(&metadata_path, json))
+            .unwrap_or_else(|e| panic!("failed to record fixture for {}: {}", fixture.url, e));
+    }
+
+    fn load(&self, url: &str) -> (Fixture, Bytes) {
+        let (metadata_path, body_path) = self.paths(url);
+        let fixture = std::fs::read(&metadata_path)
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Fixture>(&json).ok())
+            .filter(|fixture| fixture.url == url);
+        match (fixture, std::fs::read(&body_path)) {
+            (Some(fixture), Ok(body)) => (fixture, Bytes::from(body)),
+            _ => (
+                Fixture {
+                    url: url.to_string(),
+                    status: 404,
+                    final_url: None,
+                    headers: BTreeMap::new(),
+                },
+                Bytes::new(),
+            ),
+        }
+    }
+}
+
+/// The error a real client reports for an HTTP error status
+fn status_error(url: &str, status: u16) -> reqwest::Error {
+    let response = ::http::Response::builder()
+        .status(status)
+        .body(Vec::new())
+        .expect("recorded status is valid");
+    let error = reqwest::Response::from(response)
+        .error_for_status()
+        .expect_err("status is an error status");
+    match Url::parse(url) {
+        Ok(url) => error.with_url(url),
+        Err(_) => error.without_url(),
+    }
+}
+
+#[async_trait]
+impl<C: HttpClient> HttpClient for ReplayClient<C> {
+    async fn get_bytes(&self, url: &str) -> Result<Bytes, reqwest::Error> {
+        let Some(inner) = &self.inner else {
+            let (fixture, body) = self.load(url);
+            if fixture.status >= 400 {
+                return Err(status_error(url, fixture.status));
+            }
+            return Ok(body);
+        };
+
+        let result = inner.get_bytes(url).await;
+        let status = match &result {
+            Ok(_) => Some(200),
+            Err(e) => e.status().map(|status| status.as_u16()),
+        };
+        // Connection failures are not recorded; there is no response
+        if let Some(status) = status {
+            let fixture = Fixture {
+                url: url.to_string(),
+                status,
+                final_url: None,
+                headers: BTreeMap::new(),
+            };
+            self.save(&fixture, result.as_deref().unwrap_or_default());
+        }
+        result
+    }
+
+    async fn get_stream(&self, url: &str) -> Result<HttpResponse, reqwest::Error> {
+        let (fixture, body) = match &self.inner {
+            None => self.load(url),
+            Some(inner) => {
+                let response = inner.get_stream(url).await?;
+                let mut body = Vec::new();
+                let mut stream = response.body;
+                while let Some(chunk) = stream.next().await {
+                    body.extend_from_slice(&chunk?);
+                }
+
+                let fixture = Fixture {
+                    url: url.to_string(),
+                    status: response.status,
+                    final_url: response.final_url,
+                    headers: response.headers,
+                };
+                self.save(&fixture, &body);
+                (fixture, Bytes::from(body))
+            }
+        };
+
+        Ok(HttpResponse {
+            status: fixture.status,
+            content_length: Some(body.len() as u64),
+            final_url: fixture.final_url,
+            headers: fixture.headers,
+            peer_certificate: None,
+            retry_after: None,
+            body: Box::pin(futures::stream::once(async { Ok(body) })),
+        })
+    }
+
+    fn set_credentials(&mut self, host: &str, credentials: Credentials) {
+        if let Some(inner) = &mut self.inner {
+            inner.set_credentials(host, credentials);
+        }
+    }
+
+    fn set_headers(&mut self, host: &str, headers: Vec<(String, String)>) {
+        if let Some(inner) = &mut self.inner {
+            inner.set_headers(host, headers);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Serves a fixed feed and audio file, and `500` for everything else
+    #[derive(Clone)]
+    struct LiveClient;
+
+    #[async_trait]
+    impl HttpClient for LiveClient {
+        async fn get_bytes(&self, url: &str) -> Result<Bytes, reqwest::Error> {
+            match url {
+                "https://example.com/feed.xml" => Ok(Bytes::from_static(b"<rss/>")),
+                _ => Err(status_error(url, 500)),
+            }
+        }
+
+        async fn get_stream(&self, _url: &str) -> Result<HttpResponse, reqwest::Error> {
+            Ok(HttpResponse {
+                status: 200,
+                content_length: None,
+                final_url: Some("https://cdn.example.com/ep.mp3".to_string()),
+                headers: BTreeMap::from([("content-type".to_string(), "audio/mpeg".to_string())]),
+                peer_certificate: None,
+                retry_after: None,
+                body: Box::pin(futures::stream::iter([
+                    Ok(Bytes::from_static(b"fake ")),
+                    Ok(Bytes::from_static(b"audio")),
+                ])),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_responses() {
+        let dir = tempdir().unwrap();
+
+        let recorder = ReplayClient::record(LiveClient, dir.path());
+        recorder
+            .get_bytes("https://example.com/feed.xml")
+            .await
+            .unwrap();
+        recorder
+            .get_stream("https://example.com/ep.mp3")
+            .await
+            .unwrap();
+        assert!(
+            recorder
+                .get_bytes("https://example.com/broken")
+                .await
+                .is_err()
+        );
+
+        let replay = ReplayClient::replay(dir.path());
+        assert_eq!(
+            replay
+                .get_bytes("https://example.com/feed.xml")
+                .await
+                .unwrap(),
+            Bytes::from_static(b"<rss/>")
+        );
+
+        let response = replay
+            .get_stream("https://example.com/ep.mp3")
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_length, Some(10));
+        assert_eq!(
+            response.final_url.as_deref(),
+            Some("https://cdn.example.com/ep.mp3")
+        );
+        assert_eq!(response.headers["content-type"], "audio/mpeg");
+
+        let error = replay
+            .get_bytes("https://example.com/broken")
+            .await
+            .unwrap_err();
+        assert_eq!(error.status().map(|status| status.as_u16()), Some(500));
+
+        let missing = replay
+            .get_stream("https://example.com/other.mp3")
+            .await
+            .unwrap();
+        assert_eq!(missing.status, 404);
+    }
+}
@@ -2,30 +2,104 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+//! Download podcast feeds and their episodes into local directories
+//!
+//! Embedders should start from the [`prelude`], which is the stable API.
+//! The other modules expose lower-level building blocks; they are public
+//! for the `podpull` binary and advanced use, but are not covered by
+//! semantic versioning.
+
+pub mod prelude;
+
+#[doc(hidden)]
 pub mod episode;
 pub mod error;
+#[doc(hidden)]
 pub mod feed;
+#[doc(hidden)]
+pub mod hash;
+#[doc(hidden)]
+pub mod health;
+#[doc(hidden)]
 pub mod http;
+#[doc(hidden)]
 pub mod metadata;
+#[doc(hidden)]
 pub mod progress;
+#[doc(hidden)]
+pub mod retry;
+#[doc(hidden)]
+pub mod settings;
+#[doc(hidden)]
 pub mod state;
+#[doc(hidden)]
+pub mod subscriptions;
+#[doc(hidden)]
 pub mod sync;
+#[doc(hidden)]
+pub mod units;
 
-// Re-export main types for convenience
+// Re-export main types for convenience; beyond the prelude these are unstable
 pub use episode::{
-    DownloadContext, DownloadResult, download_episode, generate_filename, generate_filename_stem,
-    get_audio_extension,
+    BitratePreference, DownloadContext, DownloadResult, EnclosurePreference, FilenameTemplate,
+    MAX_TITLE_LENGTH, NUMBERED_TEMPLATE, ORIGINAL_TEMPLATE, OutputLayout, TRANSCRIPT_INFIX,
+    VideoPolicy, download_episode, download_transcript, generate_filename, generate_filename_stem,
+    get_audio_extension, is_video, matches_language, select_enclosure, transcript_extension,
+    transcript_filenames,
+};
+pub use error::{
+    DownloadError, FeedError, HealthError, MetadataError, SettingsError, StateError,
+    SubscriptionError, SyncError, UnitError,
 };
-pub use error::{DownloadError, FeedError, MetadataError, StateError, SyncError};
 pub use feed::{
-    Enclosure, Episode, Podcast, fetch_feed, fetch_feed_bytes, file_path_to_url, is_url,
-    parse_feed, parse_feed_file, read_feed_file,
+    AlternateEnclosure, Enclosure, Episode, FundingLink, Podcast, Transcript, fetch_feed,
+    fetch_feed_bytes, fetch_feed_bytes_conditional, file_path_to_url, is_url, parse_feed,
+    parse_feed_file, parse_feed_lenient, read_feed_file, repair_xml,
+};
+pub use hash::{ContentHasher, HashAlgorithm};
+pub use health::{HealthStatus, available_space, check_health};
+pub use http::{
+    AddressFamily, BandwidthLimiter, CacheValidators, ConditionalResponse, Credentials,
+    DEFAULT_USER_AGENT, DownloadBudget, HttpClient, HttpResponse, HttpVersion, ProbeResponse,
+    RECEIPT_HEADERS, RangeResponse, RateLimiter, ReqwestClient, ReqwestClientBuilder,
+    strip_credentials,
 };
-pub use http::{HttpClient, HttpResponse, ReqwestClient};
 pub use metadata::{
-    EpisodeMetadata, PodcastMetadata, read_episode_metadata, read_podcast_metadata,
-    write_episode_metadata, write_podcast_metadata,
+    ARCHIVE_DIR, CHAPTERS_SUFFIX, CHECKSUM_SUFFIX, CHECKSUMS_FILENAME, Chapter, ChaptersDocument,
+    DownloadReceipt, EPISODE_INDEX_FILENAME, EpisodeIndex, EpisodeMetadata, FileAttributes,
+    FundingMetadata, IndexedEpisode, PodcastMetadata, QUARANTINE_DIR, QuarantineNote,
+    RECEIPT_SUFFIX, SegmentFile, ShowNotesFormat, SkipSegment, TranscriptMetadata, VerifyReport,
+    XATTR_PREFIX, archive_episode, export_csv, has_feed_chapters, import_segments,
+    parse_chapters_json, parse_description_chapters, parse_itunes_duration, probe_duration,
+    quarantine_file, read_episode_index, read_episode_metadata, read_file_attributes,
+    read_podcast_metadata, read_segment_file, render_show_notes, save_episode_metadata,
+    save_podcast_metadata, verify_library, write_chapter_frames, write_chapters, write_checksums,
+    write_episode_index, write_episode_metadata, write_file_attributes, write_podcast_metadata,
+    write_receipt, write_show_notes, write_tags,
+};
+pub use progress::{
+    NoopReporter, ProgressEvent, ProgressReporter, SharedProgressReporter, SlotStatus,
+    SpeechCommand, SpeechReporter, StatusReporter, SyncPhase, SyncStatus,
+};
+pub use retry::RetryPolicy;
+pub use settings::{PodcastSettings, SETTINGS_FILENAME};
+pub use state::{
+    CORRUPT_SUFFIX, DownloadOrder, DownloadedEnclosure, EpisodeFilter, EpisodeKeys,
+    ExportedEpisode, FAILURES_FILENAME, FailureRecord, OutputState, PrunePolicy, PrunedEpisode,
+    RepairReport, RetentionPolicy, SCAN_CACHE_FILENAME, STATE_EXPORT_VERSION, StateExport,
+    StateImport, SyncPlan, UpdatePolicy, adopt_downloads, apply_retention, canonical_enclosure_url,
+    create_sync_plan, export_state, find_removed_episodes, import_state, normalize_guid,
+    prune_removed_episodes, read_failure_history, repair_output_dir, scan_output_dir,
+    scan_output_dir_read_only,
+};
+pub use subscriptions::{
+    DirTemplate, DuplicateSubscription, FeedListEntry, LIBRARY_INDEX_FILENAME, LibraryRename,
+    LibrarySync, PodcastEntry, Subscription, SubscriptionResult, canonical_feed_url,
+    deduplicate_subscriptions, export_opml, feed_dir_name, generate_opml, import_opml,
+    parse_feed_list, parse_opml, plan_library_renames, read_opml_file, rename_library_dir,
+    scan_library, subscription_dir_name, sync_feed_list, sync_podcasts, sync_subscriptions,
+};
+pub use sync::{
+    DryRunReport, PlannedDownload, SyncOptions, SyncResult, authenticate, sync_podcast,
 };
-pub use progress::{NoopReporter, ProgressEvent, ProgressReporter, SharedProgressReporter};
-pub use state::{OutputState, SyncPlan, create_sync_plan, scan_output_dir};
-pub use sync::{SyncOptions, SyncResult, sync_podcast};
+pub use units::{ByteSize, HumanDuration};
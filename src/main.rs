@@ -2,47 +2,266 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
-use console::Emoji;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use reqwest::header::{HeaderName, HeaderValue};
 
+mod cli;
+
+use cli::i18n::{self, Language, tr};
+use cli::reporter::IndicatifReporter;
+use cli::style::OutputStyle;
 use podpull::{
-    NoopReporter, ProgressEvent, ProgressReporter, ReqwestClient, SharedProgressReporter,
-    SyncOptions, sync_podcast,
+    AddressFamily, BitratePreference, ByteSize, Credentials, DirTemplate, DownloadOrder,
+    EnclosurePreference, FilenameTemplate, HashAlgorithm, HttpVersion, HumanDuration, NoopReporter,
+    OutputLayout, PodcastEntry, PodcastSettings, PrunePolicy, RateLimiter, ReqwestClient,
+    RetentionPolicy, RetryPolicy, SharedProgressReporter, ShowNotesFormat, SpeechCommand,
+    SpeechReporter, StateExport, Subscription, SubscriptionResult, SyncOptions, UpdatePolicy,
+    VideoPolicy, authenticate, check_health, deduplicate_subscriptions, export_csv, export_opml,
+    fetch_feed_bytes, file_path_to_url, import_segments, is_url, parse_feed, parse_feed_lenient,
+    parse_feed_list, plan_library_renames, read_feed_file, read_opml_file, read_podcast_metadata,
+    read_segment_file, rename_library_dir, repair_output_dir, sync_podcast, sync_podcasts,
+    sync_subscriptions, verify_library,
 };
 
-// Emoji with fallback for terminals without Unicode support
-static MICROPHONE: Emoji<'_, '_> = Emoji("🎙️  ", "");
-static GLOBE: Emoji<'_, '_> = Emoji("🌐 ", "[w] ");
-static COG: Emoji<'_, '_> = Emoji("⚙️  ", "[*] ");
-static SEARCH: Emoji<'_, '_> = Emoji("🔍 ", "[~] ");
-static HEADPHONES: Emoji<'_, '_> = Emoji("🎧 ", "[i] ");
-static SAVING: Emoji<'_, '_> = Emoji("💾 ", "[v] ");
-static SUCCESS: Emoji<'_, '_> = Emoji("✅ ", "[+] ");
-static FAILURE: Emoji<'_, '_> = Emoji("❌ ", "[!] ");
-static PARTY: Emoji<'_, '_> = Emoji("🎉 ", "[*] ");
-static FOLDER: Emoji<'_, '_> = Emoji("📁 ", "");
-static CROSS: Emoji<'_, '_> = Emoji("✗ ", "x ");
-static BROOM: Emoji<'_, '_> = Emoji("🧹 ", "[c] ");
-
 /// Download and synchronize podcasts from RSS feeds
 #[derive(Parser, Debug)]
 #[command(name = "podpull")]
 #[command(about = "Download and synchronize podcasts from RSS feeds")]
 #[command(version)]
-struct Args {
+#[command(
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    arg_required_else_help = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    sync: SyncArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Sync every feed of an OPML export into its own subdirectory
+    Import {
+        /// OPML file exported from a podcast app (AntennaPod, Overcast, ...)
+        opml: PathBuf,
+
+        /// Library directory receiving one subdirectory per podcast
+        library_dir: PathBuf,
+
+        #[command(flatten)]
+        options: Options,
+    },
+
+    /// Sync feeds from a newline-delimited list, e.g. piped from another tool
+    ///
+    /// Each line holds a feed URL, optionally followed by a tab and the
+    /// output directory (relative to the library directory). Feeds without
+    /// one get a directory named after their host and path.
+    #[command(
+        alias = "batch",
+        override_usage = "podpull sync [OPTIONS] <LIST> <LIBRARY_DIR>\n       \
+                          podpull sync [OPTIONS] --stdin <LIBRARY_DIR>"
+    )]
+    Sync {
+        /// Read the feed list from standard input
+        #[arg(long)]
+        stdin: bool,
+
+        /// File listing the feeds ("-" for standard input, left out with
+        /// --stdin), then the library directory the output directories are
+        /// relative to
+        #[arg(value_names = ["LIST", "LIBRARY_DIR"], num_args = 1..=2, required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Sync up to N feeds at once, sharing the --concurrent download limit
+        #[arg(long, value_name = "N", default_value = "1")]
+        parallel_feeds: usize,
+
+        #[command(flatten)]
+        options: Options,
+    },
+
+    /// Write an OPML file listing every podcast of a library directory
+    Export {
+        /// Library directory containing one subdirectory per podcast
+        library_dir: PathBuf,
+
+        /// File to write the OPML to (defaults to standard output)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a CSV listing every downloaded episode of a podcast or library
+    ///
+    /// Columns: podcast, title, date, duration, size, path, hash.
+    Csv {
+        /// Output directory of a synced podcast, or a library directory
+        dir: PathBuf,
+
+        /// File to write the CSV to (defaults to standard output)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Rename podcast directories of a library to match their current titles
+    ///
+    /// Syncs keep using a podcast's directory when the podcast changes its
+    /// title; this renames such directories and updates the library index.
+    RenameLibrary {
+        /// Library directory containing one subdirectory per podcast
+        library_dir: PathBuf,
+
+        /// Only list the directories that would be renamed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Name directories by the template the library was synced with, e.g. "{author} - {title}"
+        #[arg(long, value_name = "TEMPLATE", value_parser = parse_dir_template)]
+        dir_template: Option<DirTemplate>,
+    },
+
+    /// Store skip segments (sponsor reads, intros, ...) in episode metadata
+    ///
+    /// The segment file is JSON mapping episode GUIDs or audio filenames to
+    /// lists of {"start", "end", "category"} entries, times in seconds.
+    Segments {
+        /// Output directory of a synced podcast
+        output_dir: PathBuf,
+
+        /// JSON file with the segments to import
+        file: PathBuf,
+    },
+
+    /// Write the state of an output directory as a portable JSON document
+    ///
+    /// Lists the metadata of every downloaded episode, the podcast metadata
+    /// and the failure history, for import-state on another machine or in
+    /// another directory.
+    ExportState {
+        /// Output directory of a synced podcast
+        output_dir: PathBuf,
+
+        /// File to write the JSON to (defaults to standard output)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Recreate the state written by export-state in an output directory
+    ///
+    /// Writes the metadata files of the exported episodes, so that syncs
+    /// into the directory do not download them again. Files already in the
+    /// directory are kept.
+    ImportState {
+        /// Output directory to import into, created if missing
+        output_dir: PathBuf,
+
+        /// JSON file written by export-state
+        file: PathBuf,
+    },
+
+    /// Re-hash downloaded episodes and compare them to their recorded hashes
+    ///
+    /// Lists damaged and missing audio files and unreadable metadata, and
+    /// exits with status 1 if there are any.
+    Verify {
+        /// Output directory of a synced podcast
+        output_dir: PathBuf,
+    },
+
+    /// Rebuild missing or damaged episode metadata from the feed
+    ///
+    /// Sets unreadable metadata aside, removes metadata of deleted audio
+    /// files, hashes episodes lacking a hash and matches audio files without
+    /// metadata to the feed's episodes.
+    Repair {
+        /// Output directory of a synced podcast
+        output_dir: PathBuf,
+
+        /// Feed URL or file (defaults to the feed recorded in podcast.json)
+        #[arg(long, value_name = "URL|FILE")]
+        feed: Option<String>,
+
+        #[command(flatten)]
+        access: FeedAccess,
+
+        #[command(flatten)]
+        options: Options,
+    },
+
+    /// Check that an output directory is writable and was synced recently
+    ///
+    /// Exits with status 1 if not, for use as a container health check.
+    Healthcheck {
+        /// Output directory to check
+        output_dir: PathBuf,
+
+        /// Maximum age of the last sync, e.g. "26h" or "2d" (a bare number counts as hours)
+        #[arg(long, value_name = "AGE", value_parser = parse_max_age)]
+        max_age: Option<HumanDuration>,
+    },
+}
+
+/// Arguments for synchronizing a single feed (the default command)
+///
+/// The positional arguments are only optional so that subcommands can be
+/// used without them; clap requires them whenever no subcommand is given.
+#[derive(clap::Args, Debug)]
+struct SyncArgs {
     /// RSS feed URL or path to local RSS file
-    feed: String,
+    #[arg(required = true)]
+    feed: Option<String>,
 
     /// Output directory for downloaded episodes
-    output_dir: PathBuf,
+    #[arg(required = true)]
+    output_dir: Option<PathBuf>,
 
+    #[command(flatten)]
+    access: FeedAccess,
+
+    #[command(flatten)]
+    options: Options,
+}
+
+/// Credentials and headers for fetching a single feed
+#[derive(clap::Args, Debug)]
+struct FeedAccess {
+    /// Credentials for a password-protected feed (also accepted in the feed URL)
+    #[arg(long, value_name = "USER[:PASSWORD]")]
+    user: Option<String>,
+
+    /// Extra request header for the feed's host, e.g. "Authorization: Bearer TOKEN" (repeatable)
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+}
+
+impl FeedAccess {
+    /// Add the credentials and headers to `options`
+    fn apply(&self, options: &mut SyncOptions) {
+        if let Some(user) = &self.user {
+            options.credentials = Some(match user.split_once(':') {
+                Some((username, password)) => {
+                    Credentials::new(username, Some(password.to_string()))
+                }
+                None => Credentials::new(user, None),
+            });
+        }
+        options.headers.extend(self.headers.iter().cloned());
+    }
+}
+
+/// Options shared by all commands that sync feeds
+#[derive(clap::Args, Debug)]
+struct Options {
     /// Maximum number of concurrent downloads
     #[arg(short = 'c', long, default_value = "3")]
     concurrent: usize,
@@ -54,319 +273,1027 @@ struct Args {
     /// Quiet mode - suppress progress output
     #[arg(short, long)]
     quiet: bool,
+
+    /// How often to retry downloads that fail with network or server errors
+    #[arg(long, value_name = "N", default_value = "3")]
+    retries: u32,
+
+    /// Queue episodes that still fail after --retries again behind the others, up to N times
+    #[arg(long, value_name = "N", default_value = "0")]
+    requeue_failed: u32,
+
+    /// Wait before trying again an episode that failed to download, doubling with every further failure (e.g. "1h", "1d"; 0 tries on every run)
+    #[arg(long, value_name = "DURATION", default_value = "0")]
+    failure_cooldown: HumanDuration,
+
+    /// Try again episodes that failed recently, ignoring --failure-cooldown
+    #[arg(long)]
+    retry_failed: bool,
+
+    /// Longest wait honored when a throttling server sends Retry-After (e.g. "5m")
+    #[arg(long, value_name = "DURATION", default_value = "5m")]
+    max_retry_after: HumanDuration,
+
+    /// Give up on connections that stall this long, connecting or reading (e.g. "30s", "2m"; a bare number counts as seconds)
+    #[arg(long, value_name = "DURATION")]
+    timeout: Option<HumanDuration>,
+
+    /// Abort and retry episode downloads that receive no data this long (e.g. "20s"; defaults to --timeout)
+    #[arg(long, value_name = "DURATION")]
+    stall_timeout: Option<HumanDuration>,
+
+    /// Maximum combined download speed in bytes per second, with K, M or G suffix (e.g. "2M")
+    #[arg(long, value_name = "BYTES")]
+    max_speed: Option<ByteSize>,
+
+    /// Stop starting downloads when less disk space is free, with K, M or G suffix (e.g. "2G")
+    #[arg(long, value_name = "BYTES")]
+    min_free_space: Option<ByteSize>,
+
+    /// Skip episodes larger than this, with K, M or G suffix (e.g. "500M")
+    #[arg(long, value_name = "BYTES")]
+    max_episode_size: Option<ByteSize>,
+
+    /// Count episodes over --max-episode-size as failed instead of skipping them
+    #[arg(long, requires = "max_episode_size")]
+    fail_oversized: bool,
+
+    /// After syncing, delete the audio of all but the newest N episodes
+    #[arg(long, value_name = "N")]
+    keep_last: Option<usize>,
+
+    /// After syncing, delete the audio of episodes older than this, e.g. "30d" (a bare number counts as days)
+    #[arg(long, value_name = "AGE", value_parser = parse_retention_age)]
+    keep_max_age: Option<HumanDuration>,
+
+    /// After syncing, delete the audio of the oldest episodes beyond this total size (e.g. "20G")
+    #[arg(long, value_name = "BYTES")]
+    keep_max_size: Option<ByteSize>,
+
+    /// Skip forcing finished downloads to disk; faster, but a crash may leave empty episode files
+    #[arg(long)]
+    no_fsync: bool,
+
+    /// Date audio files by episode publication (or the server's Last-Modified) instead of download time
+    #[arg(long)]
+    publication_mtime: bool,
+
+    /// Write ID3v2 tags (title, podcast, author, episode number, date, show notes) into MP3 episodes
+    #[arg(long)]
+    tag: bool,
+
+    /// Write chapter marks from the feed or show notes into MP3 episodes as ID3v2 chapters
+    #[arg(long)]
+    embed_chapters: bool,
+
+    /// Store each download's content hash and GUID in extended attributes (user.podpull.*)
+    #[arg(long)]
+    xattr: bool,
+
+    /// Keep the partial file of a failed download in .quarantine/, with a note on the failure
+    #[arg(long)]
+    quarantine: bool,
+
+    /// Maximum number of requests per second to the same host (e.g. 0.5)
+    #[arg(long, value_name = "PER_SECOND", value_parser = parse_rate)]
+    rate_limit: Option<f64>,
+
+    /// HTTP versions to use; "auto" negotiates HTTP/2 with servers offering it
+    #[arg(long, value_enum, default_value = "auto")]
+    http_version: HttpVersionArg,
+
+    /// IP versions to connect over; "ipv4" works around hosts with broken IPv6 records
+    #[arg(long, value_enum, default_value = "auto")]
+    address_family: AddressFamilyArg,
+
+    /// Maximum number of idle connections kept open per host
+    #[arg(long, value_name = "N")]
+    max_idle_connections: Option<usize>,
+
+    /// Send TCP keepalive probes on open connections at this interval (e.g. "30s")
+    #[arg(long, value_name = "DURATION")]
+    keepalive: Option<HumanDuration>,
+
+    /// Maximum number of redirects to follow per request (0 disables redirects)
+    #[arg(long, value_name = "N")]
+    max_redirects: Option<usize>,
+
+    /// PEM file with additional trusted root certificates, e.g. an internal CA (repeatable)
+    #[arg(long = "ca-cert", value_name = "FILE")]
+    ca_certs: Vec<PathBuf>,
+
+    /// PEM file with a client certificate and its private key for TLS client authentication
+    #[arg(long, value_name = "FILE")]
+    client_cert: Option<PathBuf>,
+
+    /// Accept invalid TLS certificates (expired, self-signed, wrong host); insecure
+    #[arg(long)]
+    insecure: bool,
+
+    /// User-Agent header to send (defaults to "podpull/<version>")
+    #[arg(long, value_name = "AGENT")]
+    user_agent: Option<String>,
+
+    /// Speak milestone announcements through a text-to-speech command (e.g. "espeak")
+    #[arg(long, value_name = "COMMAND")]
+    speak: Option<String>,
+
+    /// Preferred formats when a feed offers alternate encodings (e.g. "opus,aac")
+    #[arg(long, value_name = "FORMATS", value_delimiter = ',')]
+    prefer_format: Vec<String>,
+
+    /// Only download episodes in these languages, e.g. "de,en" (episodes without a language are kept)
+    #[arg(long = "language", value_name = "LANGS", value_delimiter = ',')]
+    languages: Vec<String>,
+
+    /// Only download episodes published on or after this date (e.g. "2023-01-01") or within this long ago (e.g. "30d")
+    #[arg(long, value_name = "DATE|AGE", value_parser = parse_since)]
+    since: Option<DateTime<Utc>>,
+
+    /// Only download episodes published on or before this date (e.g. "2023-12-31") or more than this long ago
+    #[arg(long, value_name = "DATE|AGE", value_parser = parse_until)]
+    until: Option<DateTime<Utc>>,
+
+    /// Skip episodes shorter than this by their feed duration, e.g. "2m" to skip teasers
+    #[arg(long, value_name = "DURATION")]
+    min_duration: Option<HumanDuration>,
+
+    /// Skip episodes longer than this by their feed duration, e.g. "2h"
+    #[arg(long, value_name = "DURATION")]
+    max_duration: Option<HumanDuration>,
+
+    /// Only download episodes of these seasons, e.g. "3,4"
+    #[arg(long = "season", value_name = "SEASONS", value_delimiter = ',')]
+    seasons: Vec<u32>,
+
+    /// Only download episodes numbered within this range, e.g. "1-10", "20-" or "-5"
+    #[arg(long = "episodes", value_name = "RANGE", value_parser = parse_episode_range)]
+    episode_numbers: Option<RangeInclusive<u32>>,
+
+    /// Ask the server for size and type of enclosures the feed does not declare, before downloading
+    #[arg(long)]
+    probe: bool,
+
+    /// Minimum bitrate in kbit/s when choosing between alternate encodings
+    #[arg(long, value_name = "KBPS", value_parser = parse_bitrate)]
+    min_bitrate: Option<u64>,
+
+    /// Maximum bitrate in kbit/s when choosing between alternate encodings
+    #[arg(long, value_name = "KBPS", value_parser = parse_bitrate)]
+    max_bitrate: Option<u64>,
+
+    /// Which bitrate to pick among matching alternate encodings
+    #[arg(long, value_enum, default_value = "highest")]
+    bitrate: BitrateArg,
+
+    /// Hash algorithm for verifying downloads; blake3 is fastest on large archives
+    #[arg(long, value_enum, default_value = "sha256")]
+    hash: HashArg,
+
+    /// What to do with episodes published as video
+    #[arg(long, value_enum, default_value = "download")]
+    video: VideoArg,
+
+    /// Order in which new episodes are downloaded, and kept under --limit
+    #[arg(long, value_enum, default_value = "newest")]
+    order: OrderArg,
+
+    /// What to do with downloaded episodes whose audio the feed has replaced
+    #[arg(long, value_enum, default_value = "keep")]
+    updated: UpdatedArg,
+
+    /// What to do with downloaded episodes that are no longer in the feed
+    #[arg(long, value_enum, default_value = "keep")]
+    prune: PruneArg,
+
+    /// How episodes are arranged in the output directory
+    #[arg(long, value_enum, default_value = "flat")]
+    layout: LayoutArg,
+
+    /// Name new episode files by a template, e.g. "S{season:02}E{episode:02} - {title}", "numbered" or "original"
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_filename_template)]
+    filename_template: Option<FilenameTemplate>,
+
+    /// Name new episode files in lowercase ASCII words joined by dashes, for old players and car stereos
+    #[arg(long)]
+    ascii_filenames: bool,
+
+    /// Shorten episode titles in filenames to this many characters [default: 100]
+    #[arg(long, value_name = "CHARS")]
+    max_title_length: Option<usize>,
+
+    /// Shorten episode filenames (without extension) to this many bytes, e.g. 130 for eCryptfs
+    #[arg(long, value_name = "BYTES")]
+    max_filename_bytes: Option<usize>,
+
+    /// Shorten episode filenames so paths stay within this many characters (e.g. 260 for Windows tools)
+    #[arg(long, value_name = "CHARS")]
+    max_path_length: Option<usize>,
+
+    /// Create chapter files from timestamps in show notes when the feed has none
+    #[arg(long)]
+    generate_chapters: bool,
+
+    /// Write each episode's show notes to a Markdown or plain-text file next to the audio file
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    show_notes: Option<ShowNotesArg>,
+
+    /// Write metadata without sync timestamps, byte-identical between mirrors
+    #[arg(long)]
+    reproducible_metadata: bool,
+
+    /// Download transcripts (SRT, VTT, JSON, ...) linked by the feed next to the audio
+    #[arg(long)]
+    download_transcripts: bool,
+
+    /// Repair common XML errors (unescaped "&", HTML entities, control characters) in broken feeds
+    #[arg(long)]
+    lenient: bool,
+
+    /// Write SHA256SUMS and per-file .sha256 checksums for use with `sha256sum -c`
+    #[arg(long)]
+    checksums: bool,
+
+    /// Write episodes.json, an index of all episode metadata, after each sync
+    #[arg(long)]
+    index: bool,
+
+    /// Write metadata for audio files already in the output directory that match a feed episode, instead of downloading it again
+    #[arg(long)]
+    adopt: bool,
+
+    /// Keep the state of the output directory in a SQLite database (.podpull.sqlite) to speed up scans
+    #[cfg(feature = "sqlite")]
+    #[arg(long)]
+    database: bool,
+
+    /// Write a .receipt.json per download recording URLs, response headers, timestamps and TLS certificate
+    #[arg(long)]
+    receipts: bool,
+
+    /// Name new podcast directories of import and batch syncs by a template, e.g. "{author} - {title}"
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_dir_template)]
+    dir_template: Option<DirTemplate>,
+
+    /// Download episodes dated in the future instead of waiting for their release
+    #[arg(long)]
+    download_future_episodes: bool,
+
+    /// Only compare the output directory with the feed, never write to it
+    #[arg(long)]
+    read_only: bool,
+
+    /// Show what a sync would download, skip and prune, with the estimated size, without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Disable emoji icons in output
+    #[arg(long)]
+    no_emoji: bool,
+
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Draw progress bars and spinners with ASCII characters only
+    #[arg(long)]
+    ascii_progress: bool,
+
+    /// Language for output messages (e.g. "de"), defaults to the system locale
+    #[arg(long, value_name = "LANG", value_parser = parse_lang)]
+    lang: Option<Language>,
 }
 
-/// Progress reporter using indicatif for terminal output
-struct IndicatifReporter {
-    multi: MultiProgress,
-    bars: Mutex<HashMap<usize, ProgressBar>>,
-    main_bar: ProgressBar,
+/// Parse a `--lang` value into a language with a message catalog
+fn parse_lang(lang: &str) -> Result<Language, String> {
+    Language::from_locale(lang).ok_or_else(|| format!("unsupported language: {}", lang))
 }
 
-impl IndicatifReporter {
-    fn new() -> Self {
-        let multi = MultiProgress::new();
+/// Parse a `--dir-template` value
+fn parse_dir_template(template: &str) -> Result<DirTemplate, String> {
+    DirTemplate::parse(template).map_err(|e| e.to_string())
+}
 
-        let main_style = ProgressStyle::default_bar()
-            .template("{spinner:.green} {wide_msg}")
-            .unwrap();
+fn parse_filename_template(template: &str) -> Result<FilenameTemplate, String> {
+    FilenameTemplate::parse(template).map_err(|e| e.to_string())
+}
 
-        let main_bar = multi.add(ProgressBar::new_spinner());
-        main_bar.set_style(main_style);
-        main_bar.enable_steady_tick(std::time::Duration::from_millis(100));
+/// Parse a `--max-age` value, where a bare number counts as hours
+fn parse_max_age(age: &str) -> Result<HumanDuration, String> {
+    match age.trim().parse::<u64>() {
+        Ok(hours) => hours
+            .checked_mul(3600)
+            .map(HumanDuration::from)
+            .ok_or_else(|| format!("age out of range: {}", age)),
+        Err(_) => age.parse().map_err(|e: podpull::UnitError| e.to_string()),
+    }
+}
 
-        Self {
-            multi,
-            bars: Mutex::new(HashMap::new()),
-            main_bar,
-        }
+/// Parse a `--keep-max-age` value, where a bare number counts as days
+fn parse_retention_age(age: &str) -> Result<HumanDuration, String> {
+    match age.trim().parse::<u64>() {
+        Ok(days) => days
+            .checked_mul(86_400)
+            .map(HumanDuration::from)
+            .ok_or_else(|| format!("age out of range: {}", age)),
+        Err(_) => age.parse().map_err(|e: podpull::UnitError| e.to_string()),
     }
+}
 
-    fn get_or_create_bar(&self, download_id: usize) -> ProgressBar {
-        let mut bars = self.bars.lock().unwrap();
+/// Parse a `--since` value into the earliest publication date to download
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date_bound(value, false)
+}
+
+/// Parse an `--until` value into the first publication date not to download
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_date_bound(value, true)
+}
+
+/// Parse an RFC 3339 timestamp, a date (the start of that day in UTC, or
+/// of the next day with `inclusive_day`) or an age counted back from now
+fn parse_date_bound(value: &str, inclusive_day: bool) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let date = if inclusive_day {
+            date.succ_opt().ok_or("date out of range")?
+        } else {
+            date
+        };
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    let age: HumanDuration = value.parse().map_err(|_| {
+        format!(
+            "expected a date like 2023-01-31 or an age like 30d: {}",
+            value
+        )
+    })?;
+    chrono::Duration::from_std(age.into())
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| format!("age out of range: {}", value))
+}
 
-        if let Some(bar) = bars.get(&download_id) {
-            return bar.clone();
+/// Parse an `--episodes` range such as "1-10", with either end optional
+fn parse_episode_range(range: &str) -> Result<RangeInclusive<u32>, String> {
+    let number = |value: &str, open: u32| match value.trim() {
+        "" => Ok(open),
+        value => value
+            .parse::<u32>()
+            .map_err(|_| format!("invalid episode number: {}", value)),
+    };
+    match range.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (number(first, 0)?, number(last, u32::MAX)?);
+            if first > last {
+                return Err(format!("range ends before it starts: {}", range));
+            }
+            Ok(first..=last)
+        }
+        None => {
+            let number = number(range, 0)?;
+            Ok(number..=number)
         }
+    }
+}
 
-        let style = ProgressStyle::default_bar()
-            .template(&format!(
-                "  {SAVING}[{{bar:30.cyan/blue}}] {{bytes}}/{{total_bytes}} {{wide_msg}}"
-            ))
-            .unwrap()
-            .progress_chars("█▓░");
+/// Parse a `--min-bitrate` or `--max-bitrate` value in kbit/s into bit/s
+fn parse_bitrate(kbps: &str) -> Result<u64, String> {
+    kbps.trim()
+        .parse::<u64>()
+        .map_err(|e| e.to_string())?
+        .checked_mul(1000)
+        .ok_or_else(|| format!("bitrate out of range: {}", kbps))
+}
 
-        let bar = self.multi.add(ProgressBar::new(0));
-        bar.set_style(style);
-        bars.insert(download_id, bar.clone());
-        bar
+/// Parse a `--rate-limit` value (at least one request per 1000 seconds)
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate >= 0.001 => Ok(rate),
+        _ => Err("expected a number of requests per second of at least 0.001".to_string()),
     }
+}
+
+/// Parse a `--header` value of the form "Name: value"
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| "expected \"NAME: VALUE\"".to_string())?;
+    let (name, value) = (name.trim(), value.trim());
+
+    HeaderName::from_bytes(name.as_bytes()).map_err(|e| e.to_string())?;
+    HeaderValue::from_str(value).map_err(|e| e.to_string())?;
+
+    Ok((name.to_string(), value.to_string()))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum BitrateArg {
+    Highest,
+    Lowest,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AddressFamilyArg {
+    Auto,
+    Ipv4,
+    Ipv6,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum HttpVersionArg {
+    Auto,
+    Http1,
+    Http2,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum HashArg {
+    Sha256,
+    Sha512,
+    Blake3,
+}
 
-    fn finish_bar(&self, download_id: usize) {
-        let mut bars = self.bars.lock().unwrap();
-        if let Some(bar) = bars.remove(&download_id) {
-            bar.finish_and_clear();
+impl From<HashArg> for HashAlgorithm {
+    fn from(hash: HashArg) -> Self {
+        match hash {
+            HashArg::Sha256 => HashAlgorithm::Sha256,
+            HashArg::Sha512 => HashAlgorithm::Sha512,
+            HashArg::Blake3 => HashAlgorithm::Blake3,
         }
     }
 }
 
-impl ProgressReporter for IndicatifReporter {
-    fn report(&self, event: ProgressEvent) {
-        match event {
-            ProgressEvent::FetchingFeed { url } => {
-                self.main_bar
-                    .set_message(format!("{GLOBE}Fetching feed: {}", url.cyan()));
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ShowNotesArg {
+    /// Markdown (.md), keeping headings, emphasis and links
+    Markdown,
+    /// Plain text (.txt)
+    Text,
+}
 
-            ProgressEvent::ParsingFeed { source } => {
-                self.main_bar
-                    .set_message(format!("{COG}Parsing feed: {}", source.cyan()));
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum VideoArg {
+    /// Download videos with their video file extension
+    Download,
+    /// Leave video episodes out
+    Skip,
+    /// Count video episodes as failed
+    Fail,
+}
 
-            ProgressEvent::ScanningDirectory {
-                files_scanned,
-                total_files,
-            } => {
-                if total_files == 0 {
-                    self.main_bar
-                        .set_message(format!("{SEARCH}Scanning existing episodes..."));
-                } else {
-                    // Switch to progress bar style for scanning
-                    if files_scanned == 0 {
-                        let scan_style = ProgressStyle::default_bar()
-                            .template(&format!(
-                                "{{spinner:.green}} {SEARCH}Scanning existing episodes... [{{bar:30.cyan/blue}}] {{pos}}/{{len}}"
-                            ))
-                            .unwrap()
-                            .progress_chars("█▓░");
-                        self.main_bar.set_style(scan_style);
-                        self.main_bar.set_length(total_files as u64);
-                    }
-                    self.main_bar.set_position(files_scanned as u64);
-                }
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum UpdatedArg {
+    /// Keep the file downloaded first
+    Keep,
+    /// Download the episode again, archiving the old file
+    Redownload,
+}
 
-            ProgressEvent::SyncPlanReady {
-                podcast_title,
-                total_episodes,
-                new_episodes,
-                to_download,
-            } => {
-                // Reset to spinner style after scanning
-                let main_style = ProgressStyle::default_bar()
-                    .template("{spinner:.green} {wide_msg}")
-                    .unwrap();
-                self.main_bar.set_style(main_style);
-                if new_episodes == to_download {
-                    // No limit applied or limit >= new
-                    self.main_bar.set_message(format!(
-                        "{HEADPHONES}{} • {} total, {} new",
-                        podcast_title.bold().green(),
-                        total_episodes.to_string().cyan(),
-                        new_episodes.to_string().yellow()
-                    ));
-                } else {
-                    // Limit applied
-                    self.main_bar.set_message(format!(
-                        "{HEADPHONES}{} • {} total, {} new, downloading {}",
-                        podcast_title.bold().green(),
-                        total_episodes.to_string().cyan(),
-                        new_episodes.to_string().yellow(),
-                        to_download.to_string().green()
-                    ));
-                }
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OrderArg {
+    /// Latest publication date first
+    Newest,
+    /// Earliest publication date first, to listen through an archive
+    Oldest,
+    /// The order of the feed's items
+    Feed,
+    /// Smallest files first
+    Smallest,
+}
 
-            ProgressEvent::DownloadStarting {
-                download_id,
-                episode_title,
-                episode_index,
-                total_to_download,
-                content_length,
-            } => {
-                let bar = self.get_or_create_bar(download_id);
-                bar.set_length(content_length.unwrap_or(0));
-                bar.set_position(0);
-                // Calculate width needed for "[idx/total]" part
-                let index_width =
-                    (episode_index + 1).to_string().len() + total_to_download.to_string().len();
-                let title_width = available_title_width(index_width);
-                bar.set_message(format!(
-                    "[{}/{}] {}",
-                    (episode_index + 1).to_string().cyan(),
-                    total_to_download.to_string().cyan(),
-                    truncate_title(&episode_title, title_width)
-                ));
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PruneArg {
+    /// Keep every episode ever downloaded
+    Keep,
+    /// Delete them, mirroring the feed
+    Delete,
+    /// Move them into the .archive directory
+    Archive,
+}
 
-            ProgressEvent::DownloadProgress {
-                download_id,
-                bytes_downloaded,
-                total_bytes,
-                ..
-            } => {
-                let bar = self.get_or_create_bar(download_id);
-                if let Some(total) = total_bytes {
-                    bar.set_length(total);
-                }
-                bar.set_position(bytes_downloaded);
-            }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LayoutArg {
+    /// All episodes directly in the output directory
+    Flat,
+    /// "Season 01/", "Season 02/", ... subdirectories by season number
+    Seasons,
+    /// "2023/", "2024/", ... subdirectories by publication year
+    Years,
+    /// "2024/01/", "2024/02/", ... subdirectories by publication month
+    Months,
+}
 
-            ProgressEvent::DownloadCompleted {
-                download_id,
-                episode_title,
-                bytes_downloaded,
-            } => {
-                let bar = self.get_or_create_bar(download_id);
-                bar.set_position(bytes_downloaded);
-                // No index displayed, so use 0 for index_width calculation
-                let title_width = available_title_width(0);
-                bar.set_message(format!(
-                    "{SUCCESS}{}",
-                    truncate_title(&episode_title, title_width).green()
-                ));
-                self.finish_bar(download_id);
-            }
+impl Options {
+    /// Build the filename template, with any length limits applied
+    fn filename_template(&self) -> FilenameTemplate {
+        let mut template = self.filename_template.clone().unwrap_or_default();
+        if let Some(max) = self.max_title_length {
+            template = template.max_title_length(max);
+        }
+        if let Some(max) = self.max_filename_bytes {
+            template = template.max_stem_bytes(max);
+        }
+        template
+    }
 
-            ProgressEvent::DownloadFailed {
-                download_id,
-                episode_title,
-                error,
-            } => {
-                let bar = self.get_or_create_bar(download_id);
-                // Reserve space for " - " and some error text (at least 30 chars)
-                let title_width = available_title_width(0).saturating_sub(3 + 30);
-                bar.abandon_with_message(format!(
-                    "{FAILURE}{} - {}",
-                    truncate_title(&episode_title, title_width.max(20)).red(),
-                    error.red()
-                ));
-                self.finish_bar(download_id);
-            }
+    /// Build the enclosure preference, if any alternate-encoding option was given
+    fn enclosure_preference(&self) -> Option<EnclosurePreference> {
+        if self.prefer_format.is_empty() && self.min_bitrate.is_none() && self.max_bitrate.is_none()
+        {
+            return None;
+        }
 
-            ProgressEvent::Finalizing { .. } => {
-                // Silent - the rename is fast
-            }
+        Some(EnclosurePreference {
+            formats: self.prefer_format.clone(),
+            min_bitrate: self.min_bitrate,
+            max_bitrate: self.max_bitrate,
+            bitrate: match self.bitrate {
+                BitrateArg::Highest => BitratePreference::Highest,
+                BitrateArg::Lowest => BitratePreference::Lowest,
+            },
+        })
+    }
 
-            ProgressEvent::HashingCompleted { .. } => {
-                // Silent - hashing happens during download
-            }
+    fn sync_options(&self) -> SyncOptions {
+        SyncOptions {
+            limit: self.limit,
+            order: match self.order {
+                OrderArg::Newest => DownloadOrder::NewestFirst,
+                OrderArg::Oldest => DownloadOrder::OldestFirst,
+                OrderArg::Feed => DownloadOrder::FeedOrder,
+                OrderArg::Smallest => DownloadOrder::SmallestFirst,
+            },
+            max_concurrent: self.concurrent,
+            continue_on_error: true,
+            enclosure_preference: self.enclosure_preference(),
+            layout: match self.layout {
+                LayoutArg::Flat => OutputLayout::Flat,
+                LayoutArg::Seasons => OutputLayout::Seasons,
+                LayoutArg::Years => OutputLayout::Years,
+                LayoutArg::Months => OutputLayout::Months,
+            },
+            filename_template: self.filename_template(),
+            ascii_filenames: self.ascii_filenames,
+            max_path_length: self.max_path_length,
+            generate_chapters: self.generate_chapters,
+            show_notes: self.show_notes.map(|format| match format {
+                ShowNotesArg::Markdown => ShowNotesFormat::Markdown,
+                ShowNotesArg::Text => ShowNotesFormat::Text,
+            }),
+            reproducible_metadata: self.reproducible_metadata,
+            download_transcripts: self.download_transcripts,
+            lenient_parsing: self.lenient,
+            write_checksums: self.checksums,
+            write_index: self.index,
+            adopt_existing: self.adopt,
+            #[cfg(feature = "sqlite")]
+            database: self.database,
+            write_receipts: self.receipts,
+            retry: RetryPolicy {
+                max_retries: self.retries,
+                max_retry_after: self.max_retry_after.into(),
+                ..RetryPolicy::default()
+            },
+            requeue_failed: self.requeue_failed,
+            failure_cooldown: Some(self.failure_cooldown.duration()).filter(|d| !d.is_zero()),
+            retry_failed: self.retry_failed,
+            download_future_episodes: self.download_future_episodes,
+            credentials: None,
+            headers: Vec::new(),
+            read_only: self.read_only,
+            dry_run: self.dry_run,
+            operation_timeout: self.timeout.map(HumanDuration::duration),
+            stall_timeout: self.stall_timeout.map(HumanDuration::duration),
+            max_download_speed: self.max_speed,
+            bandwidth_limiter: None,
+            download_budget: None,
+            directory_template: self.dir_template.clone(),
+            min_free_space: self.min_free_space,
+            languages: self.languages.clone(),
+            published_after: self.since,
+            published_before: self.until,
+            min_duration: self.min_duration.map(Into::into),
+            max_duration: self.max_duration.map(Into::into),
+            seasons: (!self.seasons.is_empty()).then(|| self.seasons.clone()),
+            episode_numbers: self.episode_numbers.clone(),
+            probe_enclosures: self.probe,
+            max_episode_bytes: self.max_episode_size,
+            fail_oversized: self.fail_oversized,
+            fsync: !self.no_fsync,
+            publication_mtime: self.publication_mtime,
+            write_tags: self.tag,
+            embed_chapters: self.embed_chapters,
+            write_xattrs: self.xattr,
+            quarantine_failed: self.quarantine,
+            hash_algorithm: self.hash.into(),
+            video_policy: match self.video {
+                VideoArg::Download => VideoPolicy::Download,
+                VideoArg::Skip => VideoPolicy::Skip,
+                VideoArg::Fail => VideoPolicy::Fail,
+            },
+            update_policy: match self.updated {
+                UpdatedArg::Keep => UpdatePolicy::Keep,
+                UpdatedArg::Redownload => UpdatePolicy::Redownload,
+            },
+            retention: RetentionPolicy {
+                keep_last: self.keep_last,
+                max_age: self.keep_max_age.map(Into::into),
+                max_total_size: self.keep_max_size,
+            },
+            prune: match self.prune {
+                PruneArg::Keep => PrunePolicy::Keep,
+                PruneArg::Delete => PrunePolicy::Delete,
+                PruneArg::Archive => PrunePolicy::Archive,
+            },
+        }
+    }
 
-            ProgressEvent::PartialFilesCleanedUp { count } => {
-                if count > 0 {
-                    self.main_bar.set_message(format!(
-                        "{BROOM}Cleaned up {} interrupted download{}",
-                        count.to_string().yellow(),
-                        if count == 1 { "" } else { "s" }
-                    ));
-                }
-            }
+    /// Resolve and apply the output style, then print the banner
+    fn init_output(&self) -> OutputStyle {
+        let style = OutputStyle::resolve(self.no_emoji, self.no_color, self.ascii_progress);
+        style.apply();
 
-            ProgressEvent::SyncCompleted {
-                downloaded_count,
-                existing_count,
-                limited_count,
-                failed_count,
-            } => {
-                self.main_bar.finish_and_clear();
-
-                let mut parts = vec![
-                    format!("{} downloaded", downloaded_count.to_string().green().bold()),
-                    format!("{} existing", existing_count.to_string().yellow()),
-                ];
-
-                if limited_count > 0 {
-                    parts.push(format!("{} limited", limited_count.to_string().cyan()));
-                }
+        println!(
+            "\n{}{} {}\n",
+            style.icons.microphone,
+            "podpull".bold().magenta(),
+            tr!("- Podcast Downloader").dimmed()
+        );
 
-                parts.push(if failed_count > 0 {
-                    format!("{} failed", failed_count.to_string().red().bold())
-                } else {
-                    format!("{} failed", failed_count.to_string().green())
-                });
+        style
+    }
 
-                println!(
-                    "\n{PARTY}{} {}",
-                    "Sync complete:".bold().green(),
-                    parts.join(", ")
-                );
-            }
+    fn http_client(&self) -> Result<ReqwestClient> {
+        let mut builder = ReqwestClient::builder();
+        if let Some(timeout) = self.timeout {
+            let timeout = timeout.duration();
+            builder = builder.connect_timeout(timeout).read_timeout(timeout);
+        }
+        if let Some(rate) = self.rate_limit {
+            builder = builder.rate_limiter(RateLimiter::per_second(rate));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(max) = self.max_idle_connections {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.keepalive {
+            builder = builder.tcp_keepalive(interval.duration());
+        }
+        for path in &self.ca_certs {
+            let pem = std::fs::read(path)
+                .with_context(|| tr!("Failed to read {path}", path = path.display()))?;
+            builder = builder.root_certificates_pem(pem);
+        }
+        if let Some(path) = &self.client_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| tr!("Failed to read {path}", path = path.display()))?;
+            builder = builder.client_identity_pem(pem);
+        }
+        builder = builder.danger_accept_invalid_certs(self.insecure);
+        if let Some(max) = self.max_redirects {
+            builder = builder.max_redirects(max);
+        }
+        builder = builder.http_version(match self.http_version {
+            HttpVersionArg::Auto => HttpVersion::Auto,
+            HttpVersionArg::Http1 => HttpVersion::Http1,
+            HttpVersionArg::Http2 => HttpVersion::Http2,
+        });
+        builder = builder.address_family(match self.address_family {
+            AddressFamilyArg::Auto => AddressFamily::Auto,
+            AddressFamilyArg::Ipv4 => AddressFamily::Ipv4,
+            AddressFamilyArg::Ipv6 => AddressFamily::Ipv6,
+        });
+        builder.build().context(tr!("Failed to create HTTP client"))
+    }
+
+    fn speech_command(&self) -> Result<Option<SpeechCommand>> {
+        self.speak
+            .as_deref()
+            .map(|command_line| {
+                SpeechCommand::parse(command_line).context("The --speak command must not be empty")
+            })
+            .transpose()
+    }
+
+    /// Build the progress reporter for a single sync
+    fn reporter(
+        &self,
+        style: OutputStyle,
+        speech: Option<&SpeechCommand>,
+    ) -> SharedProgressReporter {
+        let reporter: SharedProgressReporter = if self.quiet {
+            NoopReporter::shared()
+        } else {
+            Arc::new(IndicatifReporter::new(style))
+        };
+
+        match speech {
+            Some(command) => Arc::new(SpeechReporter::new(command.clone(), reporter)),
+            None => reporter,
         }
     }
 }
 
-fn truncate_title(title: &str, max_len: usize) -> String {
-    if title.len() <= max_len {
-        title.to_string()
-    } else {
-        format!("{}...", &title[..max_len.saturating_sub(3)])
+/// Run the health check and exit with its status
+fn healthcheck(output_dir: &Path, max_age: Option<HumanDuration>) -> ! {
+    let max_age = max_age.and_then(|age| chrono::Duration::from_std(age.duration()).ok());
+
+    match check_health(output_dir, max_age) {
+        Ok(status) => {
+            println!(
+                "{}",
+                tr!("Healthy, last sync at {time}", time = status.last_sync)
+            );
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{}", tr!("Unhealthy: {reason}", reason = e));
+            std::process::exit(1);
+        }
     }
 }
 
-/// Calculate available width for episode title in progress bar
-/// Layout: "  📥 [{bar:30}] XX.XX MiB/XX.XX MiB [idx/total] title"
-fn available_title_width(index_width: usize) -> usize {
-    let term_width = console::Term::stdout().size().1 as usize;
+/// Import a segment file into the episode metadata of an output directory
+fn segments(output_dir: &Path, file: &Path) -> Result<()> {
+    let segments = read_segment_file(file).context(tr!("Failed to read segment file"))?;
+    let updated =
+        import_segments(output_dir, &segments).context(tr!("Failed to import segments"))?;
 
-    // Fixed parts:
-    // - "  " prefix: 2
-    // - emoji + space: 4 (📥 + space, accounting for unicode width)
-    // - "[" + "]": 2
-    // - bar: 30
-    // - " ": 1
-    // - bytes display "XX.XX MiB/XX.XX MiB": ~21 (max reasonable)
-    // - " ": 1
-    // - index "[idx/total] ": index_width + 4 brackets/slash + 1 space
-    let fixed_width = 2 + 4 + 2 + 30 + 1 + 21 + 1 + index_width + 4 + 1;
+    println!(
+        "{}",
+        tr!(
+            "Stored skip segments for {count} of {total} episodes",
+            count = updated,
+            total = segments.len()
+        )
+    );
 
-    term_width.saturating_sub(fixed_width).max(20) // minimum 20 chars for title
+    Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+/// Export the state of an output directory as JSON to a file or standard
+/// output
+fn export_state(output_dir: &Path, output: Option<&Path>) -> Result<()> {
+    let export = podpull::export_state(output_dir).context(tr!("Failed to export state"))?;
+    let json = serde_json::to_string_pretty(&export)?;
+
+    match output {
+        Some(path) => std::fs::write(path, json)
+            .with_context(|| tr!("Failed to write {path}", path = path.display()))?,
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Import a state exported by `export-state` into an output directory
+fn import_state(output_dir: &Path, file: &Path) -> Result<()> {
+    let json = std::fs::read_to_string(file)
+        .with_context(|| tr!("Failed to read {path}", path = file.display()))?;
+    let export: StateExport = serde_json::from_str(&json)
+        .with_context(|| tr!("Failed to read {path}", path = file.display()))?;
+    let result =
+        podpull::import_state(&export, output_dir).context(tr!("Failed to import state"))?;
 
     println!(
-        "\n{}{} {}\n",
-        MICROPHONE,
-        "podpull".bold().magenta(),
-        "- Podcast Downloader".dimmed()
+        "{}",
+        tr!(
+            "Imported {imported} episodes, kept {existing} already present",
+            imported = result.imported,
+            existing = result.existing
+        )
     );
 
-    let client = ReqwestClient::new();
+    Ok(())
+}
 
-    let options = SyncOptions {
-        limit: args.limit,
-        max_concurrent: args.concurrent,
-        continue_on_error: true,
-    };
+/// Re-hash the episodes of an output directory, exiting with 1 on damage
+fn verify(output_dir: &Path) -> Result<()> {
+    let report = verify_library(output_dir).context(tr!("Failed to verify episodes"))?;
 
-    let reporter: SharedProgressReporter = if args.quiet {
-        NoopReporter::shared()
+    for path in &report.mismatched {
+        println!("{}", tr!("Damaged: {path}", path = path.display()));
+    }
+    for path in &report.missing {
+        println!("{}", tr!("Missing: {path}", path = path.display()));
+    }
+    for path in &report.unreadable {
+        println!(
+            "{}",
+            tr!("Unreadable metadata: {path}", path = path.display())
+        );
+    }
+    println!(
+        "{}",
+        tr!(
+            "{verified} episodes intact, {damaged} damaged, {missing} missing, {unhashed} without hash",
+            verified = report.verified,
+            damaged = report.mismatched.len(),
+            missing = report.missing.len(),
+            unhashed = report.unhashed.len()
+        )
+    );
+
+    if !report.is_intact() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Rebuild the episode metadata of an output directory from its feed
+async fn repair(
+    output_dir: &Path,
+    feed: Option<&str>,
+    access: &FeedAccess,
+    options: &Options,
+) -> Result<()> {
+    let feed = match feed {
+        Some(feed) => feed.to_string(),
+        None => {
+            read_podcast_metadata(output_dir)
+                .context(tr!("Failed to read podcast metadata"))?
+                .feed_url
+        }
+    };
+    // Fetched like during a sync, including the podcast's .podpull.toml
+    let mut sync_options = options.sync_options();
+    access.apply(&mut sync_options);
+    if let Some(settings) =
+        PodcastSettings::read(output_dir).context(tr!("Failed to read podcast settings"))?
+    {
+        sync_options = settings.apply(&sync_options);
+    }
+    let (feed, client) = authenticate(&options.http_client()?, &feed, &sync_options);
+    let parse = if sync_options.lenient_parsing {
+        parse_feed_lenient
     } else {
-        Arc::new(IndicatifReporter::new())
+        parse_feed
+    };
+
+    // Feeds synced from a file are recorded as file:// URLs
+    let file = url::Url::parse(&feed)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .unwrap_or_else(|| PathBuf::from(&feed));
+    let podcast = async {
+        if is_url(&feed) {
+            let bytes = fetch_feed_bytes(&client, &feed).await?;
+            parse(&bytes, url::Url::parse(&feed)?)
+        } else {
+            parse(&read_feed_file(&file)?, file_path_to_url(&file))
+        }
+    }
+    .await
+    .with_context(|| tr!("Failed to read feed {feed}", feed = feed))?;
+
+    let report = repair_output_dir(&podcast.episodes, output_dir, options.hash.into())
+        .context(tr!("Failed to repair episodes"))?;
+
+    for path in &report.corrupt {
+        println!(
+            "{}",
+            tr!(
+                "Set aside unreadable metadata: {path}",
+                path = path.display()
+            )
+        );
+    }
+    for path in &report.orphaned {
+        println!(
+            "{}",
+            tr!(
+                "Removed metadata of missing audio: {path}",
+                path = path.display()
+            )
+        );
+    }
+    for path in &report.unmatched {
+        println!(
+            "{}",
+            tr!("No episode found for: {path}", path = path.display())
+        );
+    }
+    println!(
+        "{}",
+        tr!(
+            "{rebuilt} metadata files rebuilt, {rehashed} hashed, {orphaned} removed, {corrupt} set aside",
+            rebuilt = report.rebuilt,
+            rehashed = report.rehashed.len(),
+            orphaned = report.orphaned.len(),
+            corrupt = report.corrupt.len()
+        )
+    );
+
+    Ok(())
+}
+
+/// Export the library as OPML to a file or standard output
+fn export(library_dir: &Path, output: Option<&Path>) -> Result<()> {
+    let opml = export_opml(library_dir).context(tr!("Failed to export subscriptions"))?;
+
+    match output {
+        Some(path) => std::fs::write(path, opml)
+            .with_context(|| tr!("Failed to write {path}", path = path.display()))?,
+        None => print!("{}", opml),
+    }
+
+    Ok(())
+}
+
+/// Export the episodes below a directory as CSV to a file or standard output
+fn csv(dir: &Path, output: Option<&Path>) -> Result<()> {
+    let csv = export_csv(dir).context(tr!("Failed to export episodes"))?;
+
+    match output {
+        Some(path) => std::fs::write(path, csv)
+            .with_context(|| tr!("Failed to write {path}", path = path.display()))?,
+        None => print!("{}", csv),
+    }
+
+    Ok(())
+}
+
+/// Rename podcast directories named after outdated titles
+fn rename_library(
+    library_dir: &Path,
+    dry_run: bool,
+    dir_template: Option<&DirTemplate>,
+) -> Result<()> {
+    let renames = plan_library_renames(library_dir, dir_template)
+        .context(tr!("Failed to read library directory"))?;
+
+    if renames.is_empty() {
+        println!("{}", tr!("All podcast directories match their titles"));
+        return Ok(());
+    }
+
+    for rename in &renames {
+        let from = rename.from.display().to_string();
+        let to = rename.to.display().to_string();
+        if dry_run {
+            println!(
+                "{}",
+                tr!("Would rename {from} to {to}", from = from, to = to.yellow())
+            );
+        } else {
+            rename_library_dir(library_dir, rename)?;
+            println!(
+                "{}",
+                tr!("Renamed {from} to {to}", from = from, to = to.yellow())
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Sync a single feed into an output directory
+async fn sync(args: SyncArgs) -> Result<()> {
+    let (Some(feed), Some(output_dir)) = (args.feed, args.output_dir) else {
+        unreachable!("clap requires feed and output directory without a subcommand");
     };
+    let options = &args.options;
+    let style = options.init_output();
+    let icons = style.icons;
+    let speech = options.speech_command()?;
+
+    let client = options.http_client()?;
+    let reporter = options.reporter(style, speech.as_ref());
 
-    let result = sync_podcast(&client, &args.feed, &args.output_dir, &options, reporter)
+    let mut sync_options = options.sync_options();
+    args.access.apply(&mut sync_options);
+
+    let result = sync_podcast(&client, &feed, &output_dir, &sync_options, reporter)
         .await
-        .context("Failed to sync podcast")?;
+        .context(tr!("Failed to sync podcast"))?;
 
-    if !args.quiet && !result.failed_episodes.is_empty() {
-        println!("\n{}", "Failed episodes:".red().bold());
+    if !options.quiet && !result.failed_episodes.is_empty() {
+        println!("\n{}", tr!("Failed episodes:").red().bold());
         for (title, error) in &result.failed_episodes {
             println!(
                 "  {}{} - {}",
-                CROSS,
+                icons.cross,
                 title.yellow(),
                 error.to_string().dimmed()
             );
         }
     }
 
-    if !args.quiet {
+    if !options.quiet {
         println!(
-            "\n{FOLDER}Output: {}\n",
-            args.output_dir.display().to_string().cyan()
+            "\n{}{}\n",
+            icons.folder,
+            tr!(
+                "Output: {path}",
+                path = output_dir.display().to_string().cyan()
+            )
         );
     }
 
@@ -376,3 +1303,265 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Sync every feed of an OPML file into a library directory
+async fn import(opml: &Path, library_dir: &Path, options: &Options) -> Result<()> {
+    let style = options.init_output();
+    let icons = style.icons;
+    let speech = options.speech_command()?;
+
+    let subscriptions = read_opml_file(opml).context(tr!("Failed to read subscriptions"))?;
+    let (subscriptions, duplicates) = deduplicate_subscriptions(&subscriptions);
+
+    if !options.quiet {
+        for entry in &duplicates {
+            println!(
+                "{}{}",
+                icons.cross,
+                tr!(
+                    "Skipping duplicate feed {url} (same as {kept})",
+                    url = entry.duplicate.feed_url.yellow(),
+                    kept = entry.kept.feed_url.cyan()
+                )
+            );
+        }
+    }
+
+    let client = options.http_client()?;
+    let results = sync_subscriptions(
+        &client,
+        &subscriptions,
+        library_dir,
+        &options.sync_options(),
+        |subscription| announce_subscription(subscription, options, style, speech.as_ref()),
+    )
+    .await;
+
+    report_library_results(&results, library_dir, options, style)
+}
+
+/// Exit with a usage error of the `sync` subcommand
+fn sync_usage_error(kind: clap::error::ErrorKind, message: &str) -> ! {
+    let mut command = Cli::command();
+    command.build();
+    command
+        .find_subcommand_mut("sync")
+        .expect("sync subcommand")
+        .error(kind, message)
+        .exit()
+}
+
+/// Sync every feed of a newline-delimited list into a library directory
+async fn sync_list(
+    list: &Path,
+    library_dir: &Path,
+    parallel_feeds: usize,
+    options: &Options,
+) -> Result<()> {
+    let style = options.init_output();
+    let speech = options.speech_command()?;
+
+    let content = if list == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(list)
+    }
+    .context(tr!("Failed to read feed list"))?;
+    let entries: Vec<_> = parse_feed_list(&content)
+        .iter()
+        .map(PodcastEntry::from)
+        .collect();
+
+    let client = options.http_client()?;
+    let sync = sync_podcasts(
+        &client,
+        &entries,
+        library_dir,
+        &options.sync_options(),
+        parallel_feeds,
+        |subscription| announce_subscription(subscription, options, style, speech.as_ref()),
+    )
+    .await;
+
+    report_library_results(&sync.podcasts, library_dir, options, style)
+}
+
+/// Print the podcast about to be synced and create its progress reporter
+fn announce_subscription(
+    subscription: &Subscription,
+    options: &Options,
+    style: OutputStyle,
+    speech: Option<&SpeechCommand>,
+) -> SharedProgressReporter {
+    if !options.quiet {
+        println!(
+            "\n{}{}",
+            style.icons.headphones,
+            subscription
+                .title
+                .as_deref()
+                .unwrap_or(&subscription.feed_url)
+                .bold()
+        );
+    }
+    options.reporter(style, speech)
+}
+
+/// List failed podcasts and summarize a library sync
+///
+/// Exits with status 1 if every podcast failed.
+fn report_library_results(
+    results: &[SubscriptionResult],
+    library_dir: &Path,
+    options: &Options,
+    style: OutputStyle,
+) -> Result<()> {
+    let icons = style.icons;
+    let failures: Vec<_> = results
+        .iter()
+        .filter_map(|entry| entry.result.as_ref().err().map(|e| (entry, e)))
+        .collect();
+
+    if !options.quiet {
+        if !failures.is_empty() {
+            println!("\n{}", tr!("Failed podcasts:").red().bold());
+            for (entry, error) in &failures {
+                println!(
+                    "  {}{} - {}",
+                    icons.cross,
+                    entry.subscription.feed_url.yellow(),
+                    error.to_string().dimmed()
+                );
+            }
+        }
+
+        println!(
+            "\n{}{}",
+            icons.folder,
+            tr!(
+                "Synced {synced} of {total} podcasts into {path}",
+                synced = results.len() - failures.len(),
+                total = results.len(),
+                path = library_dir.display().to_string().cyan()
+            )
+        );
+        let totals = || {
+            results
+                .iter()
+                .filter_map(|entry| entry.result.as_ref().ok())
+        };
+        println!(
+            "{}\n",
+            tr!(
+                "{downloaded} episodes downloaded, {failed} failed",
+                downloaded = totals()
+                    .map(|result| result.downloaded)
+                    .sum::<usize>()
+                    .to_string()
+                    .green(),
+                failed = totals().map(|result| result.failed).sum::<usize>()
+            )
+        );
+    }
+
+    if !failures.is_empty() && failures.len() == results.len() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let lang = match &cli.command {
+        Some(
+            Command::Import { options, .. }
+            | Command::Sync { options, .. }
+            | Command::Repair { options, .. },
+        ) => options.lang,
+        Some(
+            Command::Export { .. }
+            | Command::Csv { .. }
+            | Command::RenameLibrary { .. }
+            | Command::Segments { .. }
+            | Command::ExportState { .. }
+            | Command::ImportState { .. }
+            | Command::Verify { .. }
+            | Command::Healthcheck { .. },
+        ) => None,
+        None => cli.sync.options.lang,
+    };
+    i18n::init(lang.unwrap_or_else(Language::detect));
+
+    match cli.command {
+        Some(Command::Healthcheck {
+            output_dir,
+            max_age,
+        }) => healthcheck(&output_dir, max_age),
+        Some(Command::Import {
+            opml,
+            library_dir,
+            options,
+        }) => import(&opml, &library_dir, &options).await,
+        Some(Command::Sync {
+            stdin,
+            paths,
+            parallel_feeds,
+            options,
+        }) => {
+            let (list, library_dir) = match (stdin, paths.as_slice()) {
+                (true, [library_dir]) => (Path::new("-"), library_dir),
+                (false, [list, library_dir]) => (list.as_path(), library_dir),
+                (true, _) => sync_usage_error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--stdin takes only the library directory",
+                ),
+                (false, _) => sync_usage_error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    "expected a feed list and a library directory (or --stdin)",
+                ),
+            };
+            sync_list(list, library_dir, parallel_feeds, &options).await
+        }
+        Some(Command::Export {
+            library_dir,
+            output,
+        }) => export(&library_dir, output.as_deref()),
+        Some(Command::Csv { dir, output }) => csv(&dir, output.as_deref()),
+        Some(Command::RenameLibrary {
+            library_dir,
+            dry_run,
+            dir_template,
+        }) => rename_library(&library_dir, dry_run, dir_template.as_ref()),
+        Some(Command::Segments { output_dir, file }) => segments(&output_dir, &file),
+        Some(Command::ExportState { output_dir, output }) => {
+            export_state(&output_dir, output.as_deref())
+        }
+        Some(Command::ImportState { output_dir, file }) => import_state(&output_dir, &file),
+        Some(Command::Verify { output_dir }) => verify(&output_dir),
+        Some(Command::Repair {
+            output_dir,
+            feed,
+            access,
+            options,
+        }) => repair(&output_dir, feed.as_deref(), &access, &options).await,
+        None => sync(cli.sync).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_episode_ranges() {
+        assert_eq!(parse_episode_range("1-10"), Ok(1..=10));
+        assert_eq!(parse_episode_range("5-"), Ok(5..=u32::MAX));
+        assert_eq!(parse_episode_range("-3"), Ok(0..=3));
+        assert_eq!(parse_episode_range("7"), Ok(7..=7));
+        assert!(parse_episode_range("10-1").is_err());
+        assert!(parse_episode_range("a-3").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::{Path, PathBuf};
+
+use super::episode::{episode_files, read_episode_metadata};
+use crate::error::MetadataError;
+
+/// Directory inside the output directory holding replaced episode files
+///
+/// Like all hidden directories, it is ignored when scanning for episodes.
+pub const ARCHIVE_DIR: &str = ".archive";
+
+/// Move a downloaded episode's audio file, metadata and sidecars into
+/// [`ARCHIVE_DIR`]
+///
+/// The files keep their names; earlier archived files of the same name are
+/// replaced. Returns the new path of the metadata file.
+pub fn archive_episode(metadata_path: &Path, output_dir: &Path) -> Result<PathBuf, MetadataError> {
+    let write_failed = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e| MetadataError::WriteFailed { path, source: e }
+    };
+
+    let metadata = read_episode_metadata(metadata_path)?;
+    let dir = output_dir.join(ARCHIVE_DIR);
+    std::fs::create_dir_all(&dir).map_err(write_failed(&dir))?;
+
+    for file in episode_files(metadata_path, &metadata) {
+        let target = dir.join(file.file_name().unwrap_or_default());
+        std::fs::rename(&file, &target).map_err(write_failed(&file))?;
+    }
+    Ok(dir.join(metadata_path.file_name().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::{EpisodeMetadata, save_episode_metadata};
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    #[test]
+    fn moves_audio_and_metadata_aside() {
+        let dir = tempdir().unwrap();
+        let season = dir.path().join("Season 01");
+        std::fs::create_dir(&season).unwrap();
+        let episode = Episode {
+            title: "Episode 1".to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some("ep1".to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep1.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let metadata = EpisodeMetadata::from_episode(&episode, "ep1.mp3", None);
+        save_episode_metadata(&metadata, &season.join("ep1.json")).unwrap();
+        std::fs::write(season.join("ep1.mp3"), b"old audio").unwrap();
+        std::fs::write(season.join("ep1.md"), "# Episode 1").unwrap();
+
+        let archived = archive_episode(&season.join("ep1.json"), dir.path()).unwrap();
+
+        let archive = dir.path().join(ARCHIVE_DIR);
+        assert_eq!(archived, archive.join("ep1.json"));
+        assert_eq!(
+            std::fs::read(archive.join("ep1.mp3")).unwrap(),
+            b"old audio"
+        );
+        assert!(archive.join("ep1.md").exists());
+        assert_eq!(std::fs::read_dir(&season).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,256 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::MetadataError;
+use crate::feed::Episode;
+
+/// Suffix of chapter sidecar files, replacing the audio file's extension
+pub const CHAPTERS_SUFFIX: &str = ".chapters.json";
+
+/// Version of the Podcasting 2.0 JSON chapters format that is written
+const CHAPTERS_FORMAT_VERSION: &str = "1.2.0";
+
+/// A single chapter mark
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    /// Start of the chapter in seconds
+    pub start_time: u64,
+    pub title: String,
+}
+
+/// Chapters document in the Podcasting 2.0 JSON chapters format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaptersDocument {
+    pub version: String,
+    pub chapters: Vec<Chapter>,
+}
+
+/// Whether the feed already links chapters for the episode
+/// (`podcast:chapters` or Podlove Simple Chapters)
+pub fn has_feed_chapters(episode: &Episode) -> bool {
+    episode
+        .extensions
+        .keys()
+        .any(|key| key.starts_with("podcast:chapters") || key.starts_with("psc:chapters"))
+}
+
+/// Extract a chapter list from timestamps in show notes
+///
+/// Recognizes one chapter per line in forms like `(00:12:34) Topic`,
+/// `[12:34] Topic` or `1:02:03 - Topic`; HTML markup is ignored. A list is
+/// only returned if it has at least two entries in ascending order, so
+/// stray timestamps in running text do not produce chapters.
+pub fn parse_description_chapters(description: &str) -> Vec<Chapter> {
+    let chapters: Vec<Chapter> = strip_html(description)
+        .lines()
+        .filter_map(parse_chapter_line)
+        .collect();
+
+    let ascending = chapters
+        .windows(2)
+        .all(|pair| pair[0].start_time < pair[1].start_time);
+
+    if chapters.len() >= 2 && ascending {
+        chapters
+    } else {
+        Vec::new()
+    }
+}
+
+/// Parse a Podcasting 2.0 JSON chapters document, as linked by
+/// `podcast:chapters`
+///
+/// Fractional start times are rounded down to whole seconds. Chapters marked
+/// `"toc": false` and untitled ones are dropped.
+pub fn parse_chapters_json(data: &[u8]) -> Result<Vec<Chapter>, MetadataError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawChapter {
+        start_time: f64,
+        title: Option<String>,
+        toc: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawDocument {
+        chapters: Vec<RawChapter>,
+    }
+
+    let document: RawDocument = serde_json::from_slice(data)?;
+    Ok(document
+        .chapters
+        .into_iter()
+        .filter(|chapter| chapter.toc != Some(false) && chapter.start_time >= 0.0)
+        .filter_map(|chapter| {
+            Some(Chapter {
+                start_time: chapter.start_time as u64,
+                title: chapter.title?,
+            })
+        })
+        .collect())
+}
+
+/// Write a chapters sidecar file
+pub fn write_chapters(chapters: &[Chapter], path: &Path) -> Result<(), MetadataError> {
+    let document = ChaptersDocument {
+        version: CHAPTERS_FORMAT_VERSION.to_string(),
+        chapters: chapters.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&document)?;
+    std::fs::write(path, json).map_err(|e| MetadataError::WriteFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+fn parse_chapter_line(line: &str) -> Option<Chapter> {
+    let line = line.trim().trim_start_matches(['-', '*', '•', ' ']);
+
+    let (line, closing) = match line.chars().next()? {
+        '(' => (&line[1..], Some(')')),
+        '[' => (&line[1..], Some(']')),
+        _ => (line, None),
+    };
+
+    let timestamp_end = line
+        .find(|c: char| !(c.is_ascii_digit() || c == ':'))
+        .unwrap_or(line.len());
+    let start_time = parse_timestamp(&line[..timestamp_end])?;
+
+    let mut rest = &line[timestamp_end..];
+    if let Some(closing) = closing {
+        rest = rest.strip_prefix(closing)?;
+    }
+
+    let title = rest
+        .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '–' | '—' | ':' | '|'))
+        .trim();
+
+    (!title.is_empty()).then(|| Chapter {
+        start_time,
+        title: title.to_string(),
+    })
+}
+
+/// Parse `MM:SS` or `H:MM:SS` into seconds
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let parts = timestamp
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts[..] {
+        [minutes, seconds] if seconds < 60 => Some(minutes * 60 + seconds),
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => {
+            Some(hours * 3600 + minutes * 60 + seconds)
+        }
+        _ => None,
+    }
+}
+
+/// Drop HTML tags, turning line-level tags into line breaks
+pub(crate) fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut chars = html.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            text.push(c);
+            continue;
+        }
+
+        let tag: String = chars.by_ref().take_while(|&c| c != '>').collect();
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        if matches!(name.as_str(), "br" | "p" | "li" | "div") {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start_time: u64, title: &str) -> Chapter {
+        Chapter {
+            start_time,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_parenthesized_timestamps() {
+        let description = "Show notes:\n(00:00:00) Intro\n(00:12:34) Main topic\n(01:02:03) Outro";
+
+        assert_eq!(
+            parse_description_chapters(description),
+            vec![
+                chapter(0, "Intro"),
+                chapter(754, "Main topic"),
+                chapter(3723, "Outro"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_html_lists_and_separators() {
+        let description = "<p>Chapters</p><ul><li>[00:00] Welcome</li>\
+            <li>05:30 - News</li><li>1:10:00 – Q&A</li></ul>";
+
+        assert_eq!(
+            parse_description_chapters(description),
+            vec![
+                chapter(0, "Welcome"),
+                chapter(330, "News"),
+                chapter(4200, "Q&A"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_single_or_unordered_timestamps() {
+        assert!(parse_description_chapters("Recorded at 10:30 in Berlin").is_empty());
+        assert!(parse_description_chapters("12:00 Later\n01:00 Earlier").is_empty());
+    }
+
+    #[test]
+    fn parses_chapters_json() {
+        let json = br#"{
+            "version": "1.2.0",
+            "chapters": [
+                {"startTime": 0, "title": "Intro"},
+                {"startTime": 61.5, "title": "Hidden", "toc": false},
+                {"startTime": 90.7, "title": "Topic", "img": "https://example.com/a.jpg"},
+                {"startTime": 120}
+            ]
+        }"#;
+
+        assert_eq!(
+            parse_chapters_json(json).unwrap(),
+            vec![chapter(0, "Intro"), chapter(90, "Topic")]
+        );
+        assert!(parse_chapters_json(b"<html>").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert_eq!(parse_timestamp("12:75"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("12"), None);
+        assert_eq!(parse_timestamp("1:02:03"), Some(3723));
+    }
+}
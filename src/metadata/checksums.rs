@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use super::episode::{episode_metadata_files, read_episode_metadata};
+use super::index::relative_path;
+use crate::error::MetadataError;
+
+/// Name of the checksum list covering the whole output directory
+pub const CHECKSUMS_FILENAME: &str = "SHA256SUMS";
+
+/// Suffix of per-file checksum files, appended to the audio filename
+pub const CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// Write `sha256sum`-compatible checksum files for all downloaded episodes
+///
+/// Creates `SHA256SUMS` in the output directory listing every audio file
+/// (relative to the output directory), plus an `<audio file>.sha256` next to
+/// each audio file. Hashes are taken from the episode metadata, so files are
+/// not re-read; episodes without a recorded SHA-256 hash (such as those
+/// hashed with another [`HashAlgorithm`](crate::hash::HashAlgorithm)) or
+/// whose audio file is gone or retired are left out. Returns the number of listed files.
+pub fn write_checksums(output_dir: &Path) -> Result<usize, MetadataError> {
+    let mut entries = Vec::new();
+
+    for path in episode_metadata_files(output_dir)? {
+        let Ok(metadata) = read_episode_metadata(&path) else {
+            continue;
+        };
+        if metadata.retired_at.is_some() {
+            continue;
+        }
+        let Some(hash) = metadata
+            .content_hash
+            .as_deref()
+            .and_then(|hash| hash.strip_prefix("sha256:"))
+        else {
+            continue;
+        };
+
+        let episode_dir = path.parent().unwrap_or(output_dir);
+        let audio_path = episode_dir.join(&metadata.audio_filename);
+        if !audio_path.is_file() {
+            continue;
+        }
+
+        let checksum_path =
+            episode_dir.join(format!("{}{}", metadata.audio_filename, CHECKSUM_SUFFIX));
+        write_file(
+            &checksum_path,
+            &format!("{}  {}\n", hash, metadata.audio_filename),
+        )?;
+
+        entries.push((relative_path(&audio_path, output_dir), hash.to_string()));
+    }
+
+    entries.sort();
+    let list: String = entries
+        .iter()
+        .map(|(relative, hash)| format!("{}  {}\n", hash, relative))
+        .collect();
+    write_file(&output_dir.join(CHECKSUMS_FILENAME), &list)?;
+
+    Ok(entries.len())
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), MetadataError> {
+    std::fs::write(path, content).map_err(|e| MetadataError::WriteFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    const HASH: &str = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+    fn write_episode(dir: &Path, name: &str, content_hash: Option<&str>) {
+        let episode = Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(name.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let audio_filename = format!("{}.mp3", name);
+        std::fs::write(dir.join(&audio_filename), b"foo").unwrap();
+        write_episode_metadata(
+            &episode,
+            &audio_filename,
+            content_hash.map(String::from),
+            &dir.join(format!("{}.json", name)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn writes_checksum_list_and_per_file_checksums() {
+        let dir = tempdir().unwrap();
+        let season = dir.path().join("Season 01");
+        std::fs::create_dir(&season).unwrap();
+
+        write_episode(dir.path(), "b", Some(&format!("sha256:{}", HASH)));
+        write_episode(&season, "a", Some(&format!("sha256:{}", HASH)));
+        write_episode(dir.path(), "unhashed", None);
+
+        assert_eq!(write_checksums(dir.path()).unwrap(), 2);
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(CHECKSUMS_FILENAME)).unwrap(),
+            format!("{HASH}  Season 01/a.mp3\n{HASH}  b.mp3\n")
+        );
+        assert_eq!(
+            std::fs::read_to_string(season.join("a.mp3.sha256")).unwrap(),
+            format!("{HASH}  a.mp3\n")
+        );
+        assert!(!dir.path().join("unhashed.mp3.sha256").exists());
+    }
+}
@@ -0,0 +1,158 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::episode::{episode_metadata_files, read_episode_metadata};
+use super::index::relative_path;
+use super::podcast::{PODCAST_METADATA_FILENAME, read_podcast_metadata};
+use crate::error::MetadataError;
+
+/// Columns of the CSV written by [`export_csv`]
+const CSV_HEADER: &str = "podcast,title,date,duration,size,path,hash";
+
+/// List every downloaded episode below `dir` as CSV
+///
+/// `dir` may be the output directory of a single podcast or a library
+/// holding one per podcast. Each episode is attributed to the podcast whose
+/// `podcast.json` is closest above it. Paths are those of the audio files,
+/// relative to `dir`; the date is the publication date as RFC 3339. Empty
+/// cells stand for unknown values. Rows are ordered by path.
+pub fn export_csv(dir: &Path) -> Result<String, MetadataError> {
+    let mut podcast_titles = HashMap::new();
+    let mut csv = format!("{}\n", CSV_HEADER);
+
+    for path in episode_metadata_files(dir)? {
+        let Ok(metadata) = read_episode_metadata(&path) else {
+            continue;
+        };
+        let episode_dir = path.parent().unwrap_or(dir);
+        let podcast = podcast_title(episode_dir, dir, &mut podcast_titles);
+
+        let row = [
+            podcast.unwrap_or_default(),
+            metadata.title,
+            metadata.pub_date.unwrap_or_default(),
+            metadata.duration.unwrap_or_default(),
+            metadata
+                .size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            relative_path(&episode_dir.join(&metadata.audio_filename), dir),
+            metadata.content_hash.unwrap_or_default(),
+        ];
+        let row: Vec<_> = row.iter().map(|cell| escape(cell)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+/// Title of the podcast `episode_dir` belongs to, looking no higher than `root`
+fn podcast_title(
+    episode_dir: &Path,
+    root: &Path,
+    cache: &mut HashMap<PathBuf, Option<String>>,
+) -> Option<String> {
+    for dir in episode_dir.ancestors() {
+        if !dir.starts_with(root) {
+            break;
+        }
+        if !cache.contains_key(dir) {
+            let title = dir
+                .join(PODCAST_METADATA_FILENAME)
+                .is_file()
+                .then(|| read_podcast_metadata(dir).ok().map(|podcast| podcast.title))
+                .flatten();
+            cache.insert(dir.to_path_buf(), title);
+        }
+        if let Some(title) = &cache[dir] {
+            return Some(title.clone());
+        }
+    }
+    None
+}
+
+/// Quote a CSV cell if needed (RFC 4180)
+fn escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::{
+        EpisodeMetadata, PodcastMetadata, save_episode_metadata, save_podcast_metadata,
+    };
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_podcast(dir: &Path, title: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        let metadata = PodcastMetadata {
+            title: title.to_string(),
+            description: None,
+            link: None,
+            author: None,
+            image_url: None,
+            feed_url: "https://example.com/feed.xml".to_string(),
+            updated_at: None,
+            etag: None,
+            last_modified: None,
+            funding: vec![],
+        };
+        save_podcast_metadata(&metadata, dir).unwrap();
+    }
+
+    fn write_episode(dir: &Path, title: &str, size: Option<u64>) {
+        std::fs::create_dir_all(dir).unwrap();
+        let episode = Episode {
+            title: title.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(title.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: Some("30:00".to_string()),
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let mut metadata = EpisodeMetadata::from_episode(&episode, "ep.mp3", None);
+        metadata.size = size;
+        save_episode_metadata(&metadata, &dir.join("ep.json")).unwrap();
+    }
+
+    #[test]
+    fn lists_episodes_of_a_library() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        write_podcast(&first, "First, the Podcast");
+        write_episode(&first.join("Season 01"), "Say \"hi\"", Some(42));
+        write_podcast(&second, "Second");
+        write_episode(&second, "Plain", None);
+
+        assert_eq!(
+            export_csv(dir.path()).unwrap(),
+            "podcast,title,date,duration,size,path,hash\n\
+             \"First, the Podcast\",\"Say \"\"hi\"\"\",,30:00,42,first/Season 01/ep.mp3,\n\
+             Second,Plain,,30:00,,second/ep.mp3,\n"
+        );
+    }
+}
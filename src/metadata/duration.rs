@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+/// Determine the playing time of an audio file, in whole seconds
+///
+/// Reads the duration from the container or stream headers where present.
+/// Otherwise (e.g. MP3 files without a Xing/Info header) the file's packets
+/// are counted, which reads the whole file but decodes nothing. Returns
+/// None for files in unknown formats or without audio.
+///
+/// Requires the `duration-probe` feature (enabled by default); without it,
+/// this always returns None.
+#[cfg(feature = "duration-probe")]
+pub fn probe_duration(path: &Path) -> Option<u64> {
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+    use symphonia::core::units::TimeBase;
+
+    let file = std::fs::File::open(path).ok()?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?
+        .format;
+
+    let track = format.default_track()?;
+    let track_id = track.id;
+    let params = &track.codec_params;
+    let time_base = params
+        .time_base
+        .or_else(|| params.sample_rate.map(|rate| TimeBase::new(1, rate)))?;
+
+    let frames = match params.n_frames {
+        Some(frames) => frames,
+        None => {
+            let mut end = 0;
+            while let Ok(packet) = format.next_packet() {
+                if packet.track_id() == track_id {
+                    end = packet.ts() + packet.dur();
+                }
+            }
+            end
+        }
+    };
+
+    let time = time_base.calc_time(frames);
+    Some(time.seconds + u64::from(time.frac >= 0.5))
+}
+
+/// Determine the playing time of an audio file, in whole seconds
+///
+/// Always None, as this build lacks the `duration-probe` feature.
+#[cfg(not(feature = "duration-probe"))]
+pub fn probe_duration(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Parse an `itunes:duration` into whole seconds
+///
+/// Accepts plain seconds (`1800`, `1800.5`) as well as `MM:SS` and
+/// `HH:MM:SS`, whose first part may exceed 59 (`90:00`) and whose seconds
+/// may have a fraction. Returns None for anything else.
+pub fn parse_itunes_duration(duration: &str) -> Option<u64> {
+    let parts: Vec<_> = duration.trim().split(':').map(str::trim).collect();
+    let (seconds, leading) = parts.split_last()?;
+    if leading.len() > 2 {
+        return None;
+    }
+    let seconds = seconds
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)?;
+    if !leading.is_empty() && seconds >= 60.0 {
+        return None;
+    }
+
+    let mut total: u64 = 0;
+    for (index, part) in leading.iter().enumerate() {
+        let value: u64 = part.parse().ok()?;
+        // Only the first part may exceed 59
+        if index > 0 && value >= 60 {
+            return None;
+        }
+        total = total.checked_mul(60)?.checked_add(value)?;
+    }
+    total.checked_mul(60)?.checked_add(seconds.round() as u64)
+}
+
+/// Format seconds as `HH:MM:SS`, like an `itunes:duration`
+pub(crate) fn format_duration(seconds: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WAV file of `seconds` of 8 kHz mono 16-bit silence
+    #[cfg(feature = "duration-probe")]
+    fn wav(seconds: u32) -> Vec<u8> {
+        let data_len = seconds * 8000 * 2;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes()); // bytes per second
+        wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+        wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        wav
+    }
+
+    #[cfg(feature = "duration-probe")]
+    #[test]
+    fn probes_audio_files_and_rejects_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("episode.wav");
+        std::fs::write(&audio, wav(3)).unwrap();
+        let text = dir.path().join("episode.mp3");
+        std::fs::write(&text, b"not audio").unwrap();
+
+        assert_eq!(probe_duration(&audio), Some(3));
+        assert_eq!(probe_duration(&text), None);
+        assert_eq!(probe_duration(&dir.path().join("missing.mp3")), None);
+    }
+
+    #[test]
+    fn parses_itunes_durations() {
+        assert_eq!(parse_itunes_duration("1800"), Some(1800));
+        assert_eq!(parse_itunes_duration(" 90.6 "), Some(91));
+        assert_eq!(parse_itunes_duration("30:00"), Some(1800));
+        assert_eq!(parse_itunes_duration("90:05"), Some(5405));
+        assert_eq!(parse_itunes_duration("1:02:03"), Some(3723));
+        assert_eq!(parse_itunes_duration("01:02:03.4"), Some(3723));
+
+        for invalid in ["", "abc", "-5", "1:60", "1:60:00", "1:2:3:4", "1::2", "NaN"] {
+            assert_eq!(parse_itunes_duration(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn formats_durations_like_itunes() {
+        assert_eq!(format_duration(59), "00:00:59");
+        assert_eq!(format_duration(3723), "01:02:03");
+    }
+}
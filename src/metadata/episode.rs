@@ -2,11 +2,18 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use super::chapters::CHAPTERS_SUFFIX;
+use super::checksums::CHECKSUM_SUFFIX;
+use super::index::EPISODE_INDEX_FILENAME;
+use super::podcast::PODCAST_METADATA_FILENAME;
+use super::receipt::{DownloadReceipt, RECEIPT_SUFFIX};
+use super::segments::SkipSegment;
+use crate::episode::TRANSCRIPT_INFIX;
 use crate::error::MetadataError;
 use crate::feed::Episode;
 
@@ -21,16 +28,60 @@ pub struct EpisodeMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub guid: Option<String>,
     pub original_url: String,
-    pub downloaded_at: String,
+    /// Length the feed announced for the enclosure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosure_length: Option<u64>,
+    /// Where `original_url` redirected to when the episode was downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// `ETag` the server sent with the audio file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` the server sent with the audio file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// `Content-Type` the server sent with the audio file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Time of the download (omitted for reproducible metadata)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloaded_at: Option<String>,
+    /// Duration from the feed, or else probed from the audio file
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub episode_number: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub season_number: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
     pub audio_filename: String,
+    /// Size of the audio file in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_hash: Option<String>,
+    /// Skippable parts of the episode, see [`import_segments`](super::import_segments)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_segments: Vec<SkipSegment>,
+    /// Transcript files downloaded alongside the audio
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transcripts: Vec<TranscriptMetadata>,
+    /// Time the audio file was deleted by a retention policy; the metadata
+    /// stays, so the episode is not downloaded again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired_at: Option<String>,
+}
+
+/// Serializable record of a downloaded transcript
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptMetadata {
+    pub filename: String,
+    pub original_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 impl EpisodeMetadata {
@@ -46,14 +97,34 @@ impl EpisodeMetadata {
             pub_date: episode.pub_date.map(|dt| dt.to_rfc3339()),
             guid: episode.guid.clone(),
             original_url: episode.enclosure.url.to_string(),
-            downloaded_at: Utc::now().to_rfc3339(),
+            enclosure_length: episode.enclosure.length,
+            final_url: None,
+            etag: None,
+            last_modified: None,
+            content_type: None,
+            downloaded_at: Some(Utc::now().to_rfc3339()),
             duration: episode.duration.clone(),
             episode_number: episode.episode_number,
             season_number: episode.season_number,
+            language: episode.language.clone(),
             audio_filename: audio_filename.to_string(),
+            size: None,
             content_hash,
+            skip_segments: Vec::new(),
+            transcripts: Vec::new(),
+            retired_at: None,
         }
     }
+
+    /// Record where the audio file was served from, and the server's
+    /// validators and content type, as captured in its download receipt
+    pub fn set_provenance(&mut self, receipt: &DownloadReceipt) {
+        let header = |name: &str| receipt.headers.get(name).cloned();
+        self.final_url = receipt.final_url.clone();
+        self.etag = header("etag");
+        self.last_modified = header("last-modified");
+        self.content_type = header("content-type");
+    }
 }
 
 /// Write episode metadata to a JSON file
@@ -63,14 +134,126 @@ pub fn write_episode_metadata(
     content_hash: Option<String>,
     path: &Path,
 ) -> Result<(), MetadataError> {
-    let metadata = EpisodeMetadata::from_episode(episode, audio_filename, content_hash);
-    let json = serde_json::to_string_pretty(&metadata)?;
+    save_episode_metadata(
+        &EpisodeMetadata::from_episode(episode, audio_filename, content_hash),
+        path,
+    )
+}
+
+/// Write already assembled episode metadata to a JSON file
+pub fn save_episode_metadata(metadata: &EpisodeMetadata, path: &Path) -> Result<(), MetadataError> {
+    let json = serde_json::to_string_pretty(metadata)?;
     std::fs::write(path, json).map_err(|e| MetadataError::WriteFailed {
         path: path.to_path_buf(),
         source: e,
     })
 }
 
+/// Files of a downloaded episode that exist, given its metadata file: the
+/// audio file, the metadata file itself and sidecars (receipt, chapters,
+/// checksum, show notes, transcripts)
+pub(crate) fn episode_files(metadata_path: &Path, metadata: &EpisodeMetadata) -> Vec<PathBuf> {
+    let dir = metadata_path.parent().unwrap_or(Path::new(""));
+    let stem = metadata_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    let mut names = vec![
+        metadata.audio_filename.clone(),
+        format!("{}{}", metadata.audio_filename, CHECKSUM_SUFFIX),
+        format!("{}{}", stem, RECEIPT_SUFFIX),
+        format!("{}{}", stem, CHAPTERS_SUFFIX),
+        format!("{}.md", stem),
+        format!("{}.txt", stem),
+    ];
+    names.extend(metadata.transcripts.iter().map(|t| t.filename.clone()));
+
+    let mut files = vec![metadata_path.to_path_buf()];
+    files.extend(
+        names
+            .into_iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file()),
+    );
+    files
+}
+
+/// Whether a file in the output directory holds episode metadata
+///
+/// Excludes `podcast.json`, the episode index and JSON sidecars (chapters,
+/// transcripts, receipts).
+pub(crate) fn is_episode_metadata_filename(filename: &str) -> bool {
+    let Some(name) = filename.strip_suffix(".json") else {
+        return false;
+    };
+
+    let is_transcript = name.ends_with(TRANSCRIPT_INFIX)
+        || name
+            .rsplit_once('.')
+            .is_some_and(|(name, _)| name.ends_with(TRANSCRIPT_INFIX));
+
+    filename != PODCAST_METADATA_FILENAME
+        && filename != EPISODE_INDEX_FILENAME
+        && !filename.ends_with(CHAPTERS_SUFFIX)
+        && !filename.ends_with(RECEIPT_SUFFIX)
+        && !is_transcript
+}
+
+/// Episode metadata files in the output directory and its subdirectories,
+/// except hidden ones
+pub(crate) fn episode_metadata_files(output_dir: &Path) -> Result<Vec<PathBuf>, MetadataError> {
+    let mut files = library_files(output_dir)?;
+    files.retain(|path| {
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        is_episode_metadata_filename(filename)
+    });
+    Ok(files)
+}
+
+/// All files below the output directory, except in hidden directories,
+/// sorted by path
+pub(crate) fn library_files(output_dir: &Path) -> Result<Vec<PathBuf>, MetadataError> {
+    let mut files = Vec::new();
+    let mut dirs = vec![output_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for path in read_dir(&dir)? {
+            if path.is_dir() {
+                if !is_hidden(&path) {
+                    dirs.push(path);
+                }
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    files.retain(|path| path.is_file());
+    files.sort();
+
+    Ok(files)
+}
+
+/// Whether a file or directory is hidden (its name starts with a dot), like
+/// the quarantine directory
+pub(crate) fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, MetadataError> {
+    let read_failed = |e| MetadataError::ReadFailed {
+        path: dir.to_path_buf(),
+        source: e,
+    };
+
+    std::fs::read_dir(dir)
+        .map_err(read_failed)?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(read_failed))
+        .collect()
+}
+
 /// Read episode metadata from a JSON file
 pub fn read_episode_metadata(path: &Path) -> Result<EpisodeMetadata, MetadataError> {
     let content = std::fs::read_to_string(path).map_err(|e| MetadataError::ReadFailed {
@@ -89,6 +272,7 @@ mod tests {
     use super::*;
     use crate::feed::Enclosure;
     use chrono::DateTime;
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
     use url::Url;
 
@@ -103,9 +287,13 @@ mod tests {
                 length: Some(1234567),
                 mime_type: Some("audio/mpeg".to_string()),
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: Some("30:00".to_string()),
             episode_number: Some(42),
             season_number: Some(2),
+            language: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -130,6 +318,35 @@ mod tests {
         assert_eq!(metadata.content_hash, Some("sha256:abc123".to_string()));
     }
 
+    #[test]
+    fn set_provenance_takes_final_url_and_headers() {
+        let mut metadata = EpisodeMetadata::from_episode(&make_episode(), "test.mp3", None);
+        let receipt = DownloadReceipt {
+            url: "https://example.com/episode.mp3".to_string(),
+            final_url: Some("https://cdn.example.com/episode.mp3".to_string()),
+            status: 200,
+            started_at: "2024-01-15T12:00:00Z".to_string(),
+            completed_at: "2024-01-15T12:00:05Z".to_string(),
+            bytes: 1234567,
+            content_hash: "sha256:abc123".to_string(),
+            headers: BTreeMap::from([
+                ("content-type".to_string(), "audio/mpeg".to_string()),
+                ("etag".to_string(), "\"v1\"".to_string()),
+            ]),
+            peer_certificate: None,
+        };
+
+        metadata.set_provenance(&receipt);
+
+        assert_eq!(
+            metadata.final_url.as_deref(),
+            Some("https://cdn.example.com/episode.mp3")
+        );
+        assert_eq!(metadata.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(metadata.last_modified, None);
+        assert_eq!(metadata.content_type.as_deref(), Some("audio/mpeg"));
+    }
+
     #[test]
     fn write_and_read_roundtrip() {
         let dir = tempdir().unwrap();
@@ -151,6 +368,26 @@ mod tests {
         assert_eq!(read_back.content_hash, Some("sha256:abc123".to_string()));
     }
 
+    #[test]
+    fn recognizes_episode_metadata_filenames() {
+        assert!(is_episode_metadata_filename("2024-01-15-episode.json"));
+        assert!(!is_episode_metadata_filename("2024-01-15-episode.mp3"));
+        assert!(!is_episode_metadata_filename("podcast.json"));
+        assert!(!is_episode_metadata_filename("episodes.json"));
+        assert!(!is_episode_metadata_filename(
+            "2024-01-15-episode.chapters.json"
+        ));
+        assert!(!is_episode_metadata_filename(
+            "2024-01-15-episode.transcript.json"
+        ));
+        assert!(!is_episode_metadata_filename(
+            "2024-01-15-episode.transcript.de.json"
+        ));
+        assert!(!is_episode_metadata_filename(
+            "2024-01-15-episode.receipt.json"
+        ));
+    }
+
     #[test]
     fn read_nonexistent_returns_error() {
         let dir = tempdir().unwrap();
@@ -171,9 +408,13 @@ mod tests {
                 length: None,
                 mime_type: None,
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: None,
             episode_number: None,
             season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
         };
 
         let metadata = EpisodeMetadata::from_episode(&episode, "minimal.mp3", None);
@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::episode::{EpisodeMetadata, episode_metadata_files, read_episode_metadata};
+use crate::error::MetadataError;
+
+/// Name of the index of all episodes in the output directory
+pub const EPISODE_INDEX_FILENAME: &str = "episodes.json";
+
+/// Metadata of all downloaded episodes of a podcast, in one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpisodeIndex {
+    pub episodes: Vec<IndexedEpisode>,
+}
+
+/// Episode metadata as listed in an [`EpisodeIndex`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedEpisode {
+    /// Directory holding the episode, relative to the output directory and
+    /// `/`-separated; empty for the output directory itself
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub directory: String,
+    #[serde(flatten)]
+    pub metadata: EpisodeMetadata,
+}
+
+/// Write `episodes.json`, listing the metadata of every downloaded episode
+///
+/// The per-episode metadata files stay authoritative; the index is rebuilt
+/// from them, ordered by path. Episodes whose metadata cannot be read, or
+/// whose audio a retention policy deleted, are left out. The index is
+/// replaced atomically, so readers never see a partial file. Returns the
+/// number of listed episodes.
+pub fn write_episode_index(output_dir: &Path) -> Result<usize, MetadataError> {
+    let mut episodes = Vec::new();
+
+    for path in episode_metadata_files(output_dir)? {
+        let Ok(metadata) = read_episode_metadata(&path) else {
+            continue;
+        };
+        if metadata.retired_at.is_some() {
+            continue;
+        }
+        let episode_dir = path.parent().unwrap_or(output_dir);
+        episodes.push(IndexedEpisode {
+            directory: relative_path(episode_dir, output_dir),
+            metadata,
+        });
+    }
+
+    let count = episodes.len();
+    let json = serde_json::to_string_pretty(&EpisodeIndex { episodes })?;
+
+    // Renaming within a directory replaces the old index in one step
+    let path = output_dir.join(EPISODE_INDEX_FILENAME);
+    let temp_path = output_dir.join(format!(".{}.tmp", EPISODE_INDEX_FILENAME));
+    let write_failed = |e| MetadataError::WriteFailed {
+        path: path.clone(),
+        source: e,
+    };
+    std::fs::write(&temp_path, json).map_err(write_failed)?;
+    std::fs::rename(&temp_path, &path).map_err(write_failed)?;
+
+    Ok(count)
+}
+
+/// Read the episode index of an output directory
+pub fn read_episode_index(output_dir: &Path) -> Result<EpisodeIndex, MetadataError> {
+    let path = output_dir.join(EPISODE_INDEX_FILENAME);
+
+    let content = std::fs::read_to_string(&path).map_err(|e| MetadataError::ReadFailed {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    serde_json::from_str(&content).map_err(|e| MetadataError::JsonParseFailed { path, source: e })
+}
+
+/// `path` relative to `base`, `/`-separated
+pub(crate) fn relative_path(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_episode(dir: &Path, name: &str) {
+        let episode = Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(name.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        write_episode_metadata(
+            &episode,
+            &format!("{}.mp3", name),
+            None,
+            &dir.join(format!("{}.json", name)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn indexes_episodes_of_all_directories() {
+        let dir = tempdir().unwrap();
+        let season = dir.path().join("Season 01");
+        std::fs::create_dir(&season).unwrap();
+
+        write_episode(dir.path(), "b");
+        write_episode(&season, "a");
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        assert_eq!(write_episode_index(dir.path()).unwrap(), 2);
+        // The index itself is not taken for episode metadata
+        assert_eq!(write_episode_index(dir.path()).unwrap(), 2);
+
+        let index = read_episode_index(dir.path()).unwrap();
+        let listed: Vec<_> = index
+            .episodes
+            .iter()
+            .map(|episode| (episode.directory.as_str(), episode.metadata.guid.as_deref()))
+            .collect();
+        assert_eq!(listed, vec![("Season 01", Some("a")), ("", Some("b"))]);
+        assert!(!dir.path().join(".episodes.json.tmp").exists());
+    }
+}
@@ -2,8 +2,52 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+mod archive;
+mod chapters;
+mod checksums;
+mod csv;
+mod duration;
 mod episode;
+mod index;
+mod notes;
 mod podcast;
+mod quarantine;
+mod receipt;
+mod segments;
+mod tags;
+mod verify;
+mod xattr;
 
-pub use episode::{EpisodeMetadata, read_episode_metadata, write_episode_metadata};
-pub use podcast::{PodcastMetadata, read_podcast_metadata, write_podcast_metadata};
+pub(crate) use duration::format_duration;
+pub(crate) use episode::{
+    episode_files, episode_metadata_files, is_episode_metadata_filename, is_hidden,
+};
+pub(crate) use podcast::PODCAST_METADATA_FILENAME;
+pub(crate) use verify::hash_file;
+
+pub use archive::{ARCHIVE_DIR, archive_episode};
+pub use chapters::{
+    CHAPTERS_SUFFIX, Chapter, ChaptersDocument, has_feed_chapters, parse_chapters_json,
+    parse_description_chapters, write_chapters,
+};
+pub use checksums::{CHECKSUM_SUFFIX, CHECKSUMS_FILENAME, write_checksums};
+pub use csv::export_csv;
+pub use duration::{parse_itunes_duration, probe_duration};
+pub use episode::{
+    EpisodeMetadata, TranscriptMetadata, read_episode_metadata, save_episode_metadata,
+    write_episode_metadata,
+};
+pub use index::{
+    EPISODE_INDEX_FILENAME, EpisodeIndex, IndexedEpisode, read_episode_index, write_episode_index,
+};
+pub use notes::{ShowNotesFormat, render_show_notes, write_show_notes};
+pub use podcast::{
+    FundingMetadata, PodcastMetadata, read_podcast_metadata, save_podcast_metadata,
+    write_podcast_metadata,
+};
+pub use quarantine::{QUARANTINE_DIR, QuarantineNote, quarantine_file};
+pub use receipt::{DownloadReceipt, RECEIPT_SUFFIX, write_receipt};
+pub use segments::{SegmentFile, SkipSegment, import_segments, read_segment_file};
+pub use tags::{write_chapter_frames, write_tags};
+pub use verify::{VerifyReport, verify_library};
+pub use xattr::{FileAttributes, XATTR_PREFIX, read_file_attributes, write_file_attributes};
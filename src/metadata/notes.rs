@@ -0,0 +1,289 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use crate::error::MetadataError;
+use crate::feed::Episode;
+
+/// Format of the show notes written by [`write_show_notes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShowNotesFormat {
+    /// Markdown, keeping headings, emphasis and links of HTML show notes
+    Markdown,
+    /// Plain text, with link targets in parentheses
+    Text,
+}
+
+impl ShowNotesFormat {
+    /// File extension of show notes in this format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Text => "txt",
+        }
+    }
+}
+
+/// Render an episode's title, date and description as a readable document
+///
+/// HTML descriptions are converted; plain-text ones are kept as they are.
+/// Returns None for episodes without a description.
+pub fn render_show_notes(episode: &Episode, format: ShowNotesFormat) -> Option<String> {
+    let description = episode.description.as_deref()?.trim();
+    if description.is_empty() {
+        return None;
+    }
+
+    let markdown = format == ShowNotesFormat::Markdown;
+    let mut notes = String::new();
+    if markdown {
+        notes.push_str("# ");
+    }
+    notes.push_str(episode.title.trim());
+    notes.push('\n');
+    if let Some(date) = episode.pub_date {
+        if markdown {
+            notes.push('\n');
+        }
+        notes.push_str(&date.format("%Y-%m-%d").to_string());
+        notes.push('\n');
+    }
+    notes.push('\n');
+    if is_html(description) {
+        notes.push_str(&html_to_text(description, markdown));
+    } else {
+        notes.push_str(description);
+    }
+    notes.push('\n');
+
+    Some(notes)
+}
+
+/// Write an episode's show notes, see [`render_show_notes`]
+///
+/// Returns false, writing nothing, for episodes without a description.
+pub fn write_show_notes(
+    episode: &Episode,
+    format: ShowNotesFormat,
+    path: &Path,
+) -> Result<bool, MetadataError> {
+    let Some(notes) = render_show_notes(episode, format) else {
+        return Ok(false);
+    };
+    std::fs::write(path, notes).map_err(|e| MetadataError::WriteFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    Ok(true)
+}
+
+/// Whether a description contains HTML tags
+fn is_html(text: &str) -> bool {
+    text.match_indices('<').any(|(index, _)| {
+        text[index + 1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/')
+    })
+}
+
+/// Convert HTML to Markdown, or to plain text without `markdown`
+fn html_to_text(html: &str, markdown: bool) -> String {
+    let mut text = String::with_capacity(html.len());
+    // Start of each open link's text in `text`, with its target
+    let mut links: Vec<(usize, Option<String>)> = Vec::new();
+    let mut skipped: Option<String> = None;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            if skipped.is_none() {
+                push_text(&mut text, rest);
+            }
+            break;
+        };
+        if skipped.is_none() {
+            push_text(&mut text, &rest[..start]);
+        }
+        let end = rest[start..]
+            .find('>')
+            .map_or(rest.len(), |end| start + end);
+        let tag = &rest[start + 1..end];
+        rest = rest.get(end + 1..).unwrap_or("");
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        if let Some(skipped_name) = &skipped {
+            if closing && name == *skipped_name {
+                skipped = None;
+            }
+            continue;
+        }
+
+        match (name.as_str(), closing) {
+            ("script" | "style", false) => skipped = Some(name),
+            ("br", _) => {
+                trim_end_spaces(&mut text);
+                text.push('\n');
+            }
+            ("p" | "div" | "ul" | "ol" | "blockquote" | "pre" | "table", _) => {
+                break_paragraph(&mut text)
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", _) => {
+                break_paragraph(&mut text);
+                if markdown && !closing {
+                    let level = usize::from(name.as_bytes()[1] - b'0');
+                    text.push_str(&"#".repeat(level));
+                    text.push(' ');
+                }
+            }
+            ("li", false) => {
+                break_line(&mut text);
+                text.push_str("- ");
+            }
+            ("tr", _) => break_line(&mut text),
+            ("strong" | "b", _) if markdown => text.push_str("**"),
+            ("em" | "i", _) if markdown => text.push('_'),
+            ("a", false) => links.push((text.len(), attribute(tag, "href"))),
+            ("a", true) => {
+                if let Some((start, Some(href))) = links.pop() {
+                    let label = text[start..].trim().to_string();
+                    text.truncate(start);
+                    if label.is_empty() || label == href {
+                        text.push_str(&href);
+                    } else if markdown {
+                        text.push_str(&format!("[{}]({})", label, href));
+                    } else {
+                        text.push_str(&format!("{} ({})", label, href));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        // At most one blank line in a row
+        if !(line.is_empty() && lines.last().is_none_or(|last| last.is_empty())) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Append text, collapsing whitespace as HTML does
+fn push_text(text: &mut String, html: &str) {
+    for c in html.chars() {
+        if c.is_whitespace() {
+            if !text.is_empty() && !text.ends_with([' ', '\n']) {
+                text.push(' ');
+            }
+        } else {
+            text.push(c);
+        }
+    }
+}
+
+fn trim_end_spaces(text: &mut String) {
+    text.truncate(text.trim_end_matches(' ').len());
+}
+
+fn break_line(text: &mut String) {
+    trim_end_spaces(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+fn break_paragraph(text: &mut String) {
+    break_line(text);
+    if !text.is_empty() && !text.ends_with("\n\n") {
+        text.push('\n');
+    }
+}
+
+/// Value of an attribute in the inside of a tag, e.g. `a href="..."`
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value.split_whitespace().next()?,
+    };
+    Some(value.to_string()).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use std::collections::BTreeMap;
+    use url::Url;
+
+    fn episode(description: &str) -> Episode {
+        Episode {
+            title: "Episode 1".to_string(),
+            description: Some(description.to_string()),
+            pub_date: chrono::DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z").ok(),
+            guid: None,
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    const HTML: &str = "<h2>Topics</h2>\n<p>We talk   about <b>Rust</b>.<br>And more:</p>\
+        <ul><li><a href=\"https://example.com/a\">Link A</a></li>\
+        <li><a href='https://example.com/b'>https://example.com/b</a></li></ul>\
+        <script>track()</script>";
+
+    #[test]
+    fn converts_html_show_notes_to_markdown() {
+        assert_eq!(
+            render_show_notes(&episode(HTML), ShowNotesFormat::Markdown).unwrap(),
+            "# Episode 1\n\n2024-01-15\n\n## Topics\n\nWe talk about **Rust**.\nAnd more:\n\n\
+             - [Link A](https://example.com/a)\n- https://example.com/b\n"
+        );
+    }
+
+    #[test]
+    fn converts_html_show_notes_to_text() {
+        assert_eq!(
+            render_show_notes(&episode(HTML), ShowNotesFormat::Text).unwrap(),
+            "Episode 1\n2024-01-15\n\nTopics\n\nWe talk about Rust.\nAnd more:\n\n\
+             - Link A (https://example.com/a)\n- https://example.com/b\n"
+        );
+    }
+
+    #[test]
+    fn keeps_plain_text_show_notes() {
+        let notes = "Line one\n\n  indented, 1 < 2";
+        assert_eq!(
+            render_show_notes(&episode(notes), ShowNotesFormat::Text).unwrap(),
+            "Episode 1\n2024-01-15\n\nLine one\n\n  indented, 1 < 2\n"
+        );
+        assert_eq!(
+            render_show_notes(&episode(" "), ShowNotesFormat::Markdown),
+            None
+        );
+    }
+}
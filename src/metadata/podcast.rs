@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::error::MetadataError;
 use crate::feed::Podcast;
 
-const PODCAST_METADATA_FILENAME: &str = "podcast.json";
+pub(crate) const PODCAST_METADATA_FILENAME: &str = "podcast.json";
 
 /// Serializable metadata for a podcast feed
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,7 +25,26 @@ pub struct PodcastMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_url: Option<String>,
     pub feed_url: String,
-    pub updated_at: String,
+    /// Time of the sync that wrote the file (omitted for reproducible metadata)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// `ETag` of the feed as of the last complete sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` of the feed as of the last complete sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Donation and support links announced by the feed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub funding: Vec<FundingMetadata>,
+}
+
+/// Serializable support link of a podcast
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingMetadata {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 impl PodcastMetadata {
@@ -38,17 +57,34 @@ impl PodcastMetadata {
             author: podcast.author.clone(),
             image_url: podcast.image_url.as_ref().map(|u| u.to_string()),
             feed_url: podcast.feed_url.to_string(),
-            updated_at: Utc::now().to_rfc3339(),
+            updated_at: Some(Utc::now().to_rfc3339()),
+            etag: None,
+            last_modified: None,
+            funding: podcast
+                .funding
+                .iter()
+                .map(|link| FundingMetadata {
+                    url: link.url.to_string(),
+                    title: link.title.clone(),
+                })
+                .collect(),
         }
     }
 }
 
 /// Write podcast metadata to the output directory
 pub fn write_podcast_metadata(podcast: &Podcast, output_dir: &Path) -> Result<(), MetadataError> {
-    let metadata = PodcastMetadata::from_podcast(podcast);
+    save_podcast_metadata(&PodcastMetadata::from_podcast(podcast), output_dir)
+}
+
+/// Write already assembled podcast metadata to the output directory
+pub fn save_podcast_metadata(
+    metadata: &PodcastMetadata,
+    output_dir: &Path,
+) -> Result<(), MetadataError> {
     let path = output_dir.join(PODCAST_METADATA_FILENAME);
 
-    let json = serde_json::to_string_pretty(&metadata)?;
+    let json = serde_json::to_string_pretty(metadata)?;
     std::fs::write(&path, json).map_err(|e| MetadataError::WriteFailed { path, source: e })
 }
 
@@ -71,6 +107,8 @@ mod tests {
     use tempfile::tempdir;
     use url::Url;
 
+    use crate::feed::FundingLink;
+
     fn make_podcast() -> Podcast {
         Podcast {
             title: "Test Podcast".to_string(),
@@ -79,6 +117,11 @@ mod tests {
             author: Some("Test Author".to_string()),
             image_url: Some(Url::parse("https://example.com/image.jpg").unwrap()),
             feed_url: Url::parse("https://example.com/feed.xml").unwrap(),
+            new_feed_url: None,
+            funding: vec![FundingLink {
+                url: Url::parse("https://example.com/donate").unwrap(),
+                title: Some("Support us".to_string()),
+            }],
             episodes: vec![],
         }
     }
@@ -97,6 +140,13 @@ mod tests {
             Some("https://example.com/image.jpg".to_string())
         );
         assert_eq!(metadata.feed_url, "https://example.com/feed.xml");
+        assert_eq!(
+            metadata.funding,
+            vec![FundingMetadata {
+                url: "https://example.com/donate".to_string(),
+                title: Some("Support us".to_string()),
+            }]
+        );
     }
 
     #[test]
@@ -111,6 +161,23 @@ mod tests {
         assert_eq!(read_back.description, Some("A test podcast".to_string()));
     }
 
+    #[test]
+    fn cache_validators_roundtrip() {
+        let dir = tempdir().unwrap();
+        let mut metadata = PodcastMetadata::from_podcast(&make_podcast());
+        metadata.etag = Some("\"v1\"".to_string());
+        metadata.last_modified = Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string());
+
+        save_podcast_metadata(&metadata, dir.path()).unwrap();
+        let read_back = read_podcast_metadata(dir.path()).unwrap();
+
+        assert_eq!(read_back.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            read_back.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+    }
+
     #[test]
     fn read_nonexistent_returns_error() {
         let dir = tempdir().unwrap();
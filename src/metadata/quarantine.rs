@@ -0,0 +1,58 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::MetadataError;
+
+/// Directory inside the output directory holding quarantined files
+///
+/// Like all hidden directories, it is ignored when scanning for episodes.
+pub const QUARANTINE_DIR: &str = ".quarantine";
+
+/// Note stored next to a quarantined file, as `<file>.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineNote {
+    pub episode_title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guid: Option<String>,
+    pub url: String,
+    /// Why the download failed
+    pub error: String,
+    /// Size of the quarantined file
+    pub bytes: u64,
+    /// RFC 3339 timestamp in UTC
+    pub quarantined_at: String,
+}
+
+/// Move a failed download into [`QUARANTINE_DIR`] together with a note
+///
+/// The file keeps its name, minus a `.partial` suffix; an earlier file of
+/// the same name is replaced. Returns the new path.
+pub fn quarantine_file(
+    file: &Path,
+    output_dir: &Path,
+    note: &QuarantineNote,
+) -> Result<PathBuf, MetadataError> {
+    let write_failed = |path: &Path| {
+        let path = path.to_path_buf();
+        move |e| MetadataError::WriteFailed { path, source: e }
+    };
+
+    let dir = output_dir.join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&dir).map_err(write_failed(&dir))?;
+
+    let filename = file.file_name().unwrap_or_default().to_string_lossy();
+    let filename = filename.strip_suffix(".partial").unwrap_or(&filename);
+    let target = dir.join(filename);
+    std::fs::rename(file, &target).map_err(write_failed(file))?;
+
+    let note_path = dir.join(format!("{}.json", filename));
+    let json = serde_json::to_string_pretty(note)?;
+    std::fs::write(&note_path, json).map_err(write_failed(&note_path))?;
+
+    Ok(target)
+}
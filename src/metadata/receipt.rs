@@ -0,0 +1,48 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::MetadataError;
+
+/// Suffix of download receipt files, replacing the audio file's extension
+pub const RECEIPT_SUFFIX: &str = ".receipt.json";
+
+/// Provenance record of a single download
+///
+/// Documents where a file was fetched from and what the server answered,
+/// for archives that need to prove the origin of collected media.
+/// Timestamps are RFC 3339 in UTC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadReceipt {
+    /// Enclosure URL that was requested
+    pub url: String,
+    /// URL the file was served from after redirects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    pub status: u16,
+    pub started_at: String,
+    pub completed_at: String,
+    pub bytes: u64,
+    /// Content hash of the file, prefixed with its algorithm (format: "sha256:...")
+    pub content_hash: String,
+    /// Selected response headers, see [`RECEIPT_HEADERS`](crate::http::RECEIPT_HEADERS)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// SHA-256 fingerprint of the server's TLS certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_certificate: Option<String>,
+}
+
+/// Write a download receipt to a JSON file
+pub fn write_receipt(receipt: &DownloadReceipt, path: &Path) -> Result<(), MetadataError> {
+    let json = serde_json::to_string_pretty(receipt)?;
+    std::fs::write(path, json).map_err(|e| MetadataError::WriteFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::episode::{episode_metadata_files, read_episode_metadata, save_episode_metadata};
+use crate::error::MetadataError;
+
+/// A part of an episode that players may skip (sponsor read, intro, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkipSegment {
+    /// Start of the segment in seconds
+    pub start: f64,
+    /// End of the segment in seconds
+    pub end: f64,
+    /// Kind of segment, e.g. "sponsor" or "intro"
+    pub category: String,
+}
+
+/// User-provided skip segments, keyed by episode GUID or audio filename
+pub type SegmentFile = BTreeMap<String, Vec<SkipSegment>>;
+
+/// Read a JSON segment file
+///
+/// The file maps an episode's GUID or audio filename to its segments:
+///
+/// ```json
+/// { "episode-guid": [{ "start": 12.5, "end": 75.0, "category": "sponsor" }] }
+/// ```
+pub fn read_segment_file(path: &Path) -> Result<SegmentFile, MetadataError> {
+    let content = std::fs::read_to_string(path).map_err(|e| MetadataError::ReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+
+    serde_json::from_str(&content).map_err(|e| MetadataError::JsonParseFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// Store skip segments in the metadata sidecars of downloaded episodes
+///
+/// Episodes are matched by GUID first, then by audio filename. Segments of a
+/// matched episode replace any previously imported ones; segments that end
+/// before they start are dropped. Returns the number of updated episodes.
+pub fn import_segments(output_dir: &Path, segments: &SegmentFile) -> Result<usize, MetadataError> {
+    let mut updated = 0;
+
+    for path in episode_metadata_files(output_dir)? {
+        // Files that are not episode metadata are skipped, like during a sync
+        let Ok(mut metadata) = read_episode_metadata(&path) else {
+            continue;
+        };
+
+        let matched = metadata
+            .guid
+            .as_ref()
+            .and_then(|guid| segments.get(guid))
+            .or_else(|| segments.get(&metadata.audio_filename));
+
+        if let Some(matched) = matched {
+            metadata.skip_segments = matched
+                .iter()
+                .filter(|segment| segment.end > segment.start)
+                .cloned()
+                .collect();
+            save_episode_metadata(&metadata, &path)?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_episode(dir: &Path, name: &str, guid: Option<&str>) -> PathBuf {
+        let episode = Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: None,
+            guid: guid.map(String::from),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let path = dir.join(format!("{}.json", name));
+        write_episode_metadata(&episode, &format!("{}.mp3", name), None, &path).unwrap();
+        path
+    }
+
+    fn segment(start: f64, end: f64) -> SkipSegment {
+        SkipSegment {
+            start,
+            end,
+            category: "sponsor".to_string(),
+        }
+    }
+
+    #[test]
+    fn imports_segments_by_guid_and_filename() {
+        let dir = tempdir().unwrap();
+        let season = dir.path().join("Season 01");
+        std::fs::create_dir(&season).unwrap();
+
+        let by_guid = write_episode(dir.path(), "first", Some("guid-1"));
+        let by_filename = write_episode(&season, "second", None);
+        let untouched = write_episode(dir.path(), "third", Some("guid-3"));
+
+        let segments = SegmentFile::from([
+            (
+                "guid-1".to_string(),
+                vec![segment(10.0, 70.5), segment(90.0, 80.0)],
+            ),
+            ("second.mp3".to_string(), vec![segment(0.0, 30.0)]),
+        ]);
+
+        assert_eq!(import_segments(dir.path(), &segments).unwrap(), 2);
+
+        let first = read_episode_metadata(&by_guid).unwrap();
+        assert_eq!(first.skip_segments, vec![segment(10.0, 70.5)]);
+        let second = read_episode_metadata(&by_filename).unwrap();
+        assert_eq!(second.skip_segments, vec![segment(0.0, 30.0)]);
+        let third = read_episode_metadata(&untouched).unwrap();
+        assert!(third.skip_segments.is_empty());
+    }
+
+    #[test]
+    fn reads_segment_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("segments.json");
+        std::fs::write(
+            &path,
+            r#"{"guid-1": [{"start": 1.5, "end": 60, "category": "intro"}]}"#,
+        )
+        .unwrap();
+
+        let segments = read_segment_file(&path).unwrap();
+        assert_eq!(
+            segments["guid-1"],
+            vec![SkipSegment {
+                start: 1.5,
+                end: 60.0,
+                category: "intro".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use chrono::{Datelike, Timelike};
+use id3::frame::{self, Comment, Frame, TableOfContents};
+use id3::{Tag, TagLike, Timestamp, Version};
+
+use super::chapters::{Chapter, strip_html};
+use super::duration::parse_itunes_duration;
+use super::podcast::PodcastMetadata;
+use crate::error::MetadataError;
+use crate::feed::Episode;
+
+/// Genre written into tagged episodes
+const GENRE: &str = "Podcast";
+
+/// Element ID of the table of contents listing the chapters
+const TOC_ELEMENT_ID: &str = "toc";
+
+/// Byte offsets are not used; chapters are placed by time
+const NO_OFFSET: u32 = u32::MAX;
+
+/// Write ID3v2.4 tags describing an episode into an MP3 file
+///
+/// Sets title, album (podcast title), artist (podcast author), track
+/// (episode number), publication date, genre and comment (show notes). Other
+/// frames already in the file, such as cover art, are kept. Files that are
+/// not MP3s are left alone; returns whether the file was tagged.
+pub fn write_tags(
+    path: &Path,
+    episode: &Episode,
+    podcast: &PodcastMetadata,
+) -> Result<bool, MetadataError> {
+    if !is_mp3(path) {
+        return Ok(false);
+    }
+
+    // Missing or unreadable tags are replaced
+    let mut tag = Tag::read_from_path(path).unwrap_or_default();
+
+    tag.set_title(episode.title.as_str());
+    tag.set_album(podcast.title.as_str());
+    if let Some(author) = &podcast.author {
+        tag.set_artist(author.as_str());
+    }
+    if let Some(number) = episode.episode_number {
+        tag.set_track(number);
+    }
+    if let Some(date) = episode.pub_date {
+        tag.set_date_recorded(Timestamp {
+            year: date.year(),
+            month: Some(date.month() as u8),
+            day: Some(date.day() as u8),
+            hour: Some(date.hour() as u8),
+            minute: Some(date.minute() as u8),
+            second: Some(date.second() as u8),
+        });
+    }
+    tag.set_genre(GENRE);
+    if let Some(description) = &episode.description {
+        tag.remove_comment(Some(""), None);
+        tag.add_frame(Comment {
+            lang: "eng".to_string(),
+            description: String::new(),
+            text: strip_html(description).trim().to_string(),
+        });
+    }
+
+    save_tag(&tag, path)
+}
+
+/// Write chapter marks into an MP3 file as ID3v2 CHAP frames with a CTOC
+///
+/// Replaces chapters already in the file. Each chapter ends where the next
+/// one starts; the last one at the episode's duration, or with the file if
+/// the feed gives none. Files that are not MP3s are left alone; returns
+/// whether chapters were written.
+pub fn write_chapter_frames(
+    path: &Path,
+    episode: &Episode,
+    chapters: &[Chapter],
+) -> Result<bool, MetadataError> {
+    if !is_mp3(path) || chapters.is_empty() {
+        return Ok(false);
+    }
+
+    let mut tag = Tag::read_from_path(path).unwrap_or_default();
+    tag.remove_all_chapters();
+    tag.remove_all_tables_of_contents();
+
+    let millis = |seconds: u64| u32::try_from(seconds * 1000).unwrap_or(u32::MAX);
+    let end = episode
+        .duration
+        .as_deref()
+        .and_then(parse_itunes_duration)
+        .map_or(u32::MAX, millis);
+
+    let mut elements = Vec::with_capacity(chapters.len());
+    for (index, chapter) in chapters.iter().enumerate() {
+        let element_id = format!("chp{}", index);
+        tag.add_frame(frame::Chapter {
+            element_id: element_id.clone(),
+            start_time: millis(chapter.start_time),
+            end_time: chapters
+                .get(index + 1)
+                .map_or(end, |next| millis(next.start_time)),
+            start_offset: NO_OFFSET,
+            end_offset: NO_OFFSET,
+            frames: vec![Frame::text("TIT2", chapter.title.as_str())],
+        });
+        elements.push(element_id);
+    }
+    tag.add_frame(TableOfContents {
+        element_id: TOC_ELEMENT_ID.to_string(),
+        top_level: true,
+        ordered: true,
+        elements,
+        frames: vec![],
+    });
+
+    save_tag(&tag, path)
+}
+
+fn is_mp3(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"))
+}
+
+fn save_tag(tag: &Tag, path: &Path) -> Result<bool, MetadataError> {
+    tag.write_to_path(path, Version::Id3v24)
+        .map_err(|e| MetadataError::TagWriteFailed {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use chrono::DateTime;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn episode() -> Episode {
+        Episode {
+            title: "Episode 1".to_string(),
+            description: Some("<p>Show notes</p>".to_string()),
+            pub_date: DateTime::parse_from_rfc2822("Mon, 15 Jan 2024 10:00:00 +0000").ok(),
+            guid: Some("ep1".to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep1.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: Some(7),
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    fn podcast() -> PodcastMetadata {
+        PodcastMetadata {
+            title: "Test Podcast".to_string(),
+            description: None,
+            link: None,
+            author: Some("Jane".to_string()),
+            image_url: None,
+            feed_url: "https://example.com/feed.xml".to_string(),
+            updated_at: None,
+            etag: None,
+            last_modified: None,
+            funding: vec![],
+        }
+    }
+
+    #[test]
+    fn tags_mp3_files_and_keeps_other_frames() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("episode.mp3");
+        let mut existing = Tag::new();
+        existing.set_title("Publisher title");
+        existing.set_album_artist("Publisher");
+        std::fs::write(&path, b"fake audio").unwrap();
+        existing.write_to_path(&path, Version::Id3v24).unwrap();
+
+        assert!(write_tags(&path, &episode(), &podcast()).unwrap());
+
+        let tag = Tag::read_from_path(&path).unwrap();
+        assert_eq!(tag.title(), Some("Episode 1"));
+        assert_eq!(tag.album(), Some("Test Podcast"));
+        assert_eq!(tag.artist(), Some("Jane"));
+        assert_eq!(tag.album_artist(), Some("Publisher"));
+        assert_eq!(tag.track(), Some(7));
+        assert_eq!(tag.genre(), Some("Podcast"));
+        assert_eq!(tag.date_recorded().map(|date| date.year), Some(2024));
+        assert_eq!(tag.comments().next().unwrap().text, "Show notes");
+        assert!(std::fs::read(&path).unwrap().ends_with(b"fake audio"));
+
+        let ogg = dir.path().join("episode.ogg");
+        std::fs::write(&ogg, b"fake audio").unwrap();
+        assert!(!write_tags(&ogg, &episode(), &podcast()).unwrap());
+        assert_eq!(std::fs::read(&ogg).unwrap(), b"fake audio");
+    }
+
+    #[test]
+    fn writes_chapter_frames_with_table_of_contents() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("episode.mp3");
+        std::fs::write(&path, b"fake audio").unwrap();
+        let mut episode = episode();
+        episode.duration = Some("05:00".to_string());
+        let chapters = [
+            Chapter {
+                start_time: 0,
+                title: "Intro".to_string(),
+            },
+            Chapter {
+                start_time: 90,
+                title: "Topic".to_string(),
+            },
+        ];
+
+        assert!(write_chapter_frames(&path, &episode, &chapters).unwrap());
+        // Writing again replaces the chapters instead of adding to them
+        assert!(write_chapter_frames(&path, &episode, &chapters).unwrap());
+
+        let tag = Tag::read_from_path(&path).unwrap();
+        let frames: Vec<_> = tag.chapters().collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].start_time, frames[0].end_time), (0, 90_000));
+        assert_eq!(
+            (frames[1].start_time, frames[1].end_time),
+            (90_000, 300_000)
+        );
+        assert_eq!(frames[1].frames[0].content().text(), Some("Topic"));
+
+        let toc: Vec<_> = tag.tables_of_contents().collect();
+        assert_eq!(toc.len(), 1);
+        assert_eq!(toc[0].elements, vec!["chp0", "chp1"]);
+    }
+}
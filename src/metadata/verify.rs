@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use super::episode::{is_episode_metadata_filename, library_files, read_episode_metadata};
+use super::xattr::read_file_attributes;
+use crate::episode::is_valid_audio_extension;
+use crate::error::MetadataError;
+use crate::hash::HashAlgorithm;
+
+/// Outcome of [`verify_library`]
+///
+/// Paths are the audio files in question, or the metadata files for
+/// `unhashed` and `unreadable`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of audio files matching their recorded hash
+    pub verified: usize,
+    /// Audio files whose content no longer matches the recorded hash
+    pub mismatched: Vec<PathBuf>,
+    /// Audio files named in episode metadata that do not exist, leaving
+    /// the metadata orphaned
+    pub missing: Vec<PathBuf>,
+    /// Episode metadata without a recorded hash, or with one of an unknown
+    /// algorithm, which cannot be verified
+    pub unhashed: Vec<PathBuf>,
+    /// Episode metadata files that cannot be read or parsed
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+    /// Whether no file is mismatched, missing or unreadable
+    pub fn is_intact(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.unreadable.is_empty()
+    }
+}
+
+/// Re-hash every downloaded episode and compare it to its episode metadata
+///
+/// Reads each audio file in full, so this takes as long as copying the
+/// library once. Meant to detect bit rot and files lost on the storage side.
+/// Audio files without episode metadata are verified against the hash in
+/// their extended attributes, if any (see [`write_file_attributes`]).
+/// Episodes whose audio a retention policy deleted are skipped.
+///
+/// [`write_file_attributes`]: super::write_file_attributes
+pub fn verify_library(output_dir: &Path) -> Result<VerifyReport, MetadataError> {
+    let mut report = VerifyReport::default();
+    let files = library_files(output_dir)?;
+    let mut described = HashSet::new();
+
+    let metadata_files = files.iter().filter(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(is_episode_metadata_filename)
+    });
+    for path in metadata_files {
+        let path = path.clone();
+        let Ok(metadata) = read_episode_metadata(&path) else {
+            report.unreadable.push(path);
+            continue;
+        };
+        if metadata.retired_at.is_some() {
+            continue;
+        }
+
+        let episode_dir = path.parent().unwrap_or(output_dir);
+        let audio_path = episode_dir.join(&metadata.audio_filename);
+        if !audio_path.is_file() {
+            report.missing.push(audio_path);
+            continue;
+        }
+        described.insert(audio_path.clone());
+
+        let Some((algorithm, _)) = metadata
+            .content_hash
+            .as_deref()
+            .and_then(HashAlgorithm::parse)
+        else {
+            report.unhashed.push(path);
+            continue;
+        };
+
+        if metadata.content_hash.as_deref() == Some(&hash_file(&audio_path, algorithm)?) {
+            report.verified += 1;
+        } else {
+            report.mismatched.push(audio_path);
+        }
+    }
+
+    let undescribed = files.iter().filter(|path| {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(is_valid_audio_extension)
+            && !described.contains(*path)
+    });
+    for audio_path in undescribed {
+        let Some(content_hash) = read_file_attributes(audio_path).content_hash else {
+            continue;
+        };
+        let Some((algorithm, _)) = HashAlgorithm::parse(&content_hash) else {
+            continue;
+        };
+
+        if content_hash == hash_file(audio_path, algorithm)? {
+            report.verified += 1;
+        } else {
+            report.mismatched.push(audio_path.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+pub(crate) fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<String, MetadataError> {
+    let read_failed = |e| MetadataError::ReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    };
+
+    let mut file = File::open(path).map_err(read_failed)?;
+    let mut hasher = algorithm.hasher();
+    std::io::copy(&mut file, &mut hasher).map_err(read_failed)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    /// SHA-256 of "foo"
+    const HASH: &str = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+    fn write_episode(dir: &Path, name: &str, content: Option<&[u8]>, content_hash: Option<&str>) {
+        let episode = Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(name.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let audio_filename = format!("{}.mp3", name);
+        if let Some(content) = content {
+            std::fs::write(dir.join(&audio_filename), content).unwrap();
+        }
+        write_episode_metadata(
+            &episode,
+            &audio_filename,
+            content_hash.map(String::from),
+            &dir.join(format!("{}.json", name)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn reports_damaged_and_missing_episodes() {
+        let dir = tempdir().unwrap();
+        let season = dir.path().join("Season 01");
+        std::fs::create_dir(&season).unwrap();
+        let hash = format!("sha256:{}", HASH);
+
+        let mut blake3 = HashAlgorithm::Blake3.hasher();
+        blake3.update(b"foo");
+
+        write_episode(dir.path(), "intact", Some(b"foo"), Some(&hash));
+        write_episode(dir.path(), "blake3", Some(b"foo"), Some(&blake3.finalize()));
+        write_episode(&season, "rotten", Some(b"fob"), Some(&hash));
+        write_episode(dir.path(), "gone", None, Some(&hash));
+        write_episode(dir.path(), "unhashed", Some(b"foo"), None);
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let report = verify_library(dir.path()).unwrap();
+
+        assert_eq!(report.verified, 2);
+        assert_eq!(report.mismatched, vec![season.join("rotten.mp3")]);
+        assert_eq!(report.missing, vec![dir.path().join("gone.mp3")]);
+        assert_eq!(report.unhashed, vec![dir.path().join("unhashed.json")]);
+        assert_eq!(report.unreadable, vec![dir.path().join("broken.json")]);
+        assert!(!report.is_intact());
+    }
+}
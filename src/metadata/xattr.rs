@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use crate::error::MetadataError;
+use crate::hash::HashAlgorithm;
+
+/// Prefix of the extended attributes podpull stores on audio files
+///
+/// The content hash is stored as hex digest under the algorithm's name
+/// (`user.podpull.sha256`), the episode GUID as `user.podpull.guid`.
+pub const XATTR_PREFIX: &str = "user.podpull.";
+
+/// What the extended attributes of an audio file record
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileAttributes {
+    /// Content hash as `<algorithm>:<hex>`, like in episode metadata
+    pub content_hash: Option<String>,
+    pub guid: Option<String>,
+}
+
+/// Store an audio file's content hash and episode GUID as extended attributes
+///
+/// Attributes of other hash algorithms are removed. Returns false where the
+/// platform or filesystem does not support extended attributes.
+pub fn write_file_attributes(
+    path: &Path,
+    content_hash: &str,
+    guid: Option<&str>,
+) -> Result<bool, MetadataError> {
+    #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+    {
+        use rustix::fs::{XattrFlags, removexattr, setxattr};
+        use rustix::io::Errno;
+
+        let write = |name: &str, value: &str| match setxattr(
+            path,
+            name,
+            value.as_bytes(),
+            XattrFlags::empty(),
+        ) {
+            Ok(()) => Ok(true),
+            Err(Errno::NOTSUP) => Ok(false),
+            Err(e) => Err(MetadataError::WriteFailed {
+                path: path.to_path_buf(),
+                source: e.into(),
+            }),
+        };
+
+        let Some((algorithm, digest)) = HashAlgorithm::parse(content_hash) else {
+            return Ok(false);
+        };
+        if !write(&hash_attribute(algorithm), digest)? {
+            return Ok(false);
+        }
+        for other in HashAlgorithm::ALL.into_iter().filter(|a| *a != algorithm) {
+            let _ = removexattr(path, hash_attribute(other).as_str());
+        }
+        match guid {
+            Some(guid) => write(&guid_attribute(), guid),
+            None => {
+                let _ = removexattr(path, guid_attribute().as_str());
+                Ok(true)
+            }
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+    {
+        let _ = (path, content_hash, guid);
+        Ok(false)
+    }
+}
+
+/// Read the content hash and GUID stored by [`write_file_attributes`]
+///
+/// Attributes that are missing or unreadable are None.
+pub fn read_file_attributes(path: &Path) -> FileAttributes {
+    FileAttributes {
+        content_hash: HashAlgorithm::ALL.into_iter().find_map(|algorithm| {
+            read_attribute(path, &hash_attribute(algorithm))
+                .map(|digest| format!("{}:{}", algorithm.prefix(), digest))
+        }),
+        guid: read_attribute(path, &guid_attribute()),
+    }
+}
+
+fn hash_attribute(algorithm: HashAlgorithm) -> String {
+    format!("{}{}", XATTR_PREFIX, algorithm.prefix())
+}
+
+fn guid_attribute() -> String {
+    format!("{}guid", XATTR_PREFIX)
+}
+
+fn read_attribute(path: &Path, name: &str) -> Option<String> {
+    #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+    {
+        // Large enough for SHA-512 digests and any sensible GUID
+        let mut buffer = [0u8; 1024];
+        let len = rustix::fs::getxattr(path, name, &mut buffer[..]).ok()?;
+        String::from_utf8(buffer[..len].to_vec()).ok()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+    {
+        let _ = (path, name);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn stores_hash_and_guid_on_the_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ep.mp3");
+        std::fs::write(&path, b"audio").unwrap();
+        assert_eq!(read_file_attributes(&path), FileAttributes::default());
+
+        if !write_file_attributes(&path, "sha256:abc", Some("guid-1")).unwrap() {
+            // The temporary directory's filesystem lacks extended attributes
+            return;
+        }
+        write_file_attributes(&path, "blake3:def", Some("guid-2")).unwrap();
+
+        assert_eq!(
+            read_file_attributes(&path),
+            FileAttributes {
+                content_hash: Some("blake3:def".to_string()),
+                guid: Some("guid-2".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The stable, high-level API for embedding podpull
+//!
+//! `use podpull::prelude::*;` brings in everything needed to sync single
+//! feeds or whole libraries, configure the HTTP client, follow progress and
+//! handle errors. These items follow semantic versioning; the modules they
+//! live in are implementation details and may be reorganized in minor
+//! releases.
+
+pub use crate::episode::{EnclosurePreference, FilenameTemplate, OutputLayout, VideoPolicy};
+pub use crate::error::{
+    DownloadError, FeedError, MetadataError, StateError, SubscriptionError, SyncError, UnitError,
+};
+pub use crate::hash::HashAlgorithm;
+pub use crate::http::{
+    AddressFamily, Credentials, HttpClient, HttpVersion, ReqwestClient, ReqwestClientBuilder,
+};
+pub use crate::metadata::{EpisodeMetadata, PodcastMetadata, VerifyReport, verify_library};
+pub use crate::progress::{
+    NoopReporter, ProgressEvent, ProgressReporter, SharedProgressReporter, StatusReporter,
+    SyncStatus,
+};
+pub use crate::retry::RetryPolicy;
+pub use crate::settings::PodcastSettings;
+pub use crate::subscriptions::{
+    DirTemplate, LibraryRename, LibrarySync, PodcastEntry, Subscription, SubscriptionResult,
+    plan_library_renames, rename_library_dir, scan_library, sync_podcasts, sync_subscriptions,
+};
+pub use crate::sync::{SyncOptions, SyncResult, sync_podcast};
+pub use crate::units::{ByteSize, HumanDuration};
@@ -0,0 +1,400 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+mod speech;
+mod status;
+
+use std::sync::Arc;
+
+use crate::sync::DryRunReport;
+
+pub use speech::{SpeechCommand, SpeechReporter, announcement};
+pub use status::{SlotStatus, StatusReporter, SyncPhase, SyncStatus};
+
+/// Events emitted during podcast synchronization for progress reporting
+#[derive(Debug, Clone)]
+pub enum ProgressEvent {
+    /// Feed is being fetched from URL (network request)
+    FetchingFeed { url: String },
+
+    /// Feed is being parsed (XML processing)
+    ParsingFeed {
+        /// Source being parsed (URL or file path)
+        source: String,
+    },
+
+    /// The feed announced a new location via `itunes:new-feed-url` and is
+    /// being fetched from there instead
+    FeedMoved { old_url: String, new_url: String },
+
+    /// The feed's title differs from the one recorded in `podcast.json`.
+    /// Syncing continues into the existing output directory.
+    TitleChanged {
+        old_title: String,
+        new_title: String,
+    },
+
+    /// Downloads were repeatedly refused (`403 Forbidden`), so the feed was
+    /// fetched again for fresh enclosure URLs
+    FeedRefreshed { url: String },
+
+    /// The server confirmed the feed is unchanged since the last complete
+    /// sync. This ends the sync; no `SyncCompleted` follows.
+    FeedNotModified { url: String },
+
+    /// Output directory is being scanned for existing episodes
+    ScanningDirectory {
+        /// Number of files scanned so far
+        files_scanned: usize,
+        /// Total number of files to scan
+        total_files: usize,
+    },
+
+    /// Sync plan is ready (feed parsed, directory scanned, plan created)
+    SyncPlanReady {
+        podcast_title: String,
+        total_episodes: usize,
+        /// All episodes not yet downloaded
+        new_episodes: usize,
+        /// Episodes to download after limit applied (may equal new_episodes)
+        to_download: usize,
+    },
+
+    /// A download is starting
+    DownloadStarting {
+        /// Identifies the download slot (0 to max_concurrent-1)
+        download_id: usize,
+        episode_title: String,
+        /// Index of this episode in the download queue
+        episode_index: usize,
+        /// Total number of episodes to download
+        total_to_download: usize,
+        /// Expected content length in bytes, if known
+        content_length: Option<u64>,
+    },
+
+    /// Download progress update
+    DownloadProgress {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        bytes_downloaded: u64,
+        total_bytes: Option<u64>,
+    },
+
+    /// A download completed successfully
+    DownloadCompleted {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        bytes_downloaded: u64,
+    },
+
+    /// A download failed with a transient error and will be retried after `delay`
+    DownloadRetrying {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        /// Number of this retry (1 for the first)
+        attempt: u32,
+        max_retries: u32,
+        delay: std::time::Duration,
+        error: String,
+    },
+
+    /// The server throttled a download (`429`, `503`) and asked to wait
+    /// `delay` via `Retry-After`; sent instead of `DownloadRetrying`
+    DownloadThrottled {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        /// Number of this retry (1 for the first)
+        attempt: u32,
+        max_retries: u32,
+        delay: std::time::Duration,
+    },
+
+    /// A download failed and will be tried again once the other episodes
+    /// are done, see
+    /// [`SyncOptions::requeue_failed`](crate::sync::SyncOptions::requeue_failed)
+    DownloadRequeued {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        error: String,
+    },
+
+    /// A download failed
+    DownloadFailed {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        error: String,
+    },
+
+    /// Download is being finalized (renamed from .partial)
+    Finalizing {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+    },
+
+    /// Hashing completed for a download
+    HashingCompleted {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        hash: String,
+    },
+
+    /// A downloaded file is far smaller or larger than the enclosure length
+    /// the feed declared; the download is kept
+    SizeMismatch {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        declared: u64,
+        received: u64,
+    },
+
+    /// An episode was skipped for exceeding
+    /// [`SyncOptions::max_episode_bytes`](crate::sync::SyncOptions::max_episode_bytes),
+    /// by its declared size or while downloading
+    EpisodeTooLarge {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+        limit: u64,
+    },
+
+    /// An episode was skipped because its enclosure is a video, see
+    /// [`SyncOptions::video_policy`](crate::sync::SyncOptions::video_policy)
+    VideoSkipped {
+        /// Identifies the download slot
+        download_id: usize,
+        episode_title: String,
+    },
+
+    /// Partial files were cleaned up during directory scan
+    PartialFilesCleanedUp { count: usize },
+
+    /// Audio files downloaded by other tools were matched to episodes, see
+    /// [`SyncOptions::adopt_existing`](crate::sync::SyncOptions::adopt_existing)
+    DownloadsAdopted { count: usize },
+
+    /// Episodes that failed to download recently are left out until their
+    /// cool-down passes, see
+    /// [`SyncOptions::failure_cooldown`](crate::sync::SyncOptions::failure_cooldown)
+    EpisodesBackedOff { count: usize },
+
+    /// A dry run planned the sync, see
+    /// [`SyncOptions::dry_run`](crate::sync::SyncOptions::dry_run). This
+    /// ends the sync; no `SyncCompleted` follows.
+    DryRunPlanned { report: DryRunReport },
+
+    /// A downloaded episode no longer in the feed was deleted or archived,
+    /// see [`SyncOptions::prune`](crate::sync::SyncOptions::prune)
+    EpisodePruned {
+        episode_title: String,
+        /// Whether its files were moved to the archive instead of deleted
+        archived: bool,
+    },
+
+    /// The audio of a downloaded episode was deleted for exceeding the
+    /// retention limits, see
+    /// [`SyncOptions::retention`](crate::sync::SyncOptions::retention)
+    EpisodeRetired { episode_title: String },
+
+    /// Sync operation completed
+    SyncCompleted {
+        downloaded_count: usize,
+        /// Episodes already present in output directory
+        existing_count: usize,
+        /// New episodes not downloaded due to --limit
+        limited_count: usize,
+        /// New episodes held back because they are scheduled for release
+        /// in the future
+        deferred_count: usize,
+        failed_count: usize,
+    },
+}
+
+/// Trait for reporting progress events during synchronization.
+///
+/// Implementations can use this to display progress bars, log messages,
+/// or collect statistics.
+pub trait ProgressReporter: Send + Sync {
+    /// Report a progress event
+    fn report(&self, event: ProgressEvent);
+}
+
+/// A shared reference to a progress reporter
+pub type SharedProgressReporter = Arc<dyn ProgressReporter>;
+
+/// A no-op progress reporter that silently ignores all events.
+/// Useful for tests or quiet mode.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopReporter;
+
+impl ProgressReporter for NoopReporter {
+    fn report(&self, _event: ProgressEvent) {
+        // Intentionally empty
+    }
+}
+
+impl NoopReporter {
+    /// Create a new NoopReporter wrapped in an Arc
+    pub fn shared() -> SharedProgressReporter {
+        Arc::new(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noop_reporter_handles_all_events() {
+        let reporter = NoopReporter;
+
+        reporter.report(ProgressEvent::FetchingFeed {
+            url: "https://example.com/feed.xml".to_string(),
+        });
+
+        reporter.report(ProgressEvent::ParsingFeed {
+            source: "https://example.com/feed.xml".to_string(),
+        });
+
+        reporter.report(ProgressEvent::FeedMoved {
+            old_url: "https://example.com/feed.xml".to_string(),
+            new_url: "https://new.example.com/feed.xml".to_string(),
+        });
+
+        reporter.report(ProgressEvent::TitleChanged {
+            old_title: "Old Show".to_string(),
+            new_title: "New Show".to_string(),
+        });
+
+        reporter.report(ProgressEvent::FeedRefreshed {
+            url: "https://new.example.com/feed.xml".to_string(),
+        });
+
+        reporter.report(ProgressEvent::FeedNotModified {
+            url: "https://new.example.com/feed.xml".to_string(),
+        });
+
+        reporter.report(ProgressEvent::ScanningDirectory {
+            files_scanned: 5,
+            total_files: 10,
+        });
+
+        reporter.report(ProgressEvent::SyncPlanReady {
+            podcast_title: "Test Podcast".to_string(),
+            total_episodes: 10,
+            new_episodes: 5,
+            to_download: 3,
+        });
+
+        reporter.report(ProgressEvent::DownloadStarting {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            episode_index: 0,
+            total_to_download: 5,
+            content_length: Some(1024),
+        });
+
+        reporter.report(ProgressEvent::DownloadProgress {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            bytes_downloaded: 512,
+            total_bytes: Some(1024),
+        });
+
+        reporter.report(ProgressEvent::DownloadCompleted {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            bytes_downloaded: 1024,
+        });
+
+        reporter.report(ProgressEvent::DownloadRetrying {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            attempt: 1,
+            max_retries: 3,
+            delay: std::time::Duration::from_secs(1),
+            error: "HTTP error 503".to_string(),
+        });
+
+        reporter.report(ProgressEvent::DownloadThrottled {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            attempt: 2,
+            max_retries: 3,
+            delay: std::time::Duration::from_secs(60),
+        });
+
+        reporter.report(ProgressEvent::DownloadRequeued {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            error: "Connection reset".to_string(),
+        });
+
+        reporter.report(ProgressEvent::DownloadFailed {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            error: "Connection timeout".to_string(),
+        });
+
+        reporter.report(ProgressEvent::Finalizing {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+        });
+
+        reporter.report(ProgressEvent::HashingCompleted {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            hash: "sha256:abc123".to_string(),
+        });
+
+        reporter.report(ProgressEvent::SizeMismatch {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            declared: 50_000_000,
+            received: 1_000,
+        });
+
+        reporter.report(ProgressEvent::EpisodeTooLarge {
+            download_id: 2,
+            episode_title: "Episode 3 (Video)".to_string(),
+            limit: 500_000_000,
+        });
+
+        reporter.report(ProgressEvent::VideoSkipped {
+            download_id: 2,
+            episode_title: "Episode 3 (Video)".to_string(),
+        });
+
+        reporter.report(ProgressEvent::PartialFilesCleanedUp { count: 2 });
+        reporter.report(ProgressEvent::DownloadsAdopted { count: 3 });
+        reporter.report(ProgressEvent::EpisodesBackedOff { count: 1 });
+        reporter.report(ProgressEvent::EpisodePruned {
+            episode_title: "Episode 0".to_string(),
+            archived: true,
+        });
+        reporter.report(ProgressEvent::EpisodeRetired {
+            episode_title: "Episode 0".to_string(),
+        });
+        reporter.report(ProgressEvent::DryRunPlanned {
+            report: DryRunReport::default(),
+        });
+
+        reporter.report(ProgressEvent::SyncCompleted {
+            downloaded_count: 4,
+            existing_count: 5,
+            limited_count: 2,
+            deferred_count: 0,
+            failed_count: 1,
+        });
+    }
+}
@@ -0,0 +1,246 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+
+use super::{ProgressEvent, ProgressReporter, SharedProgressReporter};
+
+/// External text-to-speech command used for spoken announcements
+///
+/// The announcement text is appended as the final argument, which matches
+/// the calling convention of `espeak`, `spd-say` and macOS `say`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeechCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl SpeechCommand {
+    /// Parse a command line like `"espeak -s 150"` into program and arguments
+    ///
+    /// Returns `None` if the command line is empty.
+    pub fn parse(command_line: &str) -> Option<Self> {
+        let mut parts = command_line.split_whitespace().map(String::from);
+        let program = parts.next()?;
+
+        Some(Self {
+            program,
+            args: parts.collect(),
+        })
+    }
+}
+
+/// Progress reporter that speaks short summaries at key milestones
+///
+/// Wraps another reporter and forwards every event to it unchanged. Spoken
+/// announcements are limited to the start of a sync, the number of new
+/// episodes, and the final summary, so screen reader users get the essentials
+/// without a stream of per-chunk updates.
+///
+/// Announcements are spoken one after another on a background thread.
+/// Dropping the reporter waits for pending announcements to finish.
+pub struct SpeechReporter {
+    inner: SharedProgressReporter,
+    sender: Option<Sender<String>>,
+    worker: Option<JoinHandle<()>>,
+    /// Whether the start of the current sync has already been announced
+    started: AtomicBool,
+}
+
+impl SpeechReporter {
+    /// Create a reporter speaking through `command` and forwarding to `inner`
+    pub fn new(command: SpeechCommand, inner: SharedProgressReporter) -> Self {
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        let worker = std::thread::spawn(move || {
+            for text in receiver {
+                // A missing or failing TTS command must never break the sync
+                let _ = Command::new(&command.program)
+                    .args(&command.args)
+                    .arg(&text)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+            }
+        });
+
+        Self {
+            inner,
+            sender: Some(sender),
+            worker: Some(worker),
+            started: AtomicBool::new(false),
+        }
+    }
+}
+
+impl ProgressReporter for SpeechReporter {
+    fn report(&self, event: ProgressEvent) {
+        // URL feeds emit both fetching and parsing, local files only parsing:
+        // announce whichever comes first
+        let is_start = matches!(
+            event,
+            ProgressEvent::FetchingFeed { .. } | ProgressEvent::ParsingFeed { .. }
+        );
+        let already_started = if is_start {
+            self.started.swap(true, Ordering::SeqCst)
+        } else {
+            if matches!(
+                event,
+                ProgressEvent::SyncCompleted { .. }
+                    | ProgressEvent::FeedNotModified { .. }
+                    | ProgressEvent::DryRunPlanned { .. }
+            ) {
+                self.started.store(false, Ordering::SeqCst);
+            }
+            false
+        };
+
+        if !already_started
+            && let Some(text) = announcement(&event)
+            && let Some(sender) = &self.sender
+        {
+            let _ = sender.send(text);
+        }
+
+        self.inner.report(event);
+    }
+}
+
+impl Drop for SpeechReporter {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker loop once the queue is drained
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Build the spoken text for an event, if it is a milestone worth announcing
+pub fn announcement(event: &ProgressEvent) -> Option<String> {
+    match event {
+        ProgressEvent::FetchingFeed { .. } | ProgressEvent::ParsingFeed { .. } => {
+            Some("Starting podcast sync.".to_string())
+        }
+
+        ProgressEvent::FeedMoved { .. } => Some("The feed has moved to a new address.".to_string()),
+        ProgressEvent::FeedNotModified { .. } => {
+            Some("The feed has not changed. Nothing to download.".to_string())
+        }
+
+        ProgressEvent::SyncPlanReady {
+            podcast_title,
+            new_episodes,
+            to_download,
+            ..
+        } => {
+            let mut text = format!(
+                "{}: {}.",
+                podcast_title,
+                count_phrase(*new_episodes, "new episode", "new episodes")
+            );
+            if to_download != new_episodes {
+                text.push_str(&format!(" Downloading {}.", to_download));
+            }
+            Some(text)
+        }
+
+        ProgressEvent::SyncCompleted {
+            downloaded_count,
+            failed_count,
+            ..
+        } => {
+            let mut text = format!(
+                "Sync complete, {} downloaded.",
+                count_phrase(*downloaded_count, "episode", "episodes")
+            );
+            if *failed_count > 0 {
+                text.push_str(&format!(" {} failed.", failed_count));
+            }
+            Some(text)
+        }
+
+        _ => None,
+    }
+}
+
+fn count_phrase(count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        0 => format!("no {}", plural),
+        1 => format!("1 {}", singular),
+        n => format!("{} {}", n, plural),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_program_and_arguments() {
+        let command = SpeechCommand::parse("espeak -s 150").unwrap();
+        assert_eq!(command.program, "espeak");
+        assert_eq!(command.args, vec!["-s", "150"]);
+    }
+
+    #[test]
+    fn parse_rejects_empty_command() {
+        assert!(SpeechCommand::parse("   ").is_none());
+    }
+
+    #[test]
+    fn announces_new_episode_count() {
+        let text = announcement(&ProgressEvent::SyncPlanReady {
+            podcast_title: "Test Podcast".to_string(),
+            total_episodes: 10,
+            new_episodes: 1,
+            to_download: 1,
+        });
+        assert_eq!(text.as_deref(), Some("Test Podcast: 1 new episode."));
+    }
+
+    #[test]
+    fn announces_limited_downloads() {
+        let text = announcement(&ProgressEvent::SyncPlanReady {
+            podcast_title: "Test Podcast".to_string(),
+            total_episodes: 10,
+            new_episodes: 5,
+            to_download: 2,
+        });
+        assert_eq!(
+            text.as_deref(),
+            Some("Test Podcast: 5 new episodes. Downloading 2.")
+        );
+    }
+
+    #[test]
+    fn announces_completion_with_failures() {
+        let text = announcement(&ProgressEvent::SyncCompleted {
+            downloaded_count: 0,
+            existing_count: 3,
+            limited_count: 0,
+            deferred_count: 0,
+            failed_count: 2,
+        });
+        assert_eq!(
+            text.as_deref(),
+            Some("Sync complete, no episodes downloaded. 2 failed.")
+        );
+    }
+
+    #[test]
+    fn ignores_per_chunk_progress() {
+        let text = announcement(&ProgressEvent::DownloadProgress {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            bytes_downloaded: 512,
+            total_bytes: Some(1024),
+        });
+        assert!(text.is_none());
+    }
+}
@@ -0,0 +1,305 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Serialize;
+
+use super::{ProgressEvent, ProgressReporter, SharedProgressReporter};
+
+/// Coarse phase of a synchronization run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    /// No sync has started yet
+    #[default]
+    Idle,
+    FetchingFeed,
+    ParsingFeed,
+    ScanningDirectory,
+    Downloading,
+    Completed,
+}
+
+/// Progress of a single active download slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotStatus {
+    pub download_id: usize,
+    pub episode_title: String,
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>,
+    /// The download finished and is being renamed and hashed
+    pub finalizing: bool,
+}
+
+/// Point-in-time snapshot of a synchronization run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    pub podcast_title: Option<String>,
+    pub total_episodes: usize,
+    pub new_episodes: usize,
+    pub to_download: usize,
+    /// Episodes waiting for a free download slot
+    pub queued: usize,
+    /// Active downloads, ordered by slot
+    pub active: Vec<SlotStatus>,
+    pub downloaded: usize,
+    pub failed: usize,
+    /// Bytes of all completed downloads
+    pub bytes_downloaded: u64,
+}
+
+/// Progress reporter that keeps a pollable [`SyncStatus`] snapshot
+///
+/// Wraps another reporter and forwards every event to it unchanged. Useful
+/// for dashboards that poll for state instead of consuming the event stream.
+pub struct StatusReporter {
+    inner: SharedProgressReporter,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    status: SyncStatus,
+    started: usize,
+    slots: BTreeMap<usize, SlotStatus>,
+}
+
+impl StatusReporter {
+    /// Create a reporter tracking status and forwarding to `inner`
+    pub fn new(inner: SharedProgressReporter) -> Self {
+        Self {
+            inner,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Current status of the sync
+    pub fn snapshot(&self) -> SyncStatus {
+        let state = self.lock();
+        SyncStatus {
+            queued: state.status.to_download.saturating_sub(state.started),
+            active: state.slots.values().cloned().collect(),
+            ..state.status.clone()
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Status is plain data, so a panicked writer cannot leave it inconsistent
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, event: &ProgressEvent) {
+        let mut state = self.lock();
+
+        match event {
+            ProgressEvent::FetchingFeed { .. } => {
+                if state.status.phase == SyncPhase::Completed {
+                    *state = State::default();
+                }
+                state.status.phase = SyncPhase::FetchingFeed;
+            }
+            ProgressEvent::ParsingFeed { .. } => {
+                if state.status.phase == SyncPhase::Completed {
+                    *state = State::default();
+                }
+                state.status.phase = SyncPhase::ParsingFeed;
+            }
+            ProgressEvent::FeedMoved { .. }
+            | ProgressEvent::TitleChanged { .. }
+            | ProgressEvent::FeedRefreshed { .. } => {}
+            ProgressEvent::FeedNotModified { .. } | ProgressEvent::DryRunPlanned { .. } => {
+                state.status.phase = SyncPhase::Completed;
+            }
+            ProgressEvent::ScanningDirectory { .. } => {
+                state.status.phase = SyncPhase::ScanningDirectory;
+            }
+            ProgressEvent::SyncPlanReady {
+                podcast_title,
+                total_episodes,
+                new_episodes,
+                to_download,
+            } => {
+                state.status.phase = SyncPhase::Downloading;
+                state.status.podcast_title = Some(podcast_title.clone());
+                state.status.total_episodes = *total_episodes;
+                state.status.new_episodes = *new_episodes;
+                state.status.to_download = *to_download;
+            }
+            ProgressEvent::DownloadStarting {
+                download_id,
+                episode_title,
+                content_length,
+                ..
+            } => {
+                state.started += 1;
+                state.slots.insert(
+                    *download_id,
+                    SlotStatus {
+                        download_id: *download_id,
+                        episode_title: episode_title.clone(),
+                        bytes_downloaded: 0,
+                        total_bytes: *content_length,
+                        finalizing: false,
+                    },
+                );
+            }
+            ProgressEvent::DownloadProgress {
+                download_id,
+                bytes_downloaded,
+                total_bytes,
+                ..
+            } => {
+                if let Some(slot) = state.slots.get_mut(download_id) {
+                    slot.bytes_downloaded = *bytes_downloaded;
+                    slot.total_bytes = total_bytes.or(slot.total_bytes);
+                }
+            }
+            ProgressEvent::Finalizing { download_id, .. } => {
+                if let Some(slot) = state.slots.get_mut(download_id) {
+                    slot.finalizing = true;
+                }
+            }
+            ProgressEvent::HashingCompleted { .. } | ProgressEvent::SizeMismatch { .. } => {}
+            ProgressEvent::DownloadCompleted {
+                download_id,
+                bytes_downloaded,
+                ..
+            } => {
+                state.slots.remove(download_id);
+                state.status.downloaded += 1;
+                state.status.bytes_downloaded += bytes_downloaded;
+            }
+            ProgressEvent::DownloadRetrying { download_id, .. }
+            | ProgressEvent::DownloadThrottled { download_id, .. }
+            | ProgressEvent::DownloadRequeued { download_id, .. } => {
+                // The retry announces itself with another `DownloadStarting`
+                if state.slots.remove(download_id).is_some() {
+                    state.started -= 1;
+                }
+            }
+            ProgressEvent::DownloadFailed { download_id, .. } => {
+                state.slots.remove(download_id);
+                state.status.failed += 1;
+            }
+            ProgressEvent::EpisodeTooLarge { download_id, .. }
+            | ProgressEvent::VideoSkipped { download_id, .. } => {
+                state.slots.remove(download_id);
+            }
+            ProgressEvent::PartialFilesCleanedUp { .. }
+            | ProgressEvent::DownloadsAdopted { .. }
+            | ProgressEvent::EpisodesBackedOff { .. }
+            | ProgressEvent::EpisodePruned { .. }
+            | ProgressEvent::EpisodeRetired { .. } => {}
+            ProgressEvent::SyncCompleted {
+                downloaded_count,
+                failed_count,
+                ..
+            } => {
+                state.slots.clear();
+                state.status.phase = SyncPhase::Completed;
+                state.status.downloaded = *downloaded_count;
+                state.status.failed = *failed_count;
+            }
+        }
+    }
+}
+
+impl ProgressReporter for StatusReporter {
+    fn report(&self, event: ProgressEvent) {
+        self.update(&event);
+        self.inner.report(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoopReporter;
+
+    fn plan_ready(to_download: usize) -> ProgressEvent {
+        ProgressEvent::SyncPlanReady {
+            podcast_title: "Show".to_string(),
+            total_episodes: 10,
+            new_episodes: 4,
+            to_download,
+        }
+    }
+
+    fn starting(download_id: usize, title: &str) -> ProgressEvent {
+        ProgressEvent::DownloadStarting {
+            download_id,
+            episode_title: title.to_string(),
+            episode_index: 0,
+            total_to_download: 3,
+            content_length: Some(100),
+        }
+    }
+
+    #[test]
+    fn snapshot_tracks_active_slots_and_queue() {
+        let reporter = StatusReporter::new(NoopReporter::shared());
+        assert_eq!(reporter.snapshot().phase, SyncPhase::Idle);
+
+        reporter.report(plan_ready(3));
+        reporter.report(starting(0, "Episode 1"));
+        reporter.report(starting(1, "Episode 2"));
+        reporter.report(ProgressEvent::DownloadProgress {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            bytes_downloaded: 40,
+            total_bytes: Some(100),
+        });
+
+        let status = reporter.snapshot();
+        assert_eq!(status.phase, SyncPhase::Downloading);
+        assert_eq!(status.podcast_title.as_deref(), Some("Show"));
+        assert_eq!(status.queued, 1);
+        assert_eq!(status.active.len(), 2);
+        assert_eq!(status.active[1].bytes_downloaded, 40);
+
+        reporter.report(ProgressEvent::DownloadCompleted {
+            download_id: 0,
+            episode_title: "Episode 1".to_string(),
+            bytes_downloaded: 100,
+        });
+        reporter.report(ProgressEvent::DownloadFailed {
+            download_id: 1,
+            episode_title: "Episode 2".to_string(),
+            error: "timeout".to_string(),
+        });
+
+        let status = reporter.snapshot();
+        assert!(status.active.is_empty());
+        assert_eq!(status.downloaded, 1);
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.bytes_downloaded, 100);
+    }
+
+    #[test]
+    fn new_sync_resets_completed_status() {
+        let reporter = StatusReporter::new(NoopReporter::shared());
+
+        reporter.report(plan_ready(1));
+        reporter.report(ProgressEvent::SyncCompleted {
+            downloaded_count: 1,
+            existing_count: 0,
+            limited_count: 0,
+            deferred_count: 0,
+            failed_count: 0,
+        });
+        assert_eq!(reporter.snapshot().phase, SyncPhase::Completed);
+
+        reporter.report(ProgressEvent::FetchingFeed {
+            url: "https://example.com/feed.xml".to_string(),
+        });
+
+        let status = reporter.snapshot();
+        assert_eq!(status.phase, SyncPhase::FetchingFeed);
+        assert_eq!(status.downloaded, 0);
+        assert_eq!(status.podcast_title, None);
+    }
+}
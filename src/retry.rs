@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Retrying of transient network failures
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How often and how patiently failed requests are retried
+///
+/// Only transient failures (connection errors, timeouts, `5xx` and `429`
+/// responses) are retried. The delay doubles with every retry, starting at
+/// `initial_delay` and capped at `max_delay`, and is randomized to between
+/// half and all of that so concurrent downloads do not retry in lockstep.
+/// Servers throttling with `429` or `503` and a `Retry-After` header are
+/// instead waited for as long as they ask, up to `max_retry_after`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Longest wait honored from a `Retry-After` header
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the given retry (1 for the first retry)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay);
+
+        let half = delay / 2;
+        let jitter_range = (delay - half).as_millis() as u64;
+        if jitter_range == 0 {
+            return delay;
+        }
+        half + Duration::from_millis(random() % (jitter_range + 1))
+    }
+}
+
+/// Random number from the standard library's randomly seeded hasher
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Whether a reqwest error is worth retrying
+pub(crate) fn is_transient(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => is_transient_status(status.as_u16()),
+        None => error.is_timeout() || error.is_connect() || error.is_request() || error.is_body(),
+    }
+}
+
+/// Parse a `Retry-After` header, given in seconds or as an HTTP date
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // Dates in the past mean the server is ready again
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether an HTTP status signals a temporary server-side problem
+pub(crate) fn is_transient_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_exponentially_with_jitter() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+
+        for (retry, full) in [(1, 100), (2, 200), (3, 400), (4, 800), (5, 1000), (9, 1000)] {
+            let delay = policy.delay(retry).as_millis();
+            assert!(
+                (full / 2..=full).contains(&delay),
+                "retry {retry}: {delay}ms not within {full}ms/2..={full}ms"
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_transient_statuses() {
+        assert!(is_transient_status(503));
+        assert!(is_transient_status(429));
+        assert!(!is_transient_status(404));
+        assert!(!is_transient_status(200));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let wait = parse_retry_after(&soon).unwrap();
+        assert!(wait > Duration::from_secs(80) && wait <= Duration::from_secs(90));
+
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
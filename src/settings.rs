@@ -0,0 +1,372 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Per-podcast settings stored in the output directory
+
+use std::path::Path;
+
+use crate::episode::{FilenameTemplate, VideoPolicy};
+use crate::error::SettingsError;
+use crate::http::Credentials;
+use crate::state::PrunePolicy;
+use crate::sync::SyncOptions;
+use crate::units::ByteSize;
+
+/// Name of the settings file in the output directory
+///
+/// Being hidden, it is never mistaken for an episode.
+pub const SETTINGS_FILENAME: &str = ".podpull.toml";
+
+/// Overrides of [`SyncOptions`] for a single podcast
+///
+/// Read from [`SETTINGS_FILENAME`], a TOML file of top-level keys:
+///
+/// ```toml
+/// filename_template = "{date} {title}"
+/// ascii_filenames = true
+/// limit = 20
+/// max_episode_size = "500M"
+/// languages = ["de", "en"]
+/// video = "skip"                  # "download", "skip" or "fail"
+/// prune = "delete"                # "keep", "delete" or "archive"
+/// credentials_env = "SHOW_LOGIN"  # variable holding "user:password"
+/// ```
+///
+/// Only this subset of TOML is understood: strings, integers, booleans and
+/// arrays of strings, each on a single line. Tables are rejected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PodcastSettings {
+    pub filename_template: Option<FilenameTemplate>,
+    pub ascii_filenames: Option<bool>,
+    pub limit: Option<usize>,
+    pub max_episode_size: Option<ByteSize>,
+    pub languages: Option<Vec<String>>,
+    pub video: Option<VideoPolicy>,
+    pub prune: Option<PrunePolicy>,
+    /// Environment variable holding the feed's credentials as
+    /// `user:password`, keeping them out of the settings file
+    pub credentials_env: Option<String>,
+}
+
+impl PodcastSettings {
+    /// Read the settings file of an output directory, if there is one
+    pub fn read(output_dir: &Path) -> Result<Option<Self>, SettingsError> {
+        let path = output_dir.join(SETTINGS_FILENAME);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(SettingsError::ReadFailed { path, source: e }),
+        };
+
+        Self::parse(&text)
+            .map(Some)
+            .map_err(|(line, message)| SettingsError::Invalid {
+                path,
+                line,
+                message,
+            })
+    }
+
+    /// Parse settings, failing with the line number and a description
+    fn parse(text: &str) -> Result<Self, (usize, String)> {
+        let mut settings = Self::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err((line_number, "tables are not supported".to_string()));
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err((line_number, "expected key = value".to_string()));
+            };
+            let key = key.trim();
+            let value = Value::parse(value.trim()).map_err(|e| (line_number, e))?;
+            settings
+                .set(key, value)
+                .map_err(|e| (line_number, format!("{}: {}", key, e)))?;
+        }
+
+        Ok(settings)
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match key {
+            "filename_template" => {
+                let template = value.string()?;
+                self.filename_template =
+                    Some(FilenameTemplate::parse(&template).map_err(|e| e.to_string())?);
+            }
+            "ascii_filenames" => self.ascii_filenames = Some(value.boolean()?),
+            "limit" => {
+                self.limit = Some(
+                    usize::try_from(value.integer()?)
+                        .map_err(|_| "must not be negative".to_string())?,
+                );
+            }
+            "max_episode_size" => {
+                let size = match value {
+                    Value::Integer(bytes) => bytes.to_string(),
+                    value => value.string()?,
+                };
+                self.max_episode_size = Some(size.parse().map_err(|e| format!("{}", e))?);
+            }
+            "languages" => self.languages = Some(value.strings()?),
+            "video" => {
+                self.video = Some(match value.string()?.as_str() {
+                    "download" => VideoPolicy::Download,
+                    "skip" => VideoPolicy::Skip,
+                    "fail" => VideoPolicy::Fail,
+                    _ => return Err("expected \"download\", \"skip\" or \"fail\"".to_string()),
+                });
+            }
+            "prune" => {
+                self.prune = Some(match value.string()?.as_str() {
+                    "keep" => PrunePolicy::Keep,
+                    "delete" => PrunePolicy::Delete,
+                    "archive" => PrunePolicy::Archive,
+                    _ => return Err("expected \"keep\", \"delete\" or \"archive\"".to_string()),
+                });
+            }
+            "credentials_env" => self.credentials_env = Some(value.string()?),
+            _ => return Err("unknown setting".to_string()),
+        }
+        Ok(())
+    }
+
+    /// Options for syncing this podcast: `options` with the settings applied
+    ///
+    /// A filename template replaces the given one, including its length
+    /// limits. Credentials are only taken from `credentials_env` if that
+    /// variable is set.
+    pub fn apply(&self, options: &SyncOptions) -> SyncOptions {
+        let mut options = options.clone();
+        if let Some(template) = &self.filename_template {
+            options.filename_template = template.clone();
+        }
+        if let Some(ascii) = self.ascii_filenames {
+            options.ascii_filenames = ascii;
+        }
+        if let Some(limit) = self.limit {
+            options.limit = Some(limit);
+        }
+        if let Some(size) = self.max_episode_size {
+            options.max_episode_bytes = Some(size);
+        }
+        if let Some(languages) = &self.languages {
+            options.languages = languages.clone();
+        }
+        if let Some(video) = self.video {
+            options.video_policy = video;
+        }
+        if let Some(prune) = self.prune {
+            options.prune = prune;
+        }
+        if let Some(login) = self
+            .credentials_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+        {
+            options.credentials = Some(match login.split_once(':') {
+                Some((username, password)) => {
+                    Credentials::new(username, Some(password.to_string()))
+                }
+                None => Credentials::new(login, None),
+            });
+        }
+        options
+    }
+}
+
+/// The part of a line before a `#` outside of strings
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+/// A value of the supported TOML subset
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (value, rest) = Self::parse_prefix(text)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected '{}'", rest.trim()));
+        }
+        Ok(value)
+    }
+
+    /// Parse a value at the start of `text`, returning what follows it
+    fn parse_prefix(text: &str) -> Result<(Self, &str), String> {
+        let text = text.trim_start();
+        if let Some(rest) = text.strip_prefix('\'') {
+            let (string, rest) = rest
+                .split_once('\'')
+                .ok_or_else(|| "unterminated string".to_string())?;
+            return Ok((Value::String(string.to_string()), rest));
+        }
+        if let Some(rest) = text.strip_prefix('"') {
+            let mut string = String::new();
+            let mut chars = rest.char_indices();
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '"' => return Ok((Value::String(string), &rest[index + 1..])),
+                    '\\' => string.push(match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some(c @ ('"' | '\\')) => c,
+                        _ => return Err("unsupported escape sequence".to_string()),
+                    }),
+                    c => string.push(c),
+                }
+            }
+            return Err("unterminated string".to_string());
+        }
+        if let Some(mut rest) = text.strip_prefix('[') {
+            let mut items = Vec::new();
+            loop {
+                rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix(']') {
+                    return Ok((Value::Array(items), after));
+                }
+                let (item, after) = Self::parse_prefix(rest)?;
+                items.push(item);
+                rest = after.trim_start();
+                if let Some(after) = rest.strip_prefix(',') {
+                    rest = after;
+                } else if !rest.starts_with(']') {
+                    return Err("expected ',' or ']' in array".to_string());
+                }
+            }
+        }
+
+        let end = text
+            .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+            .unwrap_or(text.len());
+        let (word, rest) = text.split_at(end);
+        let value = match word {
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            _ => Value::Integer(
+                word.replace('_', "")
+                    .parse()
+                    .map_err(|_| format!("invalid value '{}'", word))?,
+            ),
+        };
+        Ok((value, rest))
+    }
+
+    fn string(self) -> Result<String, String> {
+        match self {
+            Value::String(string) => Ok(string),
+            _ => Err("expected a string".to_string()),
+        }
+    }
+
+    fn integer(self) -> Result<i64, String> {
+        match self {
+            Value::Integer(integer) => Ok(integer),
+            _ => Err("expected an integer".to_string()),
+        }
+    }
+
+    fn boolean(self) -> Result<bool, String> {
+        match self {
+            Value::Boolean(boolean) => Ok(boolean),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+
+    fn strings(self) -> Result<Vec<String>, String> {
+        match self {
+            Value::Array(items) => items.into_iter().map(Value::string).collect(),
+            _ => Err("expected an array of strings".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reads_and_applies_settings() {
+        let dir = tempdir().unwrap();
+        assert_eq!(PodcastSettings::read(dir.path()).unwrap(), None);
+
+        std::fs::write(
+            dir.path().join(SETTINGS_FILENAME),
+            r#"
+            # Settings for this show
+            filename_template = "{date} {title}"
+            limit = 5 # newest first
+            max_episode_size = 1_000
+            languages = ["de", "en # not a comment"]
+            video = "skip"
+            prune = "archive"
+            "#,
+        )
+        .unwrap();
+        let settings = PodcastSettings::read(dir.path()).unwrap().unwrap();
+        assert_eq!(
+            settings.languages,
+            Some(vec!["de".to_string(), "en # not a comment".to_string()])
+        );
+
+        let options = settings.apply(&SyncOptions {
+            limit: Some(100),
+            ascii_filenames: true,
+            ..SyncOptions::default()
+        });
+        assert_eq!(options.limit, Some(5));
+        assert!(options.ascii_filenames);
+        assert_eq!(options.max_episode_bytes, Some("1000".parse().unwrap()));
+        assert_eq!(options.video_policy, VideoPolicy::Skip);
+        assert_eq!(options.prune, PrunePolicy::Archive);
+        assert_eq!(
+            options.filename_template,
+            FilenameTemplate::parse("{date} {title}").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_settings_with_their_line() {
+        let invalid = [
+            ("limit = 5\nlimt = 5", 2, "limt: unknown setting"),
+            ("[podcast]", 1, "tables are not supported"),
+            ("limit = \"5\"", 1, "limit: expected an integer"),
+            ("video = \"never\"", 1, "video: expected"),
+            ("languages = [\"de\"", 1, "expected ',' or ']' in array"),
+        ];
+        for (text, line, message) in invalid {
+            let error = PodcastSettings::parse(text).unwrap_err();
+            assert_eq!(error.0, line, "{}", text);
+            assert!(error.1.starts_with(message), "{}: {}", text, error.1);
+        }
+    }
+}
@@ -2,20 +2,67 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::episode::is_valid_audio_extension;
 use crate::error::StateError;
 use crate::feed::Episode;
+#[cfg(feature = "sqlite")]
 use crate::metadata::read_episode_metadata;
+use crate::metadata::{
+    EpisodeMetadata, is_episode_metadata_filename, is_hidden, read_file_attributes,
+};
 use crate::progress::{ProgressEvent, SharedProgressReporter};
 
+mod adopt;
+mod cache;
+mod failures;
+mod filter;
+mod prune;
+mod repair;
+mod retention;
+#[cfg(feature = "sqlite")]
+mod store;
+mod transfer;
+
+pub use adopt::adopt_downloads;
+pub use cache::SCAN_CACHE_FILENAME;
+pub use failures::{FAILURES_FILENAME, FailureRecord, read_failure_history};
+pub(crate) use failures::{FailureHistory, failure_key};
+pub use filter::EpisodeFilter;
+pub use prune::{PrunePolicy, PrunedEpisode, find_removed_episodes, prune_removed_episodes};
+pub use repair::{CORRUPT_SUFFIX, RepairReport, repair_output_dir};
+pub use retention::{RetentionPolicy, apply_retention};
+pub use transfer::{
+    ExportedEpisode, STATE_EXPORT_VERSION, StateExport, StateImport, export_state, import_state,
+};
+
+#[cfg(feature = "sqlite")]
+pub use store::{DownloadRecord, LibraryStore, STORE_FILENAME};
+
 /// State of the output directory, tracking already-downloaded episodes
 #[derive(Debug, Clone)]
 pub struct OutputState {
-    /// GUIDs of episodes that have been downloaded
+    /// GUIDs of episodes that have been downloaded, in the form of
+    /// [`normalize_guid`]
     pub downloaded_guids: HashSet<String>,
-    /// Filenames (without path) of existing files
+    /// Enclosure URLs of episodes that have been downloaded, in the form of
+    /// [`canonical_enclosure_url`]
+    pub downloaded_urls: HashSet<String>,
+    /// Content hashes of downloaded episodes, mapped to their metadata file
+    /// (relative to the output directory); retired episodes are left out
+    pub content_hashes: HashMap<String, String>,
+    /// Enclosures downloaded episodes were downloaded from, by GUID in the
+    /// form of [`normalize_guid`]; retired episodes are left out
+    pub enclosures: HashMap<String, DownloadedEnclosure>,
+    /// Paths of existing files, relative to the output directory
     pub existing_files: HashSet<String>,
     /// The output directory path
     pub output_dir: PathBuf,
@@ -23,6 +70,173 @@ pub struct OutputState {
     pub partial_files_cleaned: usize,
 }
 
+/// What identifies a downloaded episode besides its metadata file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeKeys {
+    pub guid: Option<String>,
+    /// Enclosure URL the episode was downloaded from
+    pub original_url: String,
+    /// Length the feed announced for the enclosure
+    pub enclosure_length: Option<u64>,
+    pub content_hash: Option<String>,
+    /// Whether a retention policy deleted the audio file, see
+    /// [`EpisodeMetadata::retired_at`]
+    pub retired: bool,
+}
+
+/// Enclosure a downloaded episode was downloaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadedEnclosure {
+    /// The episode's metadata file, relative to the output directory
+    pub metadata_path: String,
+    pub url: String,
+    pub length: Option<u64>,
+}
+
+impl From<&EpisodeMetadata> for EpisodeKeys {
+    fn from(metadata: &EpisodeMetadata) -> Self {
+        Self {
+            guid: metadata.guid.clone(),
+            original_url: metadata.original_url.clone(),
+            enclosure_length: metadata.enclosure_length,
+            content_hash: metadata.content_hash.clone(),
+            retired: metadata.retired_at.is_some(),
+        }
+    }
+}
+
+impl OutputState {
+    /// Record a downloaded episode, given its metadata file relative to the
+    /// output directory
+    pub fn record(&mut self, metadata_path: &str, keys: EpisodeKeys) {
+        self.downloaded_urls
+            .insert(canonical_enclosure_url(&keys.original_url));
+        let guid = keys.guid.as_deref().map(normalize_guid);
+        if keys.retired {
+            self.downloaded_guids.extend(guid);
+            return;
+        }
+        if let Some(guid) = guid {
+            self.enclosures.insert(
+                guid.clone(),
+                DownloadedEnclosure {
+                    metadata_path: metadata_path.to_string(),
+                    url: keys.original_url,
+                    length: keys.enclosure_length,
+                },
+            );
+            self.downloaded_guids.insert(guid);
+        }
+        if let Some(hash) = keys.content_hash {
+            self.content_hashes.insert(hash, metadata_path.to_string());
+        }
+    }
+
+    /// Whether an episode was downloaded: by its GUID, or by its enclosure
+    /// URL for feeds that regenerate their GUIDs
+    pub fn is_downloaded(&self, episode: &Episode) -> bool {
+        episode
+            .guid
+            .as_ref()
+            .is_some_and(|guid| self.downloaded_guids.contains(&normalize_guid(guid)))
+            || self
+                .downloaded_urls
+                .contains(&canonical_enclosure_url(episode.enclosure.url.as_str()))
+    }
+
+    /// The enclosure a downloaded episode was downloaded from, if the feed
+    /// has replaced it since
+    ///
+    /// An enclosure counts as replaced when its URL differs (see
+    /// [`canonical_enclosure_url`]) or both lengths are known and differ.
+    /// A length of 0, as many feeds announce, counts as unknown.
+    pub fn replaced_enclosure(&self, episode: &Episode) -> Option<&DownloadedEnclosure> {
+        let downloaded = self
+            .enclosures
+            .get(&normalize_guid(episode.guid.as_ref()?))?;
+        let known = |length: Option<u64>| length.filter(|length| *length > 0);
+        let url_changed = canonical_enclosure_url(&downloaded.url)
+            != canonical_enclosure_url(episode.enclosure.url.as_str());
+        let length_changed = known(downloaded.length)
+            .zip(known(episode.enclosure.length))
+            .is_some_and(|(old, new)| old != new);
+        (url_changed || length_changed).then_some(downloaded)
+    }
+}
+
+/// What to do with downloaded episodes whose enclosure the feed has replaced,
+/// e.g. with corrected audio
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdatePolicy {
+    /// Keep the file downloaded first
+    #[default]
+    Keep,
+    /// Download the episode again, moving the old files to
+    /// [`ARCHIVE_DIR`](crate::metadata::ARCHIVE_DIR)
+    Redownload,
+}
+
+/// Order in which a sync downloads new episodes, and keeps them under a
+/// limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DownloadOrder {
+    /// Latest publication date first
+    #[default]
+    NewestFirst,
+    /// Earliest publication date first, for listening through an archive
+    OldestFirst,
+    /// The order of the feed's items
+    FeedOrder,
+    /// Smallest announced enclosure length first
+    SmallestFirst,
+}
+
+/// Canonical form of an enclosure URL for recognizing re-published episodes
+///
+/// Ignores the scheme, credentials, fragments, a leading `www.` and
+/// `utm_*` tracking parameters. Inputs that are not URLs are returned
+/// trimmed.
+pub fn canonical_enclosure_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+
+    let host = parsed.host_str().unwrap_or_default();
+    let mut canonical = host.strip_prefix("www.").unwrap_or(host).to_string();
+    if let Some(port) = parsed.port() {
+        canonical = format!("{}:{}", canonical, port);
+    }
+    canonical.push_str(parsed.path());
+
+    let query: Vec<_> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_"))
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    if !query.is_empty() {
+        canonical = format!("{}?{}", canonical, query.join("&"));
+    }
+    canonical
+}
+
+/// Canonical form of an episode GUID for recognizing downloaded episodes
+///
+/// Feeds sometimes change the whitespace around GUIDs, or the case or
+/// scheme of URL-style ones. GUIDs are trimmed; `http` and `https` URLs
+/// also get a lowercase host and count as `https`. Other GUIDs are kept
+/// as they are, as their case may matter.
+pub fn normalize_guid(guid: &str) -> String {
+    let guid = guid.trim();
+    match Url::parse(guid) {
+        Ok(mut url) if matches!(url.scheme(), "http" | "https") => {
+            // Parsing already lowercased the scheme and host
+            let _ = url.set_scheme("https");
+            url.to_string()
+        }
+        _ => guid.to_string(),
+    }
+}
+
 /// Plan for synchronization, indicating what needs to be downloaded
 #[derive(Debug, Clone)]
 pub struct SyncPlan {
@@ -30,152 +244,362 @@ pub struct SyncPlan {
     pub to_download: Vec<Episode>,
     /// Episodes already present in the output directory
     pub already_present: Vec<Episode>,
+    /// Episodes of `already_present` whose enclosure has been replaced since
+    /// their download, see [`OutputState::replaced_enclosure`]
+    pub updated: Vec<Episode>,
     /// Total number of episodes in the feed
     pub total_episodes: usize,
 }
 
 /// Scan the output directory to detect existing downloads
 ///
-/// Reads all .json metadata files to extract GUIDs of already-downloaded episodes.
-/// Also cleans up any `.partial` files from interrupted downloads.
+/// Reads all .json metadata files to extract GUIDs of already-downloaded
+/// episodes, except those unchanged since the last scan, whose keys are
+/// kept in [`SCAN_CACHE_FILENAME`].
+/// Audio files without a metadata file count by the GUID in their extended
+/// attributes, if any (see [`write_file_attributes`]). Also cleans up any
+/// `.partial` and `.finalizing` files from interrupted downloads.
+/// Subdirectories (e.g. `Season 01/` or `2024/01/` from [`OutputLayout`])
+/// are scanned as well, except hidden ones.
+///
+/// [`OutputLayout`]: crate::episode::OutputLayout
+/// [`write_file_attributes`]: crate::metadata::write_file_attributes
 pub fn scan_output_dir(
     output_dir: &Path,
     reporter: &SharedProgressReporter,
 ) -> Result<OutputState, StateError> {
-    let mut downloaded_guids = HashSet::new();
-    let mut existing_files = HashSet::new();
-    let mut partial_files_cleaned = 0;
+    scan(output_dir, reporter, false)
+}
+
+/// Scan the output directory without modifying it
+///
+/// Like [`scan_output_dir`], but a missing directory is not created,
+/// leftover download files are left in place and the scan cache is not
+/// updated, so it works on read-only media.
+pub fn scan_output_dir_read_only(
+    output_dir: &Path,
+    reporter: &SharedProgressReporter,
+) -> Result<OutputState, StateError> {
+    scan(output_dir, reporter, true)
+}
+
+fn scan(
+    output_dir: &Path,
+    reporter: &SharedProgressReporter,
+    read_only: bool,
+) -> Result<OutputState, StateError> {
+    let (mut state, json_files) = list_output_dir(output_dir, reporter, read_only)?;
+
+    // Process JSON metadata files with progress (this is the slow part on network shares)
+    let mut cache = cache::ScanCache::load(output_dir);
+    let all_keys = read_concurrently(&json_files, reporter, |path| cache.keys(output_dir, path));
+
+    let mut present = HashSet::new();
+    for (path, keys) in json_files.iter().zip(all_keys) {
+        let metadata_path = relative_name(path, output_dir);
+        if let Some(keys) = keys {
+            state.record(&metadata_path, keys);
+        }
+        present.insert(metadata_path);
+    }
+    add_attribute_guids(&mut state);
+
+    cache.retain(&present);
+    if !read_only {
+        // Losing the cache only makes the next scan slower
+        let _ = cache.save(output_dir);
+    }
+
+    Ok(state)
+}
+
+/// Scan the output directory, taking episode GUIDs from a [`LibraryStore`]
+///
+/// Like [`scan_output_dir`], but only metadata files missing from the
+/// database are read, and then imported into it; entries whose metadata
+/// file is gone are dropped from the database. The directory listing is
+/// still needed to find partial and deleted files.
+///
+/// Requires the `sqlite` feature.
+#[cfg(feature = "sqlite")]
+pub fn scan_output_dir_with_store(
+    output_dir: &Path,
+    reporter: &SharedProgressReporter,
+    store: &LibraryStore,
+) -> Result<OutputState, StateError> {
+    let (mut state, json_files) = list_output_dir(output_dir, reporter, false)?;
+    let mut recorded = store.episode_keys()?;
+
+    let mut unrecorded = Vec::new();
+    for path in json_files {
+        let metadata_path = relative_name(&path, output_dir);
+        match recorded.remove(&metadata_path) {
+            Some(keys) => state.record(&metadata_path, keys),
+            None => unrecorded.push(path),
+        }
+    }
+    for metadata_path in recorded.keys() {
+        store.forget_episode(metadata_path)?;
+    }
+
+    let all_metadata = read_concurrently(&unrecorded, reporter, |path| {
+        read_episode_metadata(path).ok()
+    });
+    for (path, metadata) in unrecorded.iter().zip(all_metadata) {
+        if let Some(metadata) = metadata {
+            let metadata_path = relative_name(path, output_dir);
+            store.import_episode(&metadata_path, &metadata)?;
+            state.record(&metadata_path, (&metadata).into());
+        }
+    }
+    add_attribute_guids(&mut state);
+
+    Ok(state)
+}
+
+/// Number of metadata files read at the same time
+///
+/// Reading them is dominated by latency on network shares, where more
+/// threads than cores pay off.
+const SCAN_THREADS: usize = 16;
+
+/// Apply `read` to each of `files` on several threads, reporting each
+/// finished file as [`ProgressEvent::ScanningDirectory`]
+///
+/// Returns the results in the order of `files`.
+fn read_concurrently<T: Send>(
+    files: &[PathBuf],
+    reporter: &SharedProgressReporter,
+    read: impl Fn(&Path) -> T + Sync,
+) -> Vec<T> {
+    let total_files = files.len();
+    reporter.report(ProgressEvent::ScanningDirectory {
+        files_scanned: 0,
+        total_files,
+    });
+
+    let next = AtomicUsize::new(0);
+    let files_scanned = Mutex::new(0);
+    let mut results = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..SCAN_THREADS.min(total_files))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = files.get(index) else {
+                            break;
+                        };
+                        results.push((index, read(path)));
+                        // Counted under the lock, so progress never goes backwards
+                        let mut files_scanned = files_scanned.lock().unwrap();
+                        *files_scanned += 1;
+                        reporter.report(ProgressEvent::ScanningDirectory {
+                            files_scanned: *files_scanned,
+                            total_files,
+                        });
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Count audio files lacking a metadata file by the GUID in their extended
+/// attributes
+fn add_attribute_guids(state: &mut OutputState) {
+    let guids: Vec<_> = state
+        .existing_files
+        .iter()
+        .filter(|relative| {
+            relative.rsplit_once('.').is_some_and(|(stem, extension)| {
+                is_valid_audio_extension(extension)
+                    && !state.existing_files.contains(&format!("{}.json", stem))
+            })
+        })
+        .filter_map(|relative| read_file_attributes(&state.output_dir.join(relative)).guid)
+        .map(|guid| normalize_guid(&guid))
+        .collect();
+    state.downloaded_guids.extend(guids);
+}
+
+/// List the output directory, creating it if missing (unless read-only)
+///
+/// Returns the state without GUIDs, and the episode metadata files to read
+/// them from. Partial files are removed unless read-only.
+fn list_output_dir(
+    output_dir: &Path,
+    reporter: &SharedProgressReporter,
+    read_only: bool,
+) -> Result<(OutputState, Vec<PathBuf>), StateError> {
+    let mut state = OutputState {
+        downloaded_guids: HashSet::new(),
+        downloaded_urls: HashSet::new(),
+        content_hashes: HashMap::new(),
+        enclosures: HashMap::new(),
+        existing_files: HashSet::new(),
+        output_dir: output_dir.to_path_buf(),
+        partial_files_cleaned: 0,
+    };
 
     if !output_dir.exists() {
-        // Create the directory if it doesn't exist
-        std::fs::create_dir_all(output_dir).map_err(|e| StateError::CreateDirectoryFailed {
-            path: output_dir.to_path_buf(),
-            source: e,
-        })?;
+        if !read_only {
+            // Create the directory if it doesn't exist
+            std::fs::create_dir_all(output_dir).map_err(|e| StateError::CreateDirectoryFailed {
+                path: output_dir.to_path_buf(),
+                source: e,
+            })?;
+        }
 
         reporter.report(ProgressEvent::ScanningDirectory {
             files_scanned: 0,
             total_files: 0,
         });
 
-        return Ok(OutputState {
-            downloaded_guids,
-            existing_files,
-            output_dir: output_dir.to_path_buf(),
-            partial_files_cleaned,
-        });
+        return Ok((state, Vec::new()));
     }
 
-    // Collect entries first (single network traversal)
-    let entries: Vec<_> = std::fs::read_dir(output_dir)
-        .map_err(|e| StateError::ReadDirectoryFailed {
-            path: output_dir.to_path_buf(),
-            source: e,
-        })?
-        .collect();
+    // Collect entries first (single network traversal per directory)
+    let mut entries = Vec::new();
+    let mut dirs = vec![output_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for (path, is_dir) in read_entries(&dir)? {
+            if !is_dir {
+                entries.push(path);
+            } else if !is_hidden(&path) {
+                dirs.push(path);
+            }
+        }
+    }
 
     // Categorize entries - this is fast (just filename checks, no I/O)
     let mut partial_files = Vec::new();
     let mut json_files = Vec::new();
 
-    for entry in entries {
-        let entry = entry.map_err(|e| StateError::ReadDirectoryFailed {
-            path: output_dir.to_path_buf(),
-            source: e,
-        })?;
-
-        let path = entry.path();
+    for path in entries {
         let filename = path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
 
-        if filename.ends_with(".partial") {
+        if filename.ends_with(".partial") || filename.ends_with(".finalizing") {
             partial_files.push(path);
         } else {
-            existing_files.insert(filename.clone());
+            state
+                .existing_files
+                .insert(relative_name(&path, output_dir));
 
-            if filename.ends_with(".json") && filename != "podcast.json" {
+            if is_episode_metadata_filename(&filename) {
                 json_files.push(path);
             }
         }
     }
 
     // Clean up partial files (fast local operation)
-    for path in partial_files {
+    for path in partial_files.into_iter().filter(|_| !read_only) {
         if std::fs::remove_file(&path).is_ok() {
-            partial_files_cleaned += 1;
+            state.partial_files_cleaned += 1;
         }
     }
 
-    // Process JSON metadata files with progress (this is the slow part on network shares)
-    let total_json_files = json_files.len();
-
-    reporter.report(ProgressEvent::ScanningDirectory {
-        files_scanned: 0,
-        total_files: total_json_files,
-    });
-
-    for (index, path) in json_files.into_iter().enumerate() {
-        if let Ok(metadata) = read_episode_metadata(&path)
-            && let Some(guid) = metadata.guid
-        {
-            downloaded_guids.insert(guid);
-        }
+    Ok((state, json_files))
+}
 
-        reporter.report(ProgressEvent::ScanningDirectory {
-            files_scanned: index + 1,
-            total_files: total_json_files,
-        });
-    }
+/// List a directory's entries together with whether each is a directory
+fn read_entries(dir: &Path) -> Result<Vec<(PathBuf, bool)>, StateError> {
+    let read_failed = |e| StateError::ReadDirectoryFailed {
+        path: dir.to_path_buf(),
+        source: e,
+    };
+
+    std::fs::read_dir(dir)
+        .map_err(read_failed)?
+        .map(|entry| {
+            let entry = entry.map_err(read_failed)?;
+            let is_dir = entry.file_type().map_err(read_failed)?.is_dir();
+            Ok((entry.path(), is_dir))
+        })
+        .collect()
+}
 
-    Ok(OutputState {
-        downloaded_guids,
-        existing_files,
-        output_dir: output_dir.to_path_buf(),
-        partial_files_cleaned,
-    })
+/// Path relative to the output directory, using `/` as separator
+pub(crate) fn relative_name(path: &Path, output_dir: &Path) -> String {
+    path.strip_prefix(output_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 /// Create a sync plan by comparing episodes against the output state
 ///
 /// Determines which episodes need to be downloaded based on:
 /// 1. GUID matching (if episode has a GUID that matches a downloaded one, skip)
-/// 2. If no GUID match, episode will be downloaded
+/// 2. Enclosure URL matching, see [`canonical_enclosure_url`] (feeds that
+///    regenerate their GUIDs keep their enclosure URLs)
+/// 3. If neither matches, episode will be downloaded
 ///
-/// Episodes are sorted by publication date (newest first). Episodes without
-/// a publication date are placed at the end, preserving their relative order.
-pub fn create_sync_plan(episodes: Vec<Episode>, state: &OutputState) -> SyncPlan {
+/// Present episodes whose enclosure the feed has replaced are also listed
+/// in [`SyncPlan::updated`].
+///
+/// Only new episodes matching `filter` are downloaded.
+///
+/// Episodes to download are sorted by `order`. Episodes without a
+/// publication date, or without a length for [`DownloadOrder::SmallestFirst`],
+/// are placed at the end, preserving their relative order.
+pub fn create_sync_plan(
+    episodes: Vec<Episode>,
+    state: &OutputState,
+    filter: &EpisodeFilter,
+    order: DownloadOrder,
+) -> SyncPlan {
     let total_episodes = episodes.len();
     let mut to_download = Vec::new();
     let mut already_present = Vec::new();
+    let mut updated = Vec::new();
 
     for episode in episodes {
-        let is_downloaded = episode
-            .guid
-            .as_ref()
-            .is_some_and(|guid| state.downloaded_guids.contains(guid));
-
-        if is_downloaded {
+        if state.is_downloaded(&episode) {
+            if state.replaced_enclosure(&episode).is_some() {
+                updated.push(episode.clone());
+            }
             already_present.push(episode);
-        } else {
+        } else if filter.matches(&episode) {
             to_download.push(episode);
         }
     }
 
-    // Sort episodes by publication date (newest first)
-    // Episodes without pub_date are placed at the end
-    to_download.sort_by(|a, b| match (&b.pub_date, &a.pub_date) {
-        (Some(b_date), Some(a_date)) => b_date.cmp(a_date),
-        (Some(_), None) => std::cmp::Ordering::Greater, // b has date, a doesn't => b comes first
-        (None, Some(_)) => std::cmp::Ordering::Less,    // a has date, b doesn't => a comes first
-        (None, None) => std::cmp::Ordering::Equal,
-    });
+    // Stable sorts keep the feed order among equal keys; episodes
+    // without the key go last
+    match order {
+        DownloadOrder::NewestFirst => to_download
+            .sort_by_key(|episode| (episode.pub_date.is_none(), episode.pub_date.map(Reverse))),
+        DownloadOrder::OldestFirst => {
+            to_download.sort_by_key(|episode| (episode.pub_date.is_none(), episode.pub_date))
+        }
+        DownloadOrder::FeedOrder => {}
+        DownloadOrder::SmallestFirst => to_download
+            .sort_by_key(|episode| (episode.enclosure.length.is_none(), episode.enclosure.length)),
+    }
 
     SyncPlan {
         to_download,
         already_present,
+        updated,
         total_episodes,
     }
 }
@@ -187,6 +611,7 @@ mod tests {
     use crate::metadata::write_episode_metadata;
     use crate::progress::NoopReporter;
     use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use std::collections::BTreeMap;
     use tempfile::tempdir;
     use url::Url;
 
@@ -201,9 +626,13 @@ mod tests {
                 length: None,
                 mime_type: None,
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: None,
             episode_number: None,
             season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -222,9 +651,13 @@ mod tests {
                 length: None,
                 mime_type: None,
             },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
             duration: None,
             episode_number: None,
             season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -277,6 +710,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scan_reads_many_metadata_files_concurrently() {
+        struct ProgressRecorder(std::sync::Mutex<Vec<usize>>);
+        impl crate::progress::ProgressReporter for ProgressRecorder {
+            fn report(&self, event: ProgressEvent) {
+                if let ProgressEvent::ScanningDirectory { files_scanned, .. } = event {
+                    self.0.lock().unwrap().push(files_scanned);
+                }
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        for index in 0..50 {
+            let guid = format!("guid-{}", index);
+            let episode = make_episode(&guid, Some(&guid));
+            let meta_path = dir.path().join(format!("{}.json", guid));
+            write_episode_metadata(&episode, &format!("{}.mp3", guid), None, &meta_path).unwrap();
+        }
+
+        let recorder = std::sync::Arc::new(ProgressRecorder(Default::default()));
+        let reporter: SharedProgressReporter = recorder.clone();
+        let state = scan_output_dir(dir.path(), &reporter).unwrap();
+
+        assert_eq!(state.downloaded_guids.len(), 50);
+        let progress = recorder.0.lock().unwrap();
+        assert_eq!(progress.last(), Some(&50));
+        assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn scan_finds_episodes_in_season_directories() {
+        let dir = tempdir().unwrap();
+        let season_dir = dir.path().join("Season 02");
+        std::fs::create_dir(&season_dir).unwrap();
+
+        let episode = make_episode("Test Episode", Some("season-guid"));
+        let meta_path = season_dir.join("2024-01-15-test-episode.json");
+        write_episode_metadata(&episode, "2024-01-15-test-episode.mp3", None, &meta_path).unwrap();
+        std::fs::write(season_dir.join("old.mp3.partial"), b"partial").unwrap();
+
+        let reporter = NoopReporter::shared();
+        let state = scan_output_dir(dir.path(), &reporter).unwrap();
+
+        assert!(state.downloaded_guids.contains("season-guid"));
+        assert!(
+            state
+                .existing_files
+                .contains("Season 02/2024-01-15-test-episode.json")
+        );
+        assert_eq!(state.partial_files_cleaned, 1);
+    }
+
+    #[test]
+    fn scan_finds_episodes_in_nested_directories() {
+        let dir = tempdir().unwrap();
+        let month_dir = dir.path().join("2024").join("01");
+        let hidden_dir = dir.path().join(".quarantine");
+        std::fs::create_dir_all(&month_dir).unwrap();
+        std::fs::create_dir(&hidden_dir).unwrap();
+
+        let episode = make_episode("Test Episode", Some("month-guid"));
+        let meta_path = month_dir.join("2024-01-15-test-episode.json");
+        write_episode_metadata(&episode, "2024-01-15-test-episode.mp3", None, &meta_path).unwrap();
+        let hidden = make_episode("Hidden", Some("hidden-guid"));
+        write_episode_metadata(&hidden, "hidden.mp3", None, &hidden_dir.join("hidden.json"))
+            .unwrap();
+
+        let reporter = NoopReporter::shared();
+        let state = scan_output_dir(dir.path(), &reporter).unwrap();
+
+        assert!(state.downloaded_guids.contains("month-guid"));
+        assert!(!state.downloaded_guids.contains("hidden-guid"));
+        assert!(
+            state
+                .existing_files
+                .contains("2024/01/2024-01-15-test-episode.json")
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn scan_with_store_reads_only_unrecorded_metadata() {
+        let dir = tempdir().unwrap();
+        let reporter = NoopReporter::shared();
+        let kept = dir.path().join("kept.json");
+        let deleted = dir.path().join("deleted.json");
+        write_episode_metadata(&make_episode("Kept", Some("kept")), "kept.mp3", None, &kept)
+            .unwrap();
+        write_episode_metadata(
+            &make_episode("Deleted", Some("deleted")),
+            "deleted.mp3",
+            None,
+            &deleted,
+        )
+        .unwrap();
+
+        let store = LibraryStore::open(dir.path()).unwrap();
+        let state = scan_output_dir_with_store(dir.path(), &reporter, &store).unwrap();
+        assert_eq!(state.downloaded_guids.len(), 2);
+
+        // Recorded files are not read again, and deleted ones are forgotten
+        std::fs::write(&kept, "{").unwrap();
+        std::fs::remove_file(&deleted).unwrap();
+        let state = scan_output_dir_with_store(dir.path(), &reporter, &store).unwrap();
+        assert_eq!(state.downloaded_guids, HashSet::from(["kept".to_string()]));
+        assert_eq!(store.episode_keys().unwrap().len(), 1);
+    }
+
     #[test]
     fn scan_ignores_podcast_json() {
         let dir = tempdir().unwrap();
@@ -294,10 +835,131 @@ mod tests {
         assert!(state.downloaded_guids.is_empty());
     }
 
+    #[test]
+    fn canonical_enclosure_url_ignores_scheme_and_tracking() {
+        let canonical = canonical_enclosure_url("https://example.com/ep.mp3?id=1");
+        for url in [
+            "http://example.com/ep.mp3?id=1",
+            "https://www.example.com/ep.mp3?utm_source=rss&id=1",
+            "https://example.com/ep.mp3?id=1#t=10",
+        ] {
+            assert_eq!(canonical_enclosure_url(url), canonical, "{}", url);
+        }
+        assert_ne!(
+            canonical_enclosure_url("https://example.com:8080/ep.mp3?id=1"),
+            canonical
+        );
+    }
+
+    #[test]
+    fn normalize_guid_ignores_whitespace_and_url_case() {
+        let normalized = normalize_guid("https://example.com/Episodes/1");
+        for guid in [
+            "  https://example.com/Episodes/1\n",
+            "HTTPS://Example.COM/Episodes/1",
+            "http://example.com/Episodes/1",
+        ] {
+            assert_eq!(normalize_guid(guid), normalized, "{:?}", guid);
+        }
+        // Paths and non-URL GUIDs keep their case
+        assert_ne!(normalize_guid("https://example.com/episodes/1"), normalized);
+        assert_eq!(normalize_guid(" Episode-ABC "), "Episode-ABC");
+        assert_eq!(normalize_guid("urn:uuid:ABC-123"), "urn:uuid:ABC-123");
+    }
+
+    #[test]
+    fn recognizes_episodes_by_normalized_guid() {
+        let mut state = OutputState {
+            downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
+            existing_files: HashSet::new(),
+            output_dir: PathBuf::from("/tmp"),
+            partial_files_cleaned: 0,
+        };
+        state.record(
+            "ep.json",
+            EpisodeKeys {
+                guid: Some(" http://Example.com/ep/1 ".to_string()),
+                original_url: "https://cdn.example.com/old.mp3".to_string(),
+                ..EpisodeKeys::default()
+            },
+        );
+
+        let episode = make_episode("Episode", Some("https://example.com/ep/1"));
+        assert!(state.is_downloaded(&episode));
+        assert!(state.replaced_enclosure(&episode).is_some());
+        assert!(!state.is_downloaded(&make_episode("Other", Some("https://example.com/ep/2"))));
+    }
+
+    #[test]
+    fn sync_plan_lists_episodes_with_replaced_enclosures() {
+        let mut state = OutputState {
+            downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
+            existing_files: HashSet::new(),
+            output_dir: PathBuf::from("/tmp"),
+            partial_files_cleaned: 0,
+        };
+        for guid in ["same", "moved", "resized", "unknown-length"] {
+            state.record(
+                &format!("{}.json", guid),
+                EpisodeKeys {
+                    guid: Some(guid.to_string()),
+                    original_url: format!("https://example.com/{}.mp3", guid),
+                    enclosure_length: (guid != "unknown-length").then_some(1000),
+                    content_hash: None,
+                    retired: false,
+                },
+            );
+        }
+
+        let episode = |guid: &str, url: &str, length: u64| {
+            let mut episode = make_episode(guid, Some(guid));
+            episode.enclosure.url = Url::parse(url).unwrap();
+            episode.enclosure.length = Some(length);
+            episode
+        };
+        let episodes = vec![
+            episode("same", "http://example.com/same.mp3", 1000),
+            episode("moved", "https://example.com/v2/moved.mp3", 1000),
+            episode("resized", "https://example.com/resized.mp3", 2000),
+            episode(
+                "unknown-length",
+                "https://example.com/unknown-length.mp3",
+                2000,
+            ),
+        ];
+
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
+
+        assert_eq!(plan.already_present.len(), 4);
+        let updated: Vec<_> = plan.updated.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(updated, vec!["moved", "resized"]);
+        assert_eq!(
+            state
+                .replaced_enclosure(&plan.updated[0])
+                .unwrap()
+                .metadata_path,
+            "moved.json"
+        );
+    }
+
     #[test]
     fn sync_plan_identifies_new_episodes() {
         let state = OutputState {
             downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
             existing_files: HashSet::new(),
             output_dir: PathBuf::from("/tmp"),
             partial_files_cleaned: 0,
@@ -308,7 +970,12 @@ mod tests {
             make_episode("Ep 2", Some("guid-2")),
         ];
 
-        let plan = create_sync_plan(episodes, &state);
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
 
         assert_eq!(plan.to_download.len(), 2);
         assert_eq!(plan.already_present.len(), 0);
@@ -322,6 +989,9 @@ mod tests {
 
         let state = OutputState {
             downloaded_guids,
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
             existing_files: HashSet::new(),
             output_dir: PathBuf::from("/tmp"),
             partial_files_cleaned: 0,
@@ -332,7 +1002,12 @@ mod tests {
             make_episode("Ep 2", Some("guid-2")),
         ];
 
-        let plan = create_sync_plan(episodes, &state);
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
 
         assert_eq!(plan.to_download.len(), 1);
         assert_eq!(plan.to_download[0].title, "Ep 2");
@@ -347,6 +1022,9 @@ mod tests {
 
         let state = OutputState {
             downloaded_guids,
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
             existing_files: HashSet::new(),
             output_dir: PathBuf::from("/tmp"),
             partial_files_cleaned: 0,
@@ -357,7 +1035,12 @@ mod tests {
             make_episode("Ep 2", None), // No GUID, should be downloaded
         ];
 
-        let plan = create_sync_plan(episodes, &state);
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
 
         assert_eq!(plan.to_download.len(), 1);
         assert_eq!(plan.to_download[0].title, "Ep 2");
@@ -370,6 +1053,8 @@ mod tests {
         // Create some partial files
         std::fs::write(dir.path().join("episode1.mp3.partial"), b"partial data 1").unwrap();
         std::fs::write(dir.path().join("episode2.mp3.partial"), b"partial data 2").unwrap();
+        // And a copy interrupted while finalizing across filesystems
+        std::fs::write(dir.path().join("episode4.mp3.finalizing"), b"half copied").unwrap();
         // Create a normal file
         std::fs::write(dir.path().join("episode3.mp3"), b"complete audio").unwrap();
 
@@ -377,9 +1062,11 @@ mod tests {
         let state = scan_output_dir(dir.path(), &reporter).unwrap();
 
         // Partial files should have been cleaned up
-        assert_eq!(state.partial_files_cleaned, 2);
+        assert_eq!(state.partial_files_cleaned, 3);
         assert!(!dir.path().join("episode1.mp3.partial").exists());
         assert!(!dir.path().join("episode2.mp3.partial").exists());
+        assert!(!dir.path().join("episode4.mp3.finalizing").exists());
+        assert!(!state.existing_files.contains("episode4.mp3.finalizing"));
         // Normal file should still exist
         assert!(dir.path().join("episode3.mp3").exists());
         assert!(state.existing_files.contains("episode3.mp3"));
@@ -388,10 +1075,29 @@ mod tests {
         assert!(!state.existing_files.contains("episode2.mp3.partial"));
     }
 
+    #[test]
+    fn read_only_scan_leaves_directory_untouched() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("episode1.mp3.partial"), b"partial data").unwrap();
+
+        let reporter = NoopReporter::shared();
+        let state = scan_output_dir_read_only(dir.path(), &reporter).unwrap();
+        assert_eq!(state.partial_files_cleaned, 0);
+        assert!(dir.path().join("episode1.mp3.partial").exists());
+
+        let missing = dir.path().join("missing");
+        let state = scan_output_dir_read_only(&missing, &reporter).unwrap();
+        assert!(state.existing_files.is_empty());
+        assert!(!missing.exists());
+    }
+
     #[test]
     fn sync_plan_sorts_episodes_by_pub_date_newest_first() {
         let state = OutputState {
             downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
             existing_files: HashSet::new(),
             output_dir: PathBuf::from("/tmp"),
             partial_files_cleaned: 0,
@@ -412,7 +1118,12 @@ mod tests {
             ),
         ];
 
-        let plan = create_sync_plan(episodes, &state);
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
 
         // Should be sorted newest first
         assert_eq!(plan.to_download.len(), 3);
@@ -425,6 +1136,9 @@ mod tests {
     fn sync_plan_places_episodes_without_date_at_end() {
         let state = OutputState {
             downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
             existing_files: HashSet::new(),
             output_dir: PathBuf::from("/tmp"),
             partial_files_cleaned: 0,
@@ -436,7 +1150,12 @@ mod tests {
             make_episode_with_date("No Date 2", Some("guid-3"), None),
         ];
 
-        let plan = create_sync_plan(episodes, &state);
+        let plan = create_sync_plan(
+            episodes,
+            &state,
+            &EpisodeFilter::default(),
+            DownloadOrder::NewestFirst,
+        );
 
         // Episode with date should be first, undated ones at the end
         assert_eq!(plan.to_download.len(), 3);
@@ -445,4 +1164,92 @@ mod tests {
         assert_eq!(plan.to_download[1].title, "No Date 1");
         assert_eq!(plan.to_download[2].title, "No Date 2");
     }
+
+    #[test]
+    fn sync_plan_orders_episodes_as_requested() {
+        let state = OutputState {
+            downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
+            existing_files: HashSet::new(),
+            output_dir: PathBuf::from("/tmp"),
+            partial_files_cleaned: 0,
+        };
+        let episode = |title, date: Option<DateTime<FixedOffset>>, length| {
+            let mut episode = make_episode_with_date(title, Some(title), date);
+            episode.enclosure.length = length;
+            episode
+        };
+        let titles = |order| {
+            let episodes = vec![
+                episode("Middle", Some(make_date(2024, 2, 1)), Some(300)),
+                episode("Undated", None, Some(100)),
+                episode("Oldest", Some(make_date(2024, 1, 1)), None),
+                episode("Newest", Some(make_date(2024, 3, 1)), Some(200)),
+            ];
+            create_sync_plan(episodes, &state, &EpisodeFilter::default(), order)
+                .to_download
+                .into_iter()
+                .map(|episode| episode.title)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            titles(DownloadOrder::NewestFirst),
+            ["Newest", "Middle", "Oldest", "Undated"]
+        );
+        assert_eq!(
+            titles(DownloadOrder::OldestFirst),
+            ["Oldest", "Middle", "Newest", "Undated"]
+        );
+        assert_eq!(
+            titles(DownloadOrder::FeedOrder),
+            ["Middle", "Undated", "Oldest", "Newest"]
+        );
+        assert_eq!(
+            titles(DownloadOrder::SmallestFirst),
+            ["Undated", "Newest", "Middle", "Oldest"]
+        );
+    }
+
+    #[test]
+    fn sync_plan_skips_episodes_published_outside_the_range() {
+        let state = OutputState {
+            downloaded_guids: HashSet::new(),
+            downloaded_urls: HashSet::new(),
+            content_hashes: HashMap::new(),
+            enclosures: HashMap::new(),
+            existing_files: HashSet::new(),
+            output_dir: PathBuf::from("/tmp"),
+            partial_files_cleaned: 0,
+        };
+        let episodes = || {
+            vec![
+                make_episode_with_date("2022", Some("guid-1"), Some(make_date(2022, 12, 31))),
+                make_episode_with_date("2023", Some("guid-2"), Some(make_date(2023, 6, 1))),
+                make_episode_with_date("2024", Some("guid-3"), Some(make_date(2024, 1, 1))),
+                make_episode_with_date("Undated", Some("guid-4"), None),
+            ]
+        };
+        let titles = |plan: SyncPlan| {
+            plan.to_download
+                .into_iter()
+                .map(|episode| episode.title)
+                .collect::<Vec<_>>()
+        };
+        let plan = |after: Option<i32>, before: Option<i32>| {
+            let date = |year| Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+            let filter = EpisodeFilter {
+                published_after: after.map(date),
+                published_before: before.map(date),
+                ..EpisodeFilter::default()
+            };
+            create_sync_plan(episodes(), &state, &filter, DownloadOrder::NewestFirst)
+        };
+
+        assert_eq!(titles(plan(Some(2023), Some(2024))), vec!["2023"]);
+        assert_eq!(titles(plan(Some(2023), None)), vec!["2024", "2023"]);
+        assert_eq!(titles(plan(None, Some(2023))), vec!["2022"]);
+    }
 }
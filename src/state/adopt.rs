@@ -0,0 +1,226 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Adopt audio files downloaded by other tools
+
+use std::collections::HashSet;
+
+use super::{OutputState, normalize_guid};
+use crate::episode::{is_valid_audio_extension, original_stem, slug};
+use crate::error::StateError;
+use crate::feed::Episode;
+use crate::hash::HashAlgorithm;
+use crate::metadata::{EpisodeMetadata, hash_file, read_file_attributes, save_episode_metadata};
+
+/// Write episode metadata for audio files that have none, so they count as
+/// downloaded
+///
+/// Meant for output directories filled by another tool. Each file without a
+/// metadata file of the same name is matched against the feed's episodes
+/// not downloaded yet:
+///
+/// 1. by the GUID in the file's extended attributes, written by a sync with
+///    [`write_xattrs`](crate::SyncOptions::write_xattrs); such files are
+///    adopted even though scans already count their episode as downloaded;
+/// 2. by name: the file is named like the enclosure (`episode-42.mp3`), or
+///    its name contains the episode title (`2024-01-15 - Title.mp3`),
+///    ignoring case, punctuation and accents;
+/// 3. otherwise by size: the file is the only one with exactly the
+///    enclosure length the feed announces.
+///
+/// Ambiguous matches are left alone. Adopted files are hashed with
+/// `algorithm`, and their GUIDs added to the state. Episodes without a GUID
+/// cannot be tracked and are never adopted. Returns the number of adopted
+/// files.
+pub fn adopt_downloads(
+    episodes: &[Episode],
+    state: &mut OutputState,
+    algorithm: HashAlgorithm,
+) -> Result<usize, StateError> {
+    let mut candidates: Vec<Candidate> = state
+        .existing_files
+        .iter()
+        .filter_map(|relative| Candidate::new(relative, state))
+        .collect();
+    candidates.sort_by(|a, b| a.relative.cmp(&b.relative));
+
+    let mut adopted = 0;
+    let mut taken = HashSet::new();
+
+    for episode in episodes {
+        let Some(guid) = episode.guid.as_deref().map(normalize_guid) else {
+            continue;
+        };
+
+        let free = || {
+            candidates
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !taken.contains(index))
+        };
+        let by_guid: Vec<_> = free()
+            .filter(|(_, candidate)| {
+                candidate
+                    .guid
+                    .as_deref()
+                    .is_some_and(|candidate| normalize_guid(candidate) == guid)
+            })
+            .collect();
+        if by_guid.is_empty() && state.is_downloaded(episode) {
+            continue;
+        }
+        let by_name: Vec<_> = free()
+            .filter(|(_, candidate)| candidate.matches_name(episode))
+            .collect();
+        let by_size: Vec<_> = free()
+            .filter(|(_, candidate)| {
+                episode.enclosure.length.is_some_and(|length| length > 0)
+                    && candidate.size == episode.enclosure.length
+            })
+            .collect();
+        let (index, candidate) = match (&by_guid[..], &by_name[..], &by_size[..]) {
+            ([single], _, _) | ([], [single], _) | ([], [], [single]) => *single,
+            _ => continue,
+        };
+
+        let audio_path = state.output_dir.join(&candidate.relative);
+        let content_hash = hash_file(&audio_path, algorithm)?;
+        let mut metadata =
+            EpisodeMetadata::from_episode(episode, &candidate.filename, Some(content_hash));
+        metadata.size = candidate.size;
+        let metadata_relative = format!("{}.json", candidate.relative_stem);
+        save_episode_metadata(&metadata, &state.output_dir.join(&metadata_relative))?;
+
+        state.record(&metadata_relative, (&metadata).into());
+        state.existing_files.insert(metadata_relative);
+        taken.insert(index);
+        adopted += 1;
+    }
+
+    Ok(adopted)
+}
+
+/// An audio file without episode metadata
+struct Candidate {
+    /// Path relative to the output directory
+    relative: String,
+    relative_stem: String,
+    filename: String,
+    /// Slug of the filename without extension
+    slug: String,
+    size: Option<u64>,
+    /// GUID from the file's extended attributes
+    guid: Option<String>,
+}
+
+impl Candidate {
+    fn new(relative: &str, state: &OutputState) -> Option<Self> {
+        let (relative_stem, extension) = relative.rsplit_once('.')?;
+        if !is_valid_audio_extension(extension)
+            || state
+                .existing_files
+                .contains(&format!("{}.json", relative_stem))
+        {
+            return None;
+        }
+
+        let filename = relative.rsplit('/').next().unwrap_or(relative);
+        let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+        let path = state.output_dir.join(relative);
+        Some(Self {
+            relative: relative.to_string(),
+            relative_stem: relative_stem.to_string(),
+            filename: filename.to_string(),
+            slug: slug(stem),
+            size: std::fs::metadata(&path).map(|file| file.len()).ok(),
+            guid: read_file_attributes(&path).guid,
+        })
+    }
+
+    fn matches_name(&self, episode: &Episode) -> bool {
+        let original = slug(&original_stem(episode));
+        let title = slug(&episode.title);
+        (!original.is_empty() && self.slug == original)
+            || (!title.is_empty() && format!("-{}-", self.slug).contains(&format!("-{}-", title)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use crate::metadata::read_episode_metadata;
+    use crate::progress::NoopReporter;
+    use crate::state::scan_output_dir;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn episode(title: &str, url: &str, length: Option<u64>) -> Episode {
+        Episode {
+            title: title.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(title.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse(url).unwrap(),
+                length,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn adopts_files_matching_by_name_or_size() {
+        let dir = tempdir().unwrap();
+        let files = [
+            ("2024-01-15 - Épisode 1.mp3", "one"),
+            ("old/ep-two.mp3", "two"),
+            ("unnamed.mp3", "three"),
+            ("Episode 10.mp3", "ten"),
+        ];
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let episodes = [
+            episode("Episode 1", "https://example.com/a.mp3", None),
+            episode("Second", "https://example.com/Ep%20Two.mp3", None),
+            episode("Third", "https://example.com/c.mp3", Some(5)),
+            episode("Missing", "https://example.com/d.mp3", Some(9)),
+        ];
+
+        let mut state = scan_output_dir(dir.path(), &NoopReporter::shared()).unwrap();
+        let adopted = adopt_downloads(&episodes, &mut state, HashAlgorithm::Sha256).unwrap();
+
+        assert_eq!(adopted, 3);
+        let metadata =
+            read_episode_metadata(&dir.path().join("2024-01-15 - Épisode 1.json")).unwrap();
+        assert_eq!(metadata.guid.as_deref(), Some("Episode 1"));
+        assert_eq!(metadata.audio_filename, "2024-01-15 - Épisode 1.mp3");
+        assert_eq!(metadata.size, Some(3));
+        assert!(metadata.content_hash.unwrap().starts_with("sha256:"));
+        let metadata = read_episode_metadata(&dir.path().join("old/ep-two.json")).unwrap();
+        assert_eq!(metadata.guid.as_deref(), Some("Second"));
+        let metadata = read_episode_metadata(&dir.path().join("unnamed.json")).unwrap();
+        assert_eq!(metadata.guid.as_deref(), Some("Third"));
+        assert!(!dir.path().join("Episode 10.json").exists());
+
+        // A rescan finds them as downloaded, with nothing left to adopt
+        let mut state = scan_output_dir(dir.path(), &NoopReporter::shared()).unwrap();
+        assert_eq!(state.downloaded_guids.len(), 3);
+        assert_eq!(
+            adopt_downloads(&episodes, &mut state, HashAlgorithm::Sha256).unwrap(),
+            0
+        );
+    }
+}
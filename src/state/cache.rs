@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Cache of the keys read from episode metadata files
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use super::{EpisodeKeys, relative_name};
+use crate::metadata::read_episode_metadata;
+
+/// Name of the scan cache file in the output directory
+///
+/// Being hidden and not ending in `.json`, it is never mistaken for an
+/// episode.
+pub const SCAN_CACHE_FILENAME: &str = ".podpull-scan.cache";
+
+/// Keys of a metadata file, with the size and modification time they were
+/// read at
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFile {
+    len: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    keys: EpisodeKeys,
+}
+
+/// Keys of the episode metadata files of an output directory, by their
+/// path relative to it
+///
+/// Spares a scan from reading and parsing every metadata file; files are
+/// only read again once their size or modification time changes. Lookups
+/// may run on several threads.
+#[derive(Debug, Default)]
+pub(crate) struct ScanCache {
+    files: Mutex<HashMap<String, CachedFile>>,
+    changed: AtomicBool,
+}
+
+impl ScanCache {
+    /// Load the cache of an output directory; a missing or damaged cache
+    /// is an empty one
+    pub(crate) fn load(output_dir: &Path) -> Self {
+        let files = std::fs::read(output_dir.join(SCAN_CACHE_FILENAME))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            files: Mutex::new(files),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// Keys of a metadata file, read from it unless cached for its current
+    /// size and modification time
+    ///
+    /// None for unreadable metadata files.
+    pub(crate) fn keys(&self, output_dir: &Path, path: &Path) -> Option<EpisodeKeys> {
+        let relative = relative_name(path, output_dir);
+        let Ok(file) = std::fs::metadata(path) else {
+            self.remove(&relative);
+            return None;
+        };
+        let modified = file
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        if let Some(cached) = self.files.lock().unwrap().get(&relative)
+            && cached.len == file.len()
+            && cached.modified_secs == modified.as_secs()
+            && cached.modified_nanos == modified.subsec_nanos()
+        {
+            return Some(cached.keys.clone());
+        }
+
+        let Ok(metadata) = read_episode_metadata(path) else {
+            self.remove(&relative);
+            return None;
+        };
+        let keys = EpisodeKeys::from(&metadata);
+        self.changed.store(true, Ordering::Relaxed);
+        self.files.lock().unwrap().insert(
+            relative,
+            CachedFile {
+                len: file.len(),
+                modified_secs: modified.as_secs(),
+                modified_nanos: modified.subsec_nanos(),
+                keys: keys.clone(),
+            },
+        );
+        Some(keys)
+    }
+
+    fn remove(&self, relative: &str) {
+        if self.files.lock().unwrap().remove(relative).is_some() {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop the entries of metadata files other than `present`
+    pub(crate) fn retain(&mut self, present: &HashSet<String>) {
+        let files = self.files.get_mut().unwrap();
+        let count = files.len();
+        files.retain(|relative, _| present.contains(relative));
+        if files.len() != count {
+            *self.changed.get_mut() = true;
+        }
+    }
+
+    /// Write the cache into the output directory, if anything changed
+    pub(crate) fn save(&self, output_dir: &Path) -> std::io::Result<()> {
+        if !self.changed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let files = self.files.lock().unwrap();
+        let json = serde_json::to_string(&*files).map_err(std::io::Error::other)?;
+        std::fs::write(output_dir.join(SCAN_CACHE_FILENAME), json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use crate::progress::NoopReporter;
+    use crate::state::scan_output_dir;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_episode(path: &Path, guid: &str) {
+        let episode = Episode {
+            title: guid.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(guid.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        write_episode_metadata(&episode, "ep.mp3", None, path).unwrap();
+    }
+
+    #[test]
+    fn rereads_only_changed_metadata_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ep.json");
+        write_episode(&path, "original");
+        let scan = || scan_output_dir(dir.path(), &NoopReporter::shared()).unwrap();
+        assert!(scan().downloaded_guids.contains("original"));
+
+        // Unchanged files are not read again, so a doctored cache shows
+        let cache_path = dir.path().join(SCAN_CACHE_FILENAME);
+        let cache = std::fs::read_to_string(&cache_path).unwrap();
+        std::fs::write(
+            &cache_path,
+            cache.replace(r#""guid":"original""#, r#""guid":"cached""#),
+        )
+        .unwrap();
+        assert!(scan().downloaded_guids.contains("cached"));
+
+        write_episode(&path, "rewritten");
+        assert!(scan().downloaded_guids.contains("rewritten"));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(scan().downloaded_guids.is_empty());
+        let cache = std::fs::read_to_string(&cache_path).unwrap();
+        assert!(!cache.contains("rewritten"));
+    }
+}
@@ -0,0 +1,222 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! History of failed episode downloads
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{canonical_enclosure_url, normalize_guid};
+use crate::feed::Episode;
+
+/// Name of the failure history file in the output directory
+///
+/// Being hidden and not ending in `.json`, it is never mistaken for an
+/// episode.
+pub const FAILURES_FILENAME: &str = ".podpull-failures";
+
+/// Longest wait before a failing episode is tried again
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Failed download attempts of an episode since it last downloaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub episode_title: String,
+    /// Number of syncs in a row that failed to download the episode
+    pub attempts: u32,
+    /// Why the last attempt failed
+    pub last_error: String,
+    /// RFC 3339 timestamp in UTC
+    pub last_attempt: String,
+}
+
+impl FailureRecord {
+    /// When the episode is due to be tried again
+    ///
+    /// The wait starts at `cool_down` and doubles with every further
+    /// failed attempt, up to 30 days. None if the last attempt time is
+    /// unreadable.
+    pub fn retry_after(&self, cool_down: Duration) -> Option<DateTime<Utc>> {
+        let last_attempt = DateTime::parse_from_rfc3339(&self.last_attempt).ok()?;
+        let factor = 1u32 << self.attempts.saturating_sub(1).min(16);
+        let wait = cool_down.saturating_mul(factor).min(MAX_BACKOFF);
+        Some(last_attempt.with_timezone(&Utc) + wait)
+    }
+}
+
+/// Read the failure history of an output directory, by episode GUID (see
+/// [`normalize_guid`]) or,
+/// for episodes without one, canonical enclosure URL
+///
+/// A missing or damaged history is an empty one.
+pub fn read_failure_history(output_dir: &Path) -> BTreeMap<String, FailureRecord> {
+    std::fs::read(output_dir.join(FAILURES_FILENAME))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Key of an episode in the failure history
+pub(crate) fn failure_key(episode: &Episode) -> String {
+    match &episode.guid {
+        Some(guid) => normalize_guid(guid),
+        None => canonical_enclosure_url(episode.enclosure.url.as_str()),
+    }
+}
+
+/// The failure history of an output directory, updated by the downloads
+/// of a sync
+#[derive(Debug, Default)]
+pub(crate) struct FailureHistory {
+    records: Mutex<BTreeMap<String, FailureRecord>>,
+    changed: AtomicBool,
+}
+
+impl FailureHistory {
+    pub(crate) fn load(output_dir: &Path) -> Self {
+        Self {
+            records: Mutex::new(read_failure_history(output_dir)),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether `episode` failed recently enough to be left out at `now`
+    pub(crate) fn is_cooling_down(
+        &self,
+        episode: &Episode,
+        cool_down: Duration,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.records
+            .lock()
+            .unwrap()
+            .get(&failure_key(episode))
+            .and_then(|record| record.retry_after(cool_down))
+            .is_some_and(|retry_after| now < retry_after)
+    }
+
+    /// Count a failed attempt to download the episode of `key`
+    pub(crate) fn record_failure(&self, key: &str, title: &str, error: &str) {
+        let mut records = self.records.lock().unwrap();
+        let record = records
+            .entry(key.to_string())
+            .or_insert_with(|| FailureRecord {
+                episode_title: String::new(),
+                attempts: 0,
+                last_error: String::new(),
+                last_attempt: String::new(),
+            });
+        record.episode_title = title.to_string();
+        record.attempts = record.attempts.saturating_add(1);
+        record.last_error = error.to_string();
+        record.last_attempt = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        self.changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Forget the failures of the episode of `key`, once it downloaded
+    pub(crate) fn clear(&self, key: &str) {
+        if self.records.lock().unwrap().remove(key).is_some() {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Forget the failures of episodes other than `pending`, which are
+    /// present by now or gone from the feed
+    pub(crate) fn retain<'a>(&self, pending: impl IntoIterator<Item = &'a Episode>) {
+        let pending: HashSet<_> = pending.into_iter().map(failure_key).collect();
+        let mut records = self.records.lock().unwrap();
+        let count = records.len();
+        records.retain(|key, _| pending.contains(key));
+        if records.len() != count {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Write the history into the output directory, if anything changed
+    ///
+    /// An empty history removes the file.
+    pub(crate) fn save(&self, output_dir: &Path) -> std::io::Result<()> {
+        if !self.changed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let path = output_dir.join(FAILURES_FILENAME);
+        let records = self.records.lock().unwrap();
+        if records.is_empty() {
+            return match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let json = serde_json::to_string_pretty(&*records).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn episode(guid: Option<&str>) -> Episode {
+        Episode {
+            title: "Episode".to_string(),
+            description: None,
+            pub_date: None,
+            guid: guid.map(String::from),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn backs_off_longer_with_each_failure() {
+        let dir = tempdir().unwrap();
+        let episode = episode(Some("ep-1"));
+        let hour = Duration::from_secs(3600);
+        let history = FailureHistory::load(dir.path());
+        history.record_failure("ep-1", "Episode", "HTTP 500");
+        history.record_failure("ep-1", "Episode", "HTTP 404");
+        history.save(dir.path()).unwrap();
+
+        let record = read_failure_history(dir.path()).remove("ep-1").unwrap();
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.last_error, "HTTP 404");
+        let last_attempt = DateTime::parse_from_rfc3339(&record.last_attempt).unwrap();
+        assert_eq!(
+            record.retry_after(hour).unwrap(),
+            last_attempt.with_timezone(&Utc) + hour * 2
+        );
+
+        let history = FailureHistory::load(dir.path());
+        let now = Utc::now();
+        assert!(history.is_cooling_down(&episode, hour, now));
+        assert!(!history.is_cooling_down(&episode, hour, now + hour * 3));
+        // Episodes without a GUID are known by their enclosure
+        assert!(!history.is_cooling_down(&self::episode(None), hour, now));
+
+        history.clear(&failure_key(&episode));
+        history.save(dir.path()).unwrap();
+        assert!(!dir.path().join(FAILURES_FILENAME).exists());
+    }
+}
@@ -0,0 +1,141 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Select the feed episodes a sync downloads
+
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::feed::Episode;
+use crate::metadata::parse_itunes_duration;
+
+/// Criteria new episodes must meet to be downloaded, see
+/// [`create_sync_plan`](super::create_sync_plan)
+///
+/// The default lets every episode through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpisodeFilter {
+    /// Only episodes published at or after this instant
+    pub published_after: Option<DateTime<Utc>>,
+    /// Only episodes published before this instant
+    pub published_before: Option<DateTime<Utc>>,
+    /// Only episodes at least this long, by their `itunes:duration`
+    pub min_duration: Option<Duration>,
+    /// Only episodes at most this long, by their `itunes:duration`
+    pub max_duration: Option<Duration>,
+    /// Only episodes of these seasons, by their `itunes:season`
+    pub seasons: Option<Vec<u32>>,
+    /// Only episodes numbered within this range, by their `itunes:episode`
+    pub episode_numbers: Option<RangeInclusive<u32>>,
+}
+
+impl EpisodeFilter {
+    /// Whether `episode` meets all criteria
+    ///
+    /// Once a date bound is set, episodes without a publication date are
+    /// left out, and likewise for season and episode numbers. Episodes
+    /// without a readable duration pass the duration bounds, as their
+    /// length is unknown rather than wrong.
+    pub fn matches(&self, episode: &Episode) -> bool {
+        if let Some(seasons) = &self.seasons
+            && !episode
+                .season_number
+                .is_some_and(|season| seasons.contains(&season))
+        {
+            return false;
+        }
+        if let Some(numbers) = &self.episode_numbers
+            && !episode
+                .episode_number
+                .is_some_and(|number| numbers.contains(&number))
+        {
+            return false;
+        }
+        if self.published_after.is_some() || self.published_before.is_some() {
+            let in_range = episode.pub_date.is_some_and(|date| {
+                self.published_after.is_none_or(|after| date >= after)
+                    && self.published_before.is_none_or(|before| date < before)
+            });
+            if !in_range {
+                return false;
+            }
+        }
+
+        let seconds = episode.duration.as_deref().and_then(parse_itunes_duration);
+        seconds.is_none_or(|seconds| {
+            let duration = Duration::from_secs(seconds);
+            self.min_duration.is_none_or(|min| duration >= min)
+                && self.max_duration.is_none_or(|max| duration <= max)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use std::collections::BTreeMap;
+    use url::Url;
+
+    fn episode(duration: Option<&str>) -> Episode {
+        Episode {
+            title: "Episode".to_string(),
+            description: None,
+            pub_date: None,
+            guid: None,
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: duration.map(String::from),
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn filters_by_duration() {
+        let filter = EpisodeFilter {
+            min_duration: Some(Duration::from_secs(120)),
+            max_duration: Some(Duration::from_secs(3600)),
+            ..EpisodeFilter::default()
+        };
+
+        assert!(!filter.matches(&episode(Some("01:30"))));
+        assert!(filter.matches(&episode(Some("2:00"))));
+        assert!(filter.matches(&episode(Some("1:00:00"))));
+        assert!(!filter.matches(&episode(Some("3601"))));
+        // Unknown lengths are kept
+        assert!(filter.matches(&episode(None)));
+        assert!(filter.matches(&episode(Some("soon"))));
+    }
+
+    #[test]
+    fn filters_by_season_and_episode_number() {
+        let numbered = |season, number| Episode {
+            season_number: season,
+            episode_number: number,
+            ..episode(None)
+        };
+        let filter = EpisodeFilter {
+            seasons: Some(vec![3, 4]),
+            episode_numbers: Some(2..=5),
+            ..EpisodeFilter::default()
+        };
+
+        assert!(filter.matches(&numbered(Some(3), Some(2))));
+        assert!(filter.matches(&numbered(Some(4), Some(5))));
+        assert!(!filter.matches(&numbered(Some(2), Some(3))));
+        assert!(!filter.matches(&numbered(Some(3), Some(6))));
+        assert!(!filter.matches(&numbered(None, Some(3))));
+        assert!(!filter.matches(&numbered(Some(3), None)));
+    }
+}
@@ -0,0 +1,211 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Remove downloaded episodes that are no longer in the feed
+
+use std::collections::HashSet;
+
+use super::{OutputState, canonical_enclosure_url, normalize_guid, relative_name};
+use crate::error::{MetadataError, StateError};
+use crate::feed::Episode;
+use crate::metadata::{
+    EpisodeMetadata, archive_episode, episode_files, is_episode_metadata_filename,
+    read_episode_metadata,
+};
+
+/// What to do with downloaded episodes that are no longer in the feed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrunePolicy {
+    /// Keep them, archiving everything ever published
+    #[default]
+    Keep,
+    /// Delete their files, mirroring the feed
+    Delete,
+    /// Move their files to [`ARCHIVE_DIR`](crate::metadata::ARCHIVE_DIR)
+    Archive,
+}
+
+/// A downloaded episode removed by [`prune_removed_episodes`] or retired
+/// by [`apply_retention`](super::apply_retention)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedEpisode {
+    pub title: String,
+    /// Its metadata file, relative to the output directory
+    pub metadata_path: String,
+}
+
+/// Delete or archive downloaded episodes that are not among `episodes`,
+/// the feed's episodes
+///
+/// Episodes are matched by GUID or enclosure URL, as in
+/// [`create_sync_plan`](super::create_sync_plan). Besides the audio and
+/// metadata file, sidecars such as chapters and show notes are removed.
+/// Nothing is removed for a feed without episodes, which is more likely
+/// broken than emptied.
+pub fn prune_removed_episodes(
+    episodes: &[Episode],
+    state: &mut OutputState,
+    policy: PrunePolicy,
+) -> Result<Vec<PrunedEpisode>, StateError> {
+    if policy == PrunePolicy::Keep {
+        return Ok(Vec::new());
+    }
+
+    let mut pruned = Vec::new();
+    for (relative, metadata) in removed_episodes(episodes, state) {
+        let path = state.output_dir.join(&relative);
+        let files = episode_files(&path, &metadata);
+        if policy == PrunePolicy::Archive {
+            archive_episode(&path, &state.output_dir)?;
+        } else {
+            for file in &files {
+                std::fs::remove_file(file).map_err(|e| MetadataError::WriteFailed {
+                    path: file.clone(),
+                    source: e,
+                })?;
+            }
+        }
+        for file in &files {
+            state
+                .existing_files
+                .remove(&relative_name(file, &state.output_dir));
+        }
+
+        pruned.push(PrunedEpisode {
+            title: metadata.title,
+            metadata_path: relative,
+        });
+    }
+
+    Ok(pruned)
+}
+
+/// The downloaded episodes [`prune_removed_episodes`] would remove,
+/// leaving the output directory untouched
+pub fn find_removed_episodes(episodes: &[Episode], state: &OutputState) -> Vec<PrunedEpisode> {
+    removed_episodes(episodes, state)
+        .into_iter()
+        .map(|(metadata_path, metadata)| PrunedEpisode {
+            title: metadata.title,
+            metadata_path,
+        })
+        .collect()
+}
+
+/// Metadata files, and their contents, of the downloaded episodes that are
+/// not among `episodes`
+fn removed_episodes(episodes: &[Episode], state: &OutputState) -> Vec<(String, EpisodeMetadata)> {
+    if episodes.is_empty() {
+        return Vec::new();
+    }
+
+    let guids: HashSet<_> = episodes
+        .iter()
+        .filter_map(|e| e.guid.as_deref().map(normalize_guid))
+        .collect();
+    let urls: HashSet<_> = episodes
+        .iter()
+        .map(|e| canonical_enclosure_url(e.enclosure.url.as_str()))
+        .collect();
+
+    let mut metadata_files: Vec<_> = state
+        .existing_files
+        .iter()
+        .filter(|relative| {
+            is_episode_metadata_filename(relative.rsplit('/').next().unwrap_or(relative))
+        })
+        .cloned()
+        .collect();
+    metadata_files.sort();
+
+    metadata_files
+        .into_iter()
+        .filter_map(|relative| {
+            let metadata = read_episode_metadata(&state.output_dir.join(&relative)).ok()?;
+            let in_feed = metadata
+                .guid
+                .as_ref()
+                .is_some_and(|guid| guids.contains(&normalize_guid(guid)))
+                || urls.contains(&canonical_enclosure_url(&metadata.original_url));
+            (!in_feed).then_some((relative, metadata))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use crate::metadata::{ARCHIVE_DIR, write_episode_metadata};
+    use crate::progress::NoopReporter;
+    use crate::state::scan_output_dir;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn episode(name: &str) -> Episode {
+        Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(name.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse(&format!("https://example.com/{}.mp3", name)).unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn removes_episodes_missing_from_the_feed() {
+        let dir = tempdir().unwrap();
+        for name in ["kept", "removed", "archived"] {
+            let audio = format!("{}.mp3", name);
+            write_episode_metadata(
+                &episode(name),
+                &audio,
+                None,
+                &dir.path().join(format!("{}.json", name)),
+            )
+            .unwrap();
+            std::fs::write(dir.path().join(&audio), b"audio").unwrap();
+        }
+        std::fs::write(dir.path().join("removed.chapters.json"), "{}").unwrap();
+        let mut state = scan_output_dir(dir.path(), &NoopReporter::shared()).unwrap();
+
+        let feed = [episode("kept"), episode("archived")];
+        assert_eq!(find_removed_episodes(&feed, &state).len(), 1);
+        assert!(dir.path().join("removed.mp3").exists());
+        let pruned = prune_removed_episodes(&feed, &mut state, PrunePolicy::Delete).unwrap();
+        assert_eq!(
+            pruned,
+            vec![PrunedEpisode {
+                title: "removed".to_string(),
+                metadata_path: "removed.json".to_string(),
+            }]
+        );
+        assert!(!dir.path().join("removed.mp3").exists());
+        assert!(!dir.path().join("removed.chapters.json").exists());
+        assert!(!state.existing_files.contains("removed.mp3"));
+
+        let feed = [episode("kept")];
+        let pruned = prune_removed_episodes(&feed, &mut state, PrunePolicy::Archive).unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert!(dir.path().join(ARCHIVE_DIR).join("archived.mp3").exists());
+        assert!(dir.path().join("kept.mp3").exists());
+
+        // An empty feed is taken for a broken one
+        let pruned = prune_removed_episodes(&[], &mut state, PrunePolicy::Delete).unwrap();
+        assert!(pruned.is_empty());
+        assert!(dir.path().join("kept.mp3").exists());
+    }
+}
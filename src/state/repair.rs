@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rebuild lost or damaged episode metadata
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::{adopt_downloads, scan_output_dir};
+use crate::episode::is_valid_audio_extension;
+use crate::error::{MetadataError, StateError};
+use crate::feed::Episode;
+use crate::hash::HashAlgorithm;
+use crate::metadata::{
+    hash_file, is_episode_metadata_filename, read_episode_metadata, save_episode_metadata,
+};
+use crate::progress::NoopReporter;
+
+/// Suffix appended to episode metadata files that cannot be parsed
+pub const CORRUPT_SUFFIX: &str = ".corrupt";
+
+/// Outcome of [`repair_output_dir`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Unreadable metadata files, renamed with [`CORRUPT_SUFFIX`]
+    pub corrupt: Vec<PathBuf>,
+    /// Metadata files whose audio file is gone, which were removed
+    pub orphaned: Vec<PathBuf>,
+    /// Metadata files that gained the missing hash of their audio file
+    pub rehashed: Vec<PathBuf>,
+    /// Number of audio files that got new metadata from the feed
+    pub rebuilt: usize,
+    /// Audio files still without metadata, matching no feed episode
+    pub unmatched: Vec<PathBuf>,
+}
+
+/// Reconcile episode metadata files with the audio files of an output directory
+///
+/// - Metadata files that cannot be parsed are renamed aside, so their
+///   audio file counts as lacking metadata.
+/// - Metadata files whose audio file is gone are removed, so the episode
+///   is downloaded again by the next sync, unless a retention policy
+///   deleted the audio file.
+/// - Metadata without a content hash gets one, computed with `algorithm`.
+/// - Audio files without metadata are matched against `episodes`, the
+///   feed's episodes, as in [`adopt_downloads`], and get new metadata.
+///
+/// Without this, a lost metadata file means the episode is downloaded a
+/// second time under another name.
+pub fn repair_output_dir(
+    episodes: &[Episode],
+    output_dir: &Path,
+    algorithm: HashAlgorithm,
+) -> Result<RepairReport, StateError> {
+    let mut report = RepairReport::default();
+    let reporter = NoopReporter::shared();
+
+    let state = scan_output_dir(output_dir, &reporter)?;
+    let mut metadata_files: Vec<_> = state
+        .existing_files
+        .iter()
+        .filter(|relative| is_episode_metadata_filename(file_name(relative)))
+        .map(|relative| output_dir.join(relative))
+        .collect();
+    metadata_files.sort();
+
+    // Audio files some intact metadata file refers to
+    let mut described = HashSet::new();
+    for path in metadata_files {
+        let Ok(mut metadata) = read_episode_metadata(&path) else {
+            let mut corrupt = path.clone().into_os_string();
+            corrupt.push(CORRUPT_SUFFIX);
+            std::fs::rename(&path, &corrupt).map_err(write_failed(&path))?;
+            report.corrupt.push(path);
+            continue;
+        };
+        if metadata.retired_at.is_some() {
+            continue;
+        }
+
+        let audio_path = path
+            .parent()
+            .unwrap_or(output_dir)
+            .join(&metadata.audio_filename);
+        if !audio_path.is_file() {
+            std::fs::remove_file(&path).map_err(write_failed(&path))?;
+            report.orphaned.push(path);
+            continue;
+        }
+        described.insert(audio_path.clone());
+
+        if metadata.content_hash.is_none() {
+            metadata.content_hash = Some(hash_file(&audio_path, algorithm)?);
+            save_episode_metadata(&metadata, &path)?;
+            report.rehashed.push(path);
+        }
+    }
+
+    let mut state = scan_output_dir(output_dir, &reporter)?;
+    report.rebuilt = adopt_downloads(episodes, &mut state, algorithm)?;
+
+    report.unmatched = state
+        .existing_files
+        .iter()
+        .filter(|relative| {
+            relative.rsplit_once('.').is_some_and(|(stem, extension)| {
+                is_valid_audio_extension(extension)
+                    && !state.existing_files.contains(&format!("{}.json", stem))
+            })
+        })
+        .map(|relative| output_dir.join(relative))
+        .filter(|path| !described.contains(path))
+        .collect();
+    report.unmatched.sort();
+
+    Ok(report)
+}
+
+fn file_name(relative: &str) -> &str {
+    relative.rsplit('/').next().unwrap_or(relative)
+}
+
+fn write_failed(path: &Path) -> impl FnOnce(std::io::Error) -> MetadataError {
+    let path = path.to_path_buf();
+    move |e| MetadataError::WriteFailed { path, source: e }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::Enclosure;
+    use crate::metadata::write_episode_metadata;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn episode(title: &str) -> Episode {
+        Episode {
+            title: title.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(title.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse(&format!("https://example.com/{}.mp3", title)).unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn repairs_damaged_missing_and_unhashed_metadata() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        let episodes = [episode("Damaged"), episode("Lost"), episode("Unhashed")];
+
+        std::fs::write(path("undated-Damaged.mp3"), b"damaged").unwrap();
+        std::fs::write(path("undated-Damaged.json"), "{").unwrap();
+        std::fs::write(path("undated-Lost.mp3"), b"lost").unwrap();
+        write_episode_metadata(&episodes[2], "undated-Unhashed.mp3", None, &path("u.json"))
+            .unwrap();
+        std::fs::write(path("undated-Unhashed.mp3"), b"unhashed").unwrap();
+        write_episode_metadata(&episode("Gone"), "gone.mp3", None, &path("gone.json")).unwrap();
+        std::fs::write(path("stray.mp3"), b"stray").unwrap();
+
+        let report = repair_output_dir(&episodes, dir.path(), HashAlgorithm::Sha256).unwrap();
+
+        assert_eq!(report.corrupt, vec![path("undated-Damaged.json")]);
+        assert_eq!(report.orphaned, vec![path("gone.json")]);
+        assert_eq!(report.rehashed, vec![path("u.json")]);
+        assert_eq!(report.rebuilt, 2);
+        assert_eq!(report.unmatched, vec![path("stray.mp3")]);
+
+        assert!(path("undated-Damaged.json.corrupt").exists());
+        let rebuilt = read_episode_metadata(&path("undated-Damaged.json")).unwrap();
+        assert_eq!(rebuilt.guid.as_deref(), Some("Damaged"));
+        let rebuilt = read_episode_metadata(&path("undated-Lost.json")).unwrap();
+        assert!(rebuilt.content_hash.is_some());
+        let rehashed = read_episode_metadata(&path("u.json")).unwrap();
+        assert!(rehashed.content_hash.is_some());
+    }
+}
@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Delete the oldest episodes beyond retention limits
+
+use std::cmp::Reverse;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::{PrunedEpisode, relative_name};
+use crate::error::{MetadataError, StateError};
+use crate::metadata::{
+    episode_files, episode_metadata_files, read_episode_metadata, save_episode_metadata,
+};
+use crate::units::ByteSize;
+
+/// Limits on the downloaded episodes kept in an output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Keep at most this many episodes
+    pub keep_last: Option<usize>,
+    /// Keep episodes published at most this long ago
+    pub max_age: Option<Duration>,
+    /// Keep the newest episodes whose audio files fit into this size
+    pub max_total_size: Option<ByteSize>,
+}
+
+impl RetentionPolicy {
+    /// Whether no limit is set, so every episode is kept
+    pub fn is_unlimited(&self) -> bool {
+        self.keep_last.is_none() && self.max_age.is_none() && self.max_total_size.is_none()
+    }
+}
+
+/// Delete the audio files of the oldest episodes beyond `policy`
+///
+/// Episodes are ordered by publication date, or else by download time;
+/// episodes with neither count as newest and never expire. Sidecars go
+/// with the audio file, but the metadata stays, with
+/// [`retired_at`](crate::metadata::EpisodeMetadata::retired_at) set, so
+/// retired episodes are not downloaded again. Returns the retired episodes.
+pub fn apply_retention(
+    output_dir: &Path,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<Vec<PrunedEpisode>, StateError> {
+    if policy.is_unlimited() {
+        return Ok(Vec::new());
+    }
+
+    let mut episodes = Vec::new();
+    for path in episode_metadata_files(output_dir)? {
+        let Ok(metadata) = read_episode_metadata(&path) else {
+            continue;
+        };
+        let audio_path = path
+            .parent()
+            .unwrap_or(output_dir)
+            .join(&metadata.audio_filename);
+        let Ok(file) = std::fs::metadata(&audio_path) else {
+            continue;
+        };
+        if metadata.retired_at.is_some() {
+            continue;
+        }
+        let date = metadata
+            .pub_date
+            .as_deref()
+            .or(metadata.downloaded_at.as_deref())
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc));
+        episodes.push((date, file.len(), path, metadata));
+    }
+    // Newest first, undated ones before all others
+    episodes.sort_by_key(|(date, ..)| Reverse(date.unwrap_or(DateTime::<Utc>::MAX_UTC)));
+
+    let max_age = policy
+        .max_age
+        .map(|age| TimeDelta::from_std(age).unwrap_or(TimeDelta::MAX));
+    let mut kept_bytes: u64 = 0;
+    let mut retiring = false;
+    let mut retired = Vec::new();
+
+    for (index, (date, bytes, path, mut metadata)) in episodes.into_iter().enumerate() {
+        retiring = retiring
+            || policy.keep_last.is_some_and(|keep| index >= keep)
+            || max_age
+                .zip(date)
+                .is_some_and(|(max_age, date)| now.signed_duration_since(date) > max_age)
+            || policy
+                .max_total_size
+                .is_some_and(|max| kept_bytes.saturating_add(bytes) > max.bytes());
+        if !retiring {
+            kept_bytes += bytes;
+            continue;
+        }
+
+        for file in episode_files(&path, &metadata) {
+            if file != path {
+                std::fs::remove_file(&file).map_err(|e| MetadataError::WriteFailed {
+                    path: file.clone(),
+                    source: e,
+                })?;
+            }
+        }
+        metadata.retired_at = Some(now.to_rfc3339());
+        save_episode_metadata(&metadata, &path)?;
+
+        retired.push(PrunedEpisode {
+            title: metadata.title,
+            metadata_path: relative_name(&path, output_dir),
+        });
+    }
+
+    Ok(retired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_episode(dir: &Path, name: &str, pub_date: Option<&str>, bytes: usize) {
+        let episode = Episode {
+            title: name.to_string(),
+            description: None,
+            pub_date: pub_date.map(|date| DateTime::parse_from_rfc3339(date).unwrap()),
+            guid: Some(name.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse(&format!("https://example.com/{}.mp3", name)).unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        let audio = format!("{}.mp3", name);
+        write_episode_metadata(&episode, &audio, None, &dir.join(format!("{}.json", name)))
+            .unwrap();
+        std::fs::write(dir.join(audio), vec![0; bytes]).unwrap();
+    }
+
+    fn retire(dir: &Path, policy: RetentionPolicy) -> Vec<String> {
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        apply_retention(dir, &policy, now)
+            .unwrap()
+            .into_iter()
+            .map(|episode| episode.title)
+            .collect()
+    }
+
+    #[test]
+    fn retires_oldest_episodes_beyond_the_limits() {
+        let dir = tempdir().unwrap();
+        write_episode(dir.path(), "january", Some("2024-01-01T00:00:00Z"), 100);
+        write_episode(dir.path(), "february", Some("2024-02-01T00:00:00Z"), 100);
+        write_episode(dir.path(), "march", Some("2024-02-29T00:00:00Z"), 100);
+        std::fs::write(dir.path().join("january.md"), "# january").unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(45 * 86_400)),
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(retire(dir.path(), policy), vec!["january"]);
+        assert!(!dir.path().join("january.mp3").exists());
+        assert!(!dir.path().join("january.md").exists());
+        let metadata = read_episode_metadata(&dir.path().join("january.json")).unwrap();
+        assert!(metadata.retired_at.is_some());
+
+        let policy = RetentionPolicy {
+            max_total_size: Some("150".parse().unwrap()),
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(retire(dir.path(), policy), vec!["february"]);
+
+        let policy = RetentionPolicy {
+            keep_last: Some(0),
+            ..RetentionPolicy::default()
+        };
+        assert_eq!(retire(dir.path(), policy), vec!["march"]);
+    }
+}
@@ -0,0 +1,299 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! SQLite database of a library's episodes, download history and feed
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::EpisodeKeys;
+use crate::error::{MetadataError, StateError};
+use crate::metadata::{EpisodeMetadata, PodcastMetadata};
+
+/// Name of the database file in the output directory
+///
+/// Being hidden, it is never mistaken for an episode.
+pub const STORE_FILENAME: &str = ".podpull.sqlite";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS episodes (
+        metadata_path TEXT PRIMARY KEY,
+        guid TEXT,
+        audio_filename TEXT NOT NULL,
+        content_hash TEXT,
+        metadata TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS episodes_guid ON episodes (guid);
+    CREATE TABLE IF NOT EXISTS downloads (
+        id INTEGER PRIMARY KEY,
+        metadata_path TEXT NOT NULL,
+        guid TEXT,
+        recorded_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS feed (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        feed_url TEXT NOT NULL,
+        etag TEXT,
+        last_modified TEXT,
+        metadata TEXT NOT NULL
+    );
+";
+
+/// An entry of the download history, see [`LibraryStore::download_history`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadRecord {
+    /// Episode metadata file, relative to the output directory
+    pub metadata_path: String,
+    pub guid: Option<String>,
+    /// RFC 3339 timestamp in UTC
+    pub recorded_at: String,
+}
+
+/// Database holding what a sync needs to know about the output directory
+///
+/// Mirrors the per-episode metadata files, keyed by their path relative to
+/// the output directory, so a scan does not have to read and parse each of
+/// them; see [`scan_output_dir_with_store`](super::scan_output_dir_with_store).
+/// The JSON files stay authoritative: files missing from the database are
+/// imported, and entries whose file is gone are dropped.
+///
+/// Requires the `sqlite` feature.
+#[derive(Debug)]
+pub struct LibraryStore {
+    connection: Connection,
+}
+
+impl LibraryStore {
+    /// Open the database of an output directory, creating both if needed
+    pub fn open(output_dir: &Path) -> Result<Self, StateError> {
+        std::fs::create_dir_all(output_dir).map_err(|e| StateError::CreateDirectoryFailed {
+            path: output_dir.to_path_buf(),
+            source: e,
+        })?;
+        let connection = Connection::open(output_dir.join(STORE_FILENAME))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// GUIDs, enclosure URLs and content hashes of the recorded episodes,
+    /// by metadata path
+    pub fn episode_keys(&self) -> Result<HashMap<String, EpisodeKeys>, StateError> {
+        let mut statement = self.connection.prepare(
+            "SELECT metadata_path, guid, json_extract(metadata, '$.original_url'),
+                 json_extract(metadata, '$.enclosure_length'), content_hash,
+                 json_extract(metadata, '$.retired_at') IS NOT NULL
+             FROM episodes",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                EpisodeKeys {
+                    guid: row.get(1)?,
+                    original_url: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    enclosure_length: row.get(3)?,
+                    content_hash: row.get(4)?,
+                    retired: row.get(5)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Metadata of a recorded episode
+    pub fn episode(&self, metadata_path: &str) -> Result<Option<EpisodeMetadata>, StateError> {
+        let json: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT metadata FROM episodes WHERE metadata_path = ?1",
+                [metadata_path],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Record the metadata of an episode found in the output directory
+    pub fn import_episode(
+        &self,
+        metadata_path: &str,
+        metadata: &EpisodeMetadata,
+    ) -> Result<(), StateError> {
+        let json = serde_json::to_string(metadata).map_err(MetadataError::from)?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO episodes
+                (metadata_path, guid, audio_filename, content_hash, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                metadata_path,
+                metadata.guid,
+                metadata.audio_filename,
+                metadata.content_hash,
+                json
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record a finished download, adding it to the download history
+    pub fn record_download(
+        &self,
+        metadata_path: &str,
+        metadata: &EpisodeMetadata,
+    ) -> Result<(), StateError> {
+        self.import_episode(metadata_path, metadata)?;
+        self.connection.execute(
+            "INSERT INTO downloads (metadata_path, guid, recorded_at) VALUES (?1, ?2, ?3)",
+            params![
+                metadata_path,
+                metadata.guid,
+                Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Drop an episode whose metadata file is gone
+    ///
+    /// Its download history is kept.
+    pub fn forget_episode(&self, metadata_path: &str) -> Result<(), StateError> {
+        self.connection.execute(
+            "DELETE FROM episodes WHERE metadata_path = ?1",
+            [metadata_path],
+        )?;
+        Ok(())
+    }
+
+    /// All recorded downloads, oldest first
+    pub fn download_history(&self) -> Result<Vec<DownloadRecord>, StateError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT metadata_path, guid, recorded_at FROM downloads ORDER BY id")?;
+        let rows = statement.query_map([], |row| {
+            Ok(DownloadRecord {
+                metadata_path: row.get(0)?,
+                guid: row.get(1)?,
+                recorded_at: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Record the podcast metadata, including the feed's cache validators
+    pub fn save_feed(&self, metadata: &PodcastMetadata) -> Result<(), StateError> {
+        let json = serde_json::to_string(metadata).map_err(MetadataError::from)?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO feed (id, feed_url, etag, last_modified, metadata)
+             VALUES (1, ?1, ?2, ?3, ?4)",
+            params![
+                metadata.feed_url,
+                metadata.etag,
+                metadata.last_modified,
+                json
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The recorded podcast metadata
+    pub fn feed(&self) -> Result<Option<PodcastMetadata>, StateError> {
+        let json: Option<String> = self
+            .connection
+            .query_row("SELECT metadata FROM feed WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn metadata(guid: &str) -> EpisodeMetadata {
+        let episode = Episode {
+            title: guid.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(guid.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse("https://example.com/ep.mp3").unwrap(),
+                length: Some(1000),
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        EpisodeMetadata::from_episode(&episode, &format!("{}.mp3", guid), None)
+    }
+
+    #[test]
+    fn records_episodes_history_and_feed() {
+        let dir = tempdir().unwrap();
+        let store = LibraryStore::open(dir.path()).unwrap();
+
+        store.import_episode("a.json", &metadata("a")).unwrap();
+        store
+            .record_download("Season 01/b.json", &metadata("b"))
+            .unwrap();
+        store.forget_episode("a.json").unwrap();
+
+        // Reopening finds what was recorded
+        let store = LibraryStore::open(dir.path()).unwrap();
+        assert_eq!(
+            store.episode_keys().unwrap(),
+            HashMap::from([(
+                "Season 01/b.json".to_string(),
+                EpisodeKeys {
+                    guid: Some("b".to_string()),
+                    original_url: "https://example.com/ep.mp3".to_string(),
+                    enclosure_length: Some(1000),
+                    content_hash: None,
+                    retired: false,
+                }
+            )])
+        );
+        assert_eq!(
+            store
+                .episode("Season 01/b.json")
+                .unwrap()
+                .map(|metadata| metadata.audio_filename),
+            Some("b.mp3".to_string())
+        );
+        let history = store.download_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].guid.as_deref(), Some("b"));
+
+        assert!(store.feed().unwrap().is_none());
+        let feed = PodcastMetadata {
+            title: "Test Podcast".to_string(),
+            description: None,
+            link: None,
+            author: None,
+            image_url: None,
+            feed_url: "https://example.com/feed.xml".to_string(),
+            updated_at: None,
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+            funding: vec![],
+        };
+        store.save_feed(&feed).unwrap();
+        assert_eq!(
+            store.feed().unwrap().unwrap().etag.as_deref(),
+            Some("\"v1\"")
+        );
+    }
+}
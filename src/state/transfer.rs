@@ -0,0 +1,241 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Move the state of an output directory to another place
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::failures::{FAILURES_FILENAME, FailureRecord, read_failure_history};
+use super::relative_name;
+use crate::error::{MetadataError, StateError};
+use crate::metadata::{
+    EpisodeMetadata, PodcastMetadata, episode_metadata_files, read_episode_metadata,
+    read_podcast_metadata, save_episode_metadata, save_podcast_metadata,
+};
+
+/// Version of the [`StateExport`] format written by this version of podpull
+pub const STATE_EXPORT_VERSION: u32 = 1;
+
+/// The state of an output directory as one portable JSON document
+///
+/// Paths are relative to the output directory and `/`-separated, so the
+/// document can be imported into a directory anywhere, on any system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateExport {
+    /// See [`STATE_EXPORT_VERSION`]
+    pub version: u32,
+    /// RFC 3339 timestamp in UTC
+    pub exported_at: String,
+    /// Contents of `podcast.json`, if there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub podcast: Option<PodcastMetadata>,
+    /// Every downloaded episode, including retired ones
+    pub episodes: Vec<ExportedEpisode>,
+    /// Failure history, see [`FAILURES_FILENAME`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failures: BTreeMap<String, FailureRecord>,
+}
+
+/// A downloaded episode as listed in a [`StateExport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedEpisode {
+    /// The episode's metadata file, relative to the output directory
+    pub metadata_path: String,
+    #[serde(flatten)]
+    pub metadata: EpisodeMetadata,
+}
+
+/// Outcome of [`import_state`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateImport {
+    /// Number of episodes whose metadata file was written
+    pub imported: usize,
+    /// Number of episodes left alone, as their metadata file already exists
+    pub existing: usize,
+}
+
+/// Collect the state of an output directory, see [`StateExport`]
+///
+/// Unreadable metadata files are left out, like during a sync.
+pub fn export_state(output_dir: &Path) -> Result<StateExport, StateError> {
+    if !output_dir.is_dir() {
+        return Err(StateError::DirectoryNotFound(output_dir.to_path_buf()));
+    }
+
+    let episodes = episode_metadata_files(output_dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let metadata = read_episode_metadata(&path).ok()?;
+            Some(ExportedEpisode {
+                metadata_path: relative_name(&path, output_dir),
+                metadata,
+            })
+        })
+        .collect();
+
+    Ok(StateExport {
+        version: STATE_EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        podcast: read_podcast_metadata(output_dir).ok(),
+        episodes,
+        failures: read_failure_history(output_dir),
+    })
+}
+
+/// Recreate the state of an exported output directory in `output_dir`
+///
+/// Writes the metadata files of the exported episodes, so syncs into
+/// `output_dir` treat them as downloaded, whether or not their audio was
+/// moved along. Files already in `output_dir` are authoritative and kept;
+/// `podcast.json` is only written if missing, and failure records only
+/// added for episodes without one.
+pub fn import_state(export: &StateExport, output_dir: &Path) -> Result<StateImport, StateError> {
+    if export.version > STATE_EXPORT_VERSION {
+        return Err(StateError::UnsupportedExportVersion(export.version));
+    }
+    // Checked up front, so a rejected document changes nothing
+    if let Some(episode) = export
+        .episodes
+        .iter()
+        .find(|episode| !is_portable_path(&episode.metadata_path))
+    {
+        return Err(StateError::UnsafeExportPath(episode.metadata_path.clone()));
+    }
+
+    std::fs::create_dir_all(output_dir).map_err(|e| StateError::CreateDirectoryFailed {
+        path: output_dir.to_path_buf(),
+        source: e,
+    })?;
+
+    let mut result = StateImport::default();
+    for episode in &export.episodes {
+        let path = output_dir.join(&episode.metadata_path);
+        if path.exists() {
+            result.existing += 1;
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| StateError::CreateDirectoryFailed {
+                path: dir.to_path_buf(),
+                source: e,
+            })?;
+        }
+        save_episode_metadata(&episode.metadata, &path)?;
+        result.imported += 1;
+    }
+
+    if let Some(podcast) = &export.podcast
+        && read_podcast_metadata(output_dir).is_err()
+    {
+        save_podcast_metadata(podcast, output_dir)?;
+    }
+
+    if !export.failures.is_empty() {
+        let mut failures = read_failure_history(output_dir);
+        for (key, record) in &export.failures {
+            failures
+                .entry(key.clone())
+                .or_insert_with(|| record.clone());
+        }
+        let path = output_dir.join(FAILURES_FILENAME);
+        let json = serde_json::to_string_pretty(&failures).map_err(MetadataError::from)?;
+        std::fs::write(&path, json)
+            .map_err(|e| StateError::Metadata(MetadataError::WriteFailed { path, source: e }))?;
+    }
+
+    Ok(result)
+}
+
+/// Whether an exported path stays inside the output directory and names
+/// an episode metadata file
+fn is_portable_path(path: &str) -> bool {
+    path.ends_with(".json")
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{Enclosure, Episode};
+    use crate::metadata::write_episode_metadata;
+    use crate::progress::NoopReporter;
+    use crate::state::scan_output_dir;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn write_episode(path: &Path, guid: &str) {
+        let episode = Episode {
+            title: guid.to_string(),
+            description: None,
+            pub_date: None,
+            guid: Some(guid.to_string()),
+            enclosure: Enclosure {
+                url: Url::parse(&format!("https://example.com/{}.mp3", guid)).unwrap(),
+                length: None,
+                mime_type: None,
+            },
+            alternate_enclosures: vec![],
+            transcripts: vec![],
+            duration: None,
+            episode_number: None,
+            season_number: None,
+            language: None,
+            extensions: BTreeMap::new(),
+        };
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        write_episode_metadata(&episode, &format!("{}.mp3", guid), None, path).unwrap();
+    }
+
+    #[test]
+    fn export_survives_the_move_to_another_directory() {
+        let source = tempdir().unwrap();
+        write_episode(&source.path().join("ep-1.json"), "ep-1");
+        write_episode(&source.path().join("Season 01/ep-2.json"), "ep-2");
+
+        let json = serde_json::to_string(&export_state(source.path()).unwrap()).unwrap();
+        let export: StateExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(export.episodes.len(), 2);
+        assert!(
+            export
+                .episodes
+                .iter()
+                .any(|episode| episode.metadata_path == "Season 01/ep-2.json")
+        );
+
+        let target = tempdir().unwrap();
+        write_episode(&target.path().join("ep-1.json"), "ep-1");
+        let result = import_state(&export, target.path()).unwrap();
+        assert_eq!(
+            result,
+            StateImport {
+                imported: 1,
+                existing: 1
+            }
+        );
+
+        let state = scan_output_dir(target.path(), &NoopReporter::shared()).unwrap();
+        assert!(state.downloaded_guids.contains("ep-1"));
+        assert!(state.downloaded_guids.contains("ep-2"));
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_output_directory() {
+        let source = tempdir().unwrap();
+        write_episode(&source.path().join("ep-1.json"), "ep-1");
+        let mut export = export_state(source.path()).unwrap();
+        export.episodes[0].metadata_path = "../ep-1.json".to_string();
+
+        let target = tempdir().unwrap();
+        let result = import_state(&export, &target.path().join("podcast"));
+        assert!(matches!(result, Err(StateError::UnsafeExportPath(_))));
+        assert!(!target.path().join("ep-1.json").exists());
+    }
+}
@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use url::Url;
+
+use crate::episode::sanitize_title;
+
+/// A feed from a newline-delimited feed list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedListEntry {
+    pub feed_url: String,
+    /// Output directory, relative to the library root unless absolute
+    pub output_dir: Option<PathBuf>,
+}
+
+/// Parse a newline-delimited list of feeds
+///
+/// Each line holds a feed URL, optionally followed by a tab and the output
+/// directory. Blank lines and lines starting with `#` are skipped.
+pub fn parse_feed_list(content: &str) -> Vec<FeedListEntry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once('\t') {
+            Some((feed_url, output_dir)) if !output_dir.trim().is_empty() => FeedListEntry {
+                feed_url: feed_url.trim().to_string(),
+                output_dir: Some(PathBuf::from(output_dir.trim())),
+            },
+            _ => FeedListEntry {
+                feed_url: line.to_string(),
+                output_dir: None,
+            },
+        })
+        .collect()
+}
+
+/// Directory name for a listed feed without an explicit output directory
+///
+/// Derived from the feed's host and path alone, so it stays the same no
+/// matter where the feed appears in the list.
+pub fn feed_dir_name(feed_url: &str) -> String {
+    let name = match Url::parse(feed_url) {
+        Ok(url) => std::iter::once(url.host_str().unwrap_or_default())
+            .chain(url.path_segments().into_iter().flatten())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-"),
+        Err(_) => feed_url.to_string(),
+    };
+
+    let name = sanitize_title(&name);
+    if name.is_empty() {
+        "podcast".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_urls_with_optional_output_dir() {
+        let list = "https://example.com/a.xml\n\
+                    \n\
+                    # comment\n\
+                    https://example.com/b.xml\tShows/B\n\
+                    https://example.com/c.xml\t\n";
+
+        assert_eq!(
+            parse_feed_list(list),
+            vec![
+                FeedListEntry {
+                    feed_url: "https://example.com/a.xml".to_string(),
+                    output_dir: None,
+                },
+                FeedListEntry {
+                    feed_url: "https://example.com/b.xml".to_string(),
+                    output_dir: Some(PathBuf::from("Shows/B")),
+                },
+                FeedListEntry {
+                    feed_url: "https://example.com/c.xml".to_string(),
+                    output_dir: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dir_name_from_host_and_path() {
+        assert_eq!(
+            feed_dir_name("https://feeds.example.com/show/feed.xml"),
+            "feeds.example.com-show-feed.xml"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, Datelike};
+use url::Url;
+
+use crate::episode::{sanitize_title, slug};
+use crate::error::SubscriptionError;
+use crate::feed::Podcast;
+use crate::metadata::{PodcastMetadata, episode_metadata_files, read_episode_metadata};
+
+use super::canonical_feed_url;
+
+/// File in a library root recording which directory each feed syncs into
+pub const LIBRARY_INDEX_FILENAME: &str = ".podpull-library.json";
+
+const PLACEHOLDERS: &[&str] = &["title", "author", "year", "host"];
+
+/// Template for naming podcast directories, such as `{author} - {title}`
+///
+/// Placeholders are `{title}`, `{author}`, `{year}` (of the oldest episode)
+/// and `{host}` (of the feed URL). Appending `:slug` (`{title:slug}`) turns
+/// the value into lowercase ASCII words joined by dashes. Missing values
+/// render empty, and separators left dangling at either end are trimmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirTemplate(String);
+
+impl DirTemplate {
+    /// Parse a template, rejecting unknown or unclosed placeholders
+    pub fn parse(template: &str) -> Result<Self, SubscriptionError> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| SubscriptionError::InvalidTemplate(template.to_string()))?;
+            let placeholder = &rest[start + 1..start + end];
+            let (name, style) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+            if !PLACEHOLDERS.contains(&name) || !matches!(style, "" | "slug") {
+                return Err(SubscriptionError::InvalidTemplate(template.to_string()));
+            }
+            rest = &rest[start + end + 1..];
+        }
+
+        Ok(Self(template.to_string()))
+    }
+
+    /// Directory name for a podcast (empty if every placeholder was empty)
+    pub fn render(&self, podcast: &Podcast) -> String {
+        let year = podcast
+            .episodes
+            .iter()
+            .filter_map(|episode| episode.pub_date)
+            .min()
+            .map(|date| date.year());
+
+        self.render_values(
+            &podcast.title,
+            podcast.author.as_deref(),
+            year,
+            podcast.feed_url.host_str(),
+        )
+    }
+
+    /// Directory name for a synced podcast, from its `podcast.json` and the
+    /// episodes downloaded into `podcast_dir`
+    pub(crate) fn render_synced(&self, metadata: &PodcastMetadata, podcast_dir: &Path) -> String {
+        let year = episode_metadata_files(podcast_dir)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|path| read_episode_metadata(path).ok()?.pub_date)
+            .filter_map(|date| DateTime::parse_from_rfc3339(&date).ok())
+            .min()
+            .map(|date| date.year());
+        let host = Url::parse(&metadata.feed_url).ok();
+
+        self.render_values(
+            &metadata.title,
+            metadata.author.as_deref(),
+            year,
+            host.as_ref().and_then(Url::host_str),
+        )
+    }
+
+    fn render_values(
+        &self,
+        title: &str,
+        author: Option<&str>,
+        year: Option<i32>,
+        host: Option<&str>,
+    ) -> String {
+        let mut rendered = String::new();
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find('{') {
+            // Validated by parse
+            let end = start + rest[start..].find('}').unwrap_or(0);
+            rendered.push_str(&rest[..start]);
+
+            let placeholder = &rest[start + 1..end];
+            let (name, style) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+            let value = match name {
+                "title" => Some(title.to_string()),
+                "author" => author.map(String::from),
+                "year" => year.map(|year| year.to_string()),
+                "host" => host.map(String::from),
+                _ => None,
+            }
+            .unwrap_or_default();
+
+            if style == "slug" {
+                rendered.push_str(&slug(&value));
+            } else {
+                rendered.push_str(&value);
+            }
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+
+        sanitize_title(&rendered)
+    }
+}
+
+/// Assigns podcast directories in a library, keeping earlier choices
+///
+/// Directory names are recorded per feed (by [`canonical_feed_url`]) in
+/// [`LIBRARY_INDEX_FILENAME`], so a feed keeps its directory even when its
+/// title or the naming template changes later.
+pub(crate) struct DirectoryNamer {
+    index: BTreeMap<String, String>,
+    used: HashSet<String>,
+    changed: bool,
+}
+
+impl DirectoryNamer {
+    /// Start from the library's index (empty if missing or unreadable)
+    pub(crate) fn load(library_root: &Path) -> Self {
+        let index = std::fs::read(library_root.join(LIBRARY_INDEX_FILENAME))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            index,
+            used: HashSet::new(),
+            changed: false,
+        }
+    }
+
+    /// The directory recorded for a feed, unless another feed of this run
+    /// already took it
+    pub(crate) fn recorded(&self, feed_url: &str) -> Option<&str> {
+        self.index
+            .get(&canonical_feed_url(feed_url))
+            .map(String::as_str)
+            .filter(|name| !self.used.contains(*name))
+    }
+
+    /// Claim a directory for a feed, based on `name`
+    ///
+    /// Names used earlier in this run or recorded for other feeds get a
+    /// numeric suffix. The first name claimed for a feed is recorded.
+    pub(crate) fn claim(&mut self, feed_url: &str, name: &str) -> String {
+        let key = canonical_feed_url(feed_url);
+
+        let taken = |candidate: &String| {
+            self.used.contains(candidate)
+                || self
+                    .index
+                    .iter()
+                    .any(|(other, dir)| *other != key && dir == candidate)
+        };
+        let mut candidate = name.to_string();
+        let mut counter = 2;
+        while taken(&candidate) {
+            candidate = format!("{} ({})", name, counter);
+            counter += 1;
+        }
+
+        self.used.insert(candidate.clone());
+        if let Entry::Vacant(entry) = self.index.entry(key) {
+            entry.insert(candidate.clone());
+            self.changed = true;
+        }
+        candidate
+    }
+
+    /// Point every feed recorded in directory `from` to directory `to`
+    pub(crate) fn rename(&mut self, from: &str, to: &str) {
+        for dir in self.index.values_mut().filter(|dir| *dir == from) {
+            *dir = to.to_string();
+            self.changed = true;
+        }
+    }
+
+    /// Write the index back if feeds were recorded or moved
+    pub(crate) fn save(&self, library_root: &Path) -> std::io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(library_root)?;
+        let json = serde_json::to_string_pretty(&self.index).map_err(std::io::Error::other)?;
+        std::fs::write(library_root.join(LIBRARY_INDEX_FILENAME), json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn podcast(title: &str, author: Option<&str>) -> Podcast {
+        Podcast {
+            title: title.to_string(),
+            description: None,
+            link: None,
+            author: author.map(String::from),
+            image_url: None,
+            feed_url: Url::parse("https://feeds.example.com/show.xml").unwrap(),
+            new_feed_url: None,
+            funding: vec![],
+            episodes: vec![],
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_and_slugs() {
+        let template = DirTemplate::parse("{author} - {title:slug} ({host})").unwrap();
+        assert_eq!(
+            template.render(&podcast("Tech Talk: Weekly!", Some("Jane"))),
+            "Jane - tech-talk-weekly (feeds.example.com)"
+        );
+
+        let template = DirTemplate::parse("{author} - {title}").unwrap();
+        assert_eq!(template.render(&podcast("Show", None)), "Show");
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        assert!(DirTemplate::parse("{name}").is_err());
+        assert!(DirTemplate::parse("{title:upper}").is_err());
+        assert!(DirTemplate::parse("{title").is_err());
+    }
+
+    #[test]
+    fn keeps_recorded_directories_across_runs() {
+        let dir = tempdir().unwrap();
+
+        let mut namer = DirectoryNamer::load(dir.path());
+        assert_eq!(namer.claim("https://a.example.com/feed", "Show"), "Show");
+        assert_eq!(
+            namer.claim("https://b.example.com/feed", "Show"),
+            "Show (2)"
+        );
+        namer.save(dir.path()).unwrap();
+
+        let mut namer = DirectoryNamer::load(dir.path());
+        assert_eq!(
+            namer.recorded("http://b.example.com/feed/"),
+            Some("Show (2)")
+        );
+        // A new feed may not take a directory recorded for another one
+        assert_eq!(
+            namer.claim("https://c.example.com/feed", "Show"),
+            "Show (3)"
+        );
+    }
+}
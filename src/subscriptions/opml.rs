@@ -0,0 +1,182 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use quick_xml::Reader;
+use quick_xml::encoding::Decoder;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::error::SubscriptionError;
+
+use super::Subscription;
+
+/// Parse the feed subscriptions from an OPML document
+///
+/// Every `<outline>` with an `xmlUrl` attribute is a subscription, no matter
+/// how deeply it is nested, so category folders from AntennaPod or Overcast
+/// exports are flattened. The title is taken from `title`, falling back to
+/// `text`.
+pub fn parse_opml(content: &[u8]) -> Result<Vec<Subscription>, SubscriptionError> {
+    let mut reader = Reader::from_reader(content);
+    let mut buf = Vec::new();
+    let mut subscriptions = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"outline" =>
+            {
+                if let Some(subscription) = parse_outline(&element, reader.decoder())? {
+                    subscriptions.push(subscription);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(subscriptions)
+}
+
+/// Read and parse an OPML file
+pub fn read_opml_file(path: &Path) -> Result<Vec<Subscription>, SubscriptionError> {
+    let content = std::fs::read(path).map_err(|e| SubscriptionError::ReadFailed {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    parse_opml(&content)
+}
+
+/// Generate an OPML 2.0 document listing the given subscriptions
+///
+/// Subscriptions without a title use their feed URL as outline text.
+pub fn generate_opml(subscriptions: &[Subscription], title: &str) -> String {
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    opml.push_str("<opml version=\"2.0\">\n");
+    opml.push_str(&format!(
+        "  <head>\n    <title>{}</title>\n  </head>\n",
+        escape(title)
+    ));
+    opml.push_str("  <body>\n");
+
+    for subscription in subscriptions {
+        let text = escape(
+            subscription
+                .title
+                .as_deref()
+                .unwrap_or(&subscription.feed_url),
+        );
+        opml.push_str(&format!(
+            "    <outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\"/>\n",
+            text,
+            text,
+            escape(subscription.feed_url.as_str())
+        ));
+    }
+
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+fn parse_outline(
+    element: &BytesStart,
+    decoder: Decoder,
+) -> Result<Option<Subscription>, SubscriptionError> {
+    let mut feed_url = None;
+    let mut title = None;
+    let mut text = None;
+
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let value = attribute
+            .decode_and_unescape_value(decoder)?
+            .trim()
+            .to_string();
+        if value.is_empty() {
+            continue;
+        }
+
+        match attribute.key.as_ref() {
+            b"xmlUrl" => feed_url = Some(value),
+            b"title" => title = Some(value),
+            b"text" => text = Some(value),
+            _ => {}
+        }
+    }
+
+    Ok(feed_url.map(|feed_url| Subscription {
+        title: title.or(text),
+        feed_url,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_opml_finds_nested_outlines() {
+        let opml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="Tech">
+      <outline type="rss" text="Show &amp; Tell" xmlUrl="https://example.com/show.xml"/>
+    </outline>
+    <outline type="rss" text="Fallback" title="Other Show" xmlUrl="https://example.org/feed"/>
+    <outline type="rss" xmlUrl="https://example.net/untitled.xml"></outline>
+  </body>
+</opml>"#;
+
+        let subscriptions = parse_opml(opml.as_bytes()).unwrap();
+
+        assert_eq!(
+            subscriptions,
+            vec![
+                Subscription {
+                    title: Some("Show & Tell".to_string()),
+                    feed_url: "https://example.com/show.xml".to_string(),
+                },
+                Subscription {
+                    title: Some("Other Show".to_string()),
+                    feed_url: "https://example.org/feed".to_string(),
+                },
+                Subscription {
+                    title: None,
+                    feed_url: "https://example.net/untitled.xml".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_opml_roundtrips_through_parse() {
+        let subscriptions = vec![
+            Subscription {
+                title: Some("Q&A \"Live\" <Show>".to_string()),
+                feed_url: "https://example.com/feed.xml?a=1&b=2".to_string(),
+            },
+            Subscription {
+                title: None,
+                feed_url: "https://example.org/feed".to_string(),
+            },
+        ];
+
+        let opml = generate_opml(&subscriptions, "podpull library");
+        let parsed = parse_opml(opml.as_bytes()).unwrap();
+
+        assert_eq!(parsed[0], subscriptions[0]);
+        assert_eq!(parsed[1].title.as_deref(), Some("https://example.org/feed"));
+        assert_eq!(parsed[1].feed_url, "https://example.org/feed");
+    }
+
+    #[test]
+    fn parse_opml_rejects_malformed_xml() {
+        let result = parse_opml(b"<opml><body><outline xmlUrl=\"x></body></opml>");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::SubscriptionError;
+use crate::metadata::read_podcast_metadata;
+
+use super::naming::{DirTemplate, DirectoryNamer};
+use super::{Subscription, subscription_dir_name};
+
+/// A podcast directory whose name no longer matches the podcast's title
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryRename {
+    pub title: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Find podcast directories of a library that are named after an old title
+///
+/// Compares every podcast directory (see [`scan_library`](super::scan_library))
+/// with the name `template` renders from its `podcast.json`, or without a
+/// template (or a name) the one [`subscription_dir_name`] gives its current
+/// title. Renames onto an existing directory, or onto a name another rename
+/// already takes, are left out.
+pub fn plan_library_renames(
+    library_root: &Path,
+    template: Option<&DirTemplate>,
+) -> Result<Vec<LibraryRename>, SubscriptionError> {
+    let read_failed = |e| SubscriptionError::LibraryReadFailed {
+        path: library_root.to_path_buf(),
+        source: e,
+    };
+
+    let mut directories = std::fs::read_dir(library_root)
+        .map_err(read_failed)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_failed)?;
+    directories.retain(|path| path.is_dir());
+    directories.sort();
+
+    let mut claimed = HashSet::new();
+    let mut renames = Vec::new();
+
+    for from in directories {
+        let Ok(metadata) = read_podcast_metadata(&from) else {
+            continue;
+        };
+        let name = template
+            .map(|template| template.render_synced(&metadata, &from))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| {
+                subscription_dir_name(&Subscription {
+                    title: Some(metadata.title.clone()),
+                    feed_url: metadata.feed_url.clone(),
+                })
+            });
+        let to = library_root.join(&name);
+
+        if to == from || to.exists() || !claimed.insert(name) {
+            continue;
+        }
+        renames.push(LibraryRename {
+            title: metadata.title,
+            from,
+            to,
+        });
+    }
+
+    Ok(renames)
+}
+
+/// Rename a podcast directory and update the library index to match
+///
+/// Refuses to overwrite an existing directory. If the index cannot be
+/// written, the directory is moved back so both stay consistent.
+pub fn rename_library_dir(
+    library_root: &Path,
+    rename: &LibraryRename,
+) -> Result<(), SubscriptionError> {
+    let rename_failed = |e| SubscriptionError::RenameFailed {
+        from: rename.from.clone(),
+        to: rename.to.clone(),
+        source: e,
+    };
+
+    if rename.to.exists() {
+        return Err(rename_failed(std::io::Error::from(
+            std::io::ErrorKind::AlreadyExists,
+        )));
+    }
+
+    std::fs::rename(&rename.from, &rename.to).map_err(rename_failed)?;
+
+    let mut namer = DirectoryNamer::load(library_root);
+    if let (Some(from), Some(to)) = (dir_name(&rename.from), dir_name(&rename.to)) {
+        namer.rename(&from, &to);
+    }
+    if let Err(e) = namer.save(library_root) {
+        let _ = std::fs::rename(&rename.to, &rename.from);
+        return Err(rename_failed(e));
+    }
+
+    Ok(())
+}
+
+fn dir_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{PodcastMetadata, save_podcast_metadata};
+    use crate::subscriptions::LIBRARY_INDEX_FILENAME;
+    use tempfile::tempdir;
+
+    fn podcast_dir(library: &Path, name: &str, title: &str, feed_url: &str) {
+        authored_podcast_dir(library, name, title, None, feed_url);
+    }
+
+    fn authored_podcast_dir(
+        library: &Path,
+        name: &str,
+        title: &str,
+        author: Option<&str>,
+        feed_url: &str,
+    ) {
+        let dir = library.join(name);
+        std::fs::create_dir(&dir).unwrap();
+        let metadata = PodcastMetadata {
+            title: title.to_string(),
+            description: None,
+            link: None,
+            author: author.map(String::from),
+            image_url: None,
+            feed_url: feed_url.to_string(),
+            updated_at: None,
+            etag: None,
+            last_modified: None,
+            funding: vec![],
+        };
+        save_podcast_metadata(&metadata, &dir).unwrap();
+    }
+
+    #[test]
+    fn renames_directories_named_after_old_titles() {
+        let library = tempdir().unwrap();
+        podcast_dir(
+            library.path(),
+            "Old Show",
+            "New Show",
+            "https://a.example.com/feed",
+        );
+        podcast_dir(library.path(), "Same", "Same", "https://b.example.com/feed");
+        std::fs::write(
+            library.path().join(LIBRARY_INDEX_FILENAME),
+            r#"{"a.example.com/feed": "Old Show"}"#,
+        )
+        .unwrap();
+
+        let renames = plan_library_renames(library.path(), None).unwrap();
+        assert_eq!(
+            renames,
+            vec![LibraryRename {
+                title: "New Show".to_string(),
+                from: library.path().join("Old Show"),
+                to: library.path().join("New Show"),
+            }]
+        );
+
+        rename_library_dir(library.path(), &renames[0]).unwrap();
+
+        assert!(library.path().join("New Show/podcast.json").exists());
+        assert!(!library.path().join("Old Show").exists());
+        let namer = DirectoryNamer::load(library.path());
+        assert_eq!(
+            namer.recorded("https://a.example.com/feed"),
+            Some("New Show")
+        );
+    }
+
+    #[test]
+    fn skips_renames_onto_existing_directories() {
+        let library = tempdir().unwrap();
+        podcast_dir(
+            library.path(),
+            "Old Show",
+            "Show",
+            "https://a.example.com/feed",
+        );
+        std::fs::create_dir(library.path().join("Show")).unwrap();
+
+        assert!(
+            plan_library_renames(library.path(), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn renames_templated_libraries_by_their_template() {
+        let library = tempdir().unwrap();
+        authored_podcast_dir(
+            library.path(),
+            "Jane - Show",
+            "Show",
+            Some("Jane"),
+            "https://a.example.com/feed",
+        );
+        authored_podcast_dir(
+            library.path(),
+            "Jane - Old Title",
+            "New Title",
+            Some("Jane"),
+            "https://b.example.com/feed",
+        );
+        let template = DirTemplate::parse("{author} - {title}").unwrap();
+
+        let renames = plan_library_renames(library.path(), Some(&template)).unwrap();
+        assert_eq!(
+            renames,
+            vec![LibraryRename {
+                title: "New Title".to_string(),
+                from: library.path().join("Jane - Old Title"),
+                to: library.path().join("Jane - New Title"),
+            }]
+        );
+    }
+}
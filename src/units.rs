@@ -0,0 +1,316 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Human-readable sizes and durations for options, such as `1.5G` or `45m`
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::UnitError;
+
+const SIZE_UNITS: &[(char, u64)] = &[
+    ('T', 1 << 40),
+    ('G', 1 << 30),
+    ('M', 1 << 20),
+    ('K', 1 << 10),
+];
+
+const DURATION_UNITS: &[(&str, u64)] = &[
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+/// A number of bytes, written like `250k`, `1.5G` or `4096`
+///
+/// Suffixes `K`, `M`, `G` and `T` are binary multiples (`1K` = 1024) and
+/// may be followed by `B` or `iB`; letter case does not matter. Displays
+/// with the largest suffix that divides the size evenly. Serializes as that
+/// string, and deserializes from strings or plain numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn new(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = UnitError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || UnitError::InvalidSize(value.to_string());
+
+        let upper = value.trim().to_ascii_uppercase();
+        let number = upper
+            .strip_suffix("IB")
+            .or_else(|| upper.strip_suffix('B'))
+            .unwrap_or(&upper);
+
+        let (number, multiplier) = match SIZE_UNITS
+            .iter()
+            .find(|(suffix, _)| number.ends_with(*suffix))
+        {
+            Some((_, multiplier)) => (&number[..number.len() - 1], *multiplier),
+            None => (number, 1),
+        };
+
+        let number: f64 = number.trim().parse().map_err(|_| invalid())?;
+        let bytes = number * multiplier as f64;
+        if !bytes.is_finite() || bytes < 0.0 || bytes > u64::MAX as f64 {
+            return Err(invalid());
+        }
+
+        Ok(Self(bytes.round() as u64))
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match SIZE_UNITS
+            .iter()
+            .find(|(_, multiplier)| self.0 != 0 && self.0.is_multiple_of(*multiplier))
+        {
+            Some((suffix, multiplier)) => write!(f, "{}{}", self.0 / multiplier, suffix),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor::<Self>::new("a size like \"1.5G\""))
+    }
+}
+
+/// A duration, written like `45m`, `1h30m`, `1.5h` or `500ms`
+///
+/// Units are `d`, `h`, `m`, `s` and `ms`; a bare number counts as seconds.
+/// Displays in the same notation (`1h30m`). Serializes as that string, and
+/// deserializes from strings or plain numbers of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(Duration);
+
+impl HumanDuration {
+    pub const fn new(duration: Duration) -> Self {
+        Self(duration)
+    }
+
+    pub const fn duration(self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl From<u64> for HumanDuration {
+    /// A number of seconds
+    fn from(seconds: u64) -> Self {
+        Self(Duration::from_secs(seconds))
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = UnitError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || UnitError::InvalidDuration(value.to_string());
+
+        let trimmed = value.trim().to_ascii_lowercase();
+        if trimmed.is_empty() {
+            return Err(invalid());
+        }
+        if let Ok(seconds) = trimmed.parse::<f64>() {
+            return seconds_to_duration(seconds).ok_or_else(invalid);
+        }
+
+        let mut millis = 0.0;
+        let mut rest = trimmed.as_str();
+        while !rest.is_empty() {
+            let unit_start = rest
+                .find(|c: char| c.is_ascii_alphabetic())
+                .ok_or_else(invalid)?;
+            let number: f64 = rest[..unit_start].trim().parse().map_err(|_| invalid())?;
+
+            let unit_end = rest[unit_start..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .map_or(rest.len(), |end| unit_start + end);
+            let (_, multiplier) = DURATION_UNITS
+                .iter()
+                .find(|(unit, _)| *unit == &rest[unit_start..unit_end])
+                .ok_or_else(invalid)?;
+
+            millis += number * *multiplier as f64;
+            rest = rest[unit_end..].trim_start();
+        }
+
+        seconds_to_duration(millis / 1000.0).ok_or_else(invalid)
+    }
+}
+
+fn seconds_to_duration(seconds: f64) -> Option<HumanDuration> {
+    Duration::try_from_secs_f64(seconds).ok().map(HumanDuration)
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut millis = self.0.as_millis();
+        if millis == 0 {
+            return f.write_str("0s");
+        }
+
+        for (unit, multiplier) in DURATION_UNITS {
+            let multiplier = u128::from(*multiplier);
+            if millis >= multiplier {
+                write!(f, "{}{}", millis / multiplier, unit)?;
+                millis %= multiplier;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor::<Self>::new("a duration like \"45m\""))
+    }
+}
+
+/// Deserializes a unit from its string notation or a plain number
+struct UnitVisitor<T> {
+    expecting: &'static str,
+    unit: std::marker::PhantomData<T>,
+}
+
+impl<T> UnitVisitor<T> {
+    fn new(expecting: &'static str) -> Self {
+        Self {
+            expecting,
+            unit: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Visitor<'_> for UnitVisitor<T>
+where
+    T: FromStr<Err = UnitError> + From<u64>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        Ok(T::from(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        u64::try_from(value)
+            .map(T::from)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_with_binary_suffixes() {
+        assert_eq!("4096".parse(), Ok(ByteSize::new(4096)));
+        assert_eq!("250k".parse(), Ok(ByteSize::new(250 * 1024)));
+        assert_eq!("1.5G".parse(), Ok(ByteSize::new(1536 * 1024 * 1024)));
+        assert_eq!("2 MiB".parse(), Ok(ByteSize::new(2 * 1024 * 1024)));
+        assert_eq!("10MB".parse(), Ok(ByteSize::new(10 * 1024 * 1024)));
+        assert!("fast".parse::<ByteSize>().is_err());
+        assert!("-1K".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn displays_sizes_with_largest_even_suffix() {
+        assert_eq!(ByteSize::new(1536 * 1024 * 1024).to_string(), "1536M");
+        assert_eq!(ByteSize::new(2 << 30).to_string(), "2G");
+        assert_eq!(ByteSize::new(1000).to_string(), "1000");
+        assert_eq!(ByteSize::new(0).to_string(), "0");
+    }
+
+    #[test]
+    fn parses_durations() {
+        let parse = |value: &str| value.parse::<HumanDuration>().map(Duration::from);
+
+        assert_eq!(parse("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse("45m"), Ok(Duration::from_secs(45 * 60)));
+        assert_eq!(parse("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse("1.5h"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse("2d"), Ok(Duration::from_secs(2 * 86_400)));
+        assert!(parse("").is_err());
+        assert!(parse("5 fortnights").is_err());
+        assert!(parse("m").is_err());
+    }
+
+    #[test]
+    fn displays_durations_in_parseable_notation() {
+        let duration = HumanDuration::new(Duration::from_millis(5_400_250));
+        assert_eq!(duration.to_string(), "1h30m250ms");
+        assert_eq!(duration.to_string().parse(), Ok(duration));
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let size: ByteSize = serde_json::from_str("\"1.5G\"").unwrap();
+        assert_eq!(serde_json::to_string(&size).unwrap(), "\"1536M\"");
+        assert_eq!(
+            serde_json::from_str::<ByteSize>("1024").unwrap(),
+            ByteSize::new(1024)
+        );
+
+        let duration: HumanDuration = serde_json::from_str("90").unwrap();
+        assert_eq!(serde_json::to_string(&duration).unwrap(), "\"1m30s\"");
+    }
+}